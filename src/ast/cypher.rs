@@ -1,9 +1,13 @@
 use crate::{
-    common::{Expr, FieldValue, NameSpace, Property, RelationshipDirection, Variable},
+    common::{
+        ConstraintKind, Expr, FieldValue, NameSpace, PathMode, PathSelector, Property,
+        RelationshipDirection, Variable,
+    },
     meta::Label,
 };
 
 use paste::paste;
+use serde::{Deserialize, Serialize};
 
 macro_rules! cypher_nodes_impl {
     ( $(
@@ -11,7 +15,7 @@ macro_rules! cypher_nodes_impl {
         $name:ident { $( $(#[doc = $param_doc:expr])* $param:ident : $type:ty, )* },
     )* ) => {
         paste! {
-            #[derive(Clone)]
+            #[derive(Clone, Serialize, Deserialize)]
             pub enum CypherNode {
                 $(
                     $(#[doc = $node_doc])*
@@ -71,6 +75,17 @@ macro_rules! cypher_nodes_impl {
                 }
             }
 
+            impl CypherNode {
+                /// Names of all the variants.
+                pub const VARIANTS: &'static [&'static str] = &[$(stringify!($name),)*];
+
+                pub fn name(&self) -> &'static str {
+                    match self {
+                        $(CypherNode::$name { .. } => stringify!($name),)*
+                    }
+                }
+            }
+
             impl std::fmt::Debug for CypherNode {
                 fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     match self {
@@ -143,7 +158,7 @@ cypher_nodes_impl! {
 
     /// ReadingClause
     ///
-    /// Match or Unwind or InqueryCall
+    /// Match or Unwind or InqueryCall or CallSubquery or LoadCsv
     ReadingClause {
         reading_clause: Box<CypherNode>,
     },
@@ -205,6 +220,28 @@ cypher_nodes_impl! {
         yield_items: Option<Box<CypherNode>>,
     },
 
+    /// CallSubquery
+    ///
+    /// `CALL` `{` (`WITH` Variable+)? RegularQuery `}` (`IN TRANSACTIONS` (`OF` n `ROWS`)?)?
+    CallSubquery {
+        // importing WITH: outer variables visible inside the subquery.
+        import_variables: Vec<Variable>,
+        query: Box<CypherNode>,
+        // IN TRANSACTIONS (OF rows ROWS)?
+        in_transactions: (bool, Option<i32>),
+    },
+
+    /// LoadCsv
+    ///
+    /// `LOAD CSV` (`WITH HEADERS`)? `FROM` url `AS` Variable (`FIELDTERMINATOR` string)?
+    /// of a csv fixture, the dialect decides the url of the file.
+    LoadCsv {
+        with_headers: bool,
+        file: String,
+        variable: Variable,
+        field_terminator: Option<String>,
+    },
+
     /// Create
     Create {
         pattern: Box<CypherNode>,
@@ -216,6 +253,22 @@ cypher_nodes_impl! {
         merge_actions: Vec<(String, Box<CypherNode>)>,
     },
 
+    /// Foreach: `FOREACH` `(` Variable `IN` Expression `|` UpdatingClause+ `)`
+    Foreach {
+        variable: Variable,
+        expression: Expr,
+        updating_clauses: Vec<Box<CypherNode>>,
+    },
+
+    /// ConditionalUpdate: UpdatingClause+ that only run when the condition holds.
+    ///
+    /// `FOREACH` `(` Variable `IN` `CASE WHEN` condition `THEN [1] ELSE [] END` `|` UpdatingClause+ `)`
+    ConditionalUpdate {
+        variable: Variable,
+        condition: Expr,
+        updating_clauses: Vec<Box<CypherNode>>,
+    },
+
     /// Delete
     Delete {
         is_detach: bool,
@@ -257,6 +310,51 @@ cypher_nodes_impl! {
         property_remove: Vec<Expr>,
     },
 
+    /// SchemaCommand: CreateIndex | DropIndex | CreateConstraint | DropConstraint | ShowSchema
+    SchemaCommand {
+        command: Box<CypherNode>,
+    },
+
+    /// CreateIndex
+    ///
+    /// `CREATE INDEX` name (`IF NOT EXISTS`)? `FOR` (NodePattern|RelationshipPattern) `ON` `(` PropertyExpression+ `)`
+    CreateIndex {
+        name: String,
+        if_not_exists: bool,
+        variable: Variable,
+        label: Label,
+        properties: Vec<Property>,
+    },
+
+    /// DropIndex: `DROP INDEX` name (`IF EXISTS`)?
+    DropIndex {
+        name: String,
+        if_exists: bool,
+    },
+
+    /// CreateConstraint
+    ///
+    /// `CREATE CONSTRAINT` name (`IF NOT EXISTS`)? `FOR` (NodePattern|RelationshipPattern) `REQUIRE` PropertyExpression+ ConstraintKind
+    CreateConstraint {
+        name: String,
+        if_not_exists: bool,
+        variable: Variable,
+        label: Label,
+        properties: Vec<Property>,
+        kind: ConstraintKind,
+    },
+
+    /// DropConstraint: `DROP CONSTRAINT` name (`IF EXISTS`)?
+    DropConstraint {
+        name: String,
+        if_exists: bool,
+    },
+
+    /// ShowSchema: `SHOW` (`INDEXES`|`CONSTRAINTS`)
+    ShowSchema {
+        show_constraints: bool,
+    },
+
     /// Pattern
     ///
     /// Vec<PatternPart>
@@ -267,15 +365,19 @@ cypher_nodes_impl! {
     /// PatternPart
     ///
     /// Variable = AnonymousPatternPart
-    /// AnonymousPatternPart : PatternElement
+    /// AnonymousPatternPart : PathSelector? PathMode? PatternElement
     PatternPart {
         var: Option<Variable>,
+        /// path selector of a MATCH pattern, e.g. `SHORTEST 2`.
+        selector: Option<PathSelector>,
+        /// GQL path mode of a MATCH pattern.
+        path_mode: Option<PathMode>,
         pattern_element: Box<CypherNode>,
     },
 
     /// PatternElement
     ///
-    /// Vec<(NodePattern, Vec<(RelationShipPattern, NodePattern)>)>
+    /// Vec<(NodePattern, Vec<(RelationShipPattern|QuantifiedPathPattern, NodePattern)>)>
     PatternElement {
         parenthesis: bool,
         pattern_element: (Box<CypherNode>, Vec<(Box<CypherNode>, Box<CypherNode>)>),
@@ -298,6 +400,17 @@ cypher_nodes_impl! {
         properties: Option<(Property, FieldValue)>,
     },
 
+    /// QuantifiedPathPattern: `(` PatternElement (`WHERE` Expression)? `)` Quantifier?
+    ///
+    /// Takes the place of a relationship between two node patterns, a grouped
+    /// sub-pattern without quantifier.
+    QuantifiedPathPattern {
+        pattern_element: Box<CypherNode>,
+        where_clause: Option<Expr>,
+        /// (lower, upper) bounds of `{m,n}`, `*` and `+`.
+        quantifier: Option<(Option<i32>, Option<i32>)>,
+    },
+
 }
 
 impl From<Box<CypherNode>> for CypherNode {
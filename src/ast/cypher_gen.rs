@@ -4,10 +4,12 @@ use super::{
 };
 use crate::{
     common::{
-        constants, DataKind, Diagnostic, Expr, ExprKind, NameSpace, RandomGenerator,
-        RelationshipDirection, VariableGenerator,
+        constants, is_write_procedure, CaseAlternative, ConstraintKind, DataKind, Diagnostic, Expr,
+        ExprKind, Literal, NameSpace, PathMode, PathSelector, Property, RandomGenerator,
+        RelationshipDirection, Variable, VariableGenerator,
     },
-    meta::GraphSchema,
+    config::{Dialect, StressConfig, Weights},
+    meta::{GraphSchema, Label},
 };
 
 pub struct CypherGenerator {
@@ -16,41 +18,166 @@ pub struct CypherGenerator {
     pub limit: i32,
     pub graph_schema: GraphSchema,
     pub variables: VariableGenerator,
+    // csv fixtures readable by LOAD CSV, empty disables LOAD CSV.
+    pub csv_files: Vec<String>,
+    // inside a CALL { } subquery.
+    in_subquery: bool,
+    // generating the pattern of a MATCH clause.
+    in_match: bool,
+    // initial limit of each query.
+    pub query_limit: i32,
+    pub weights: Weights,
+    // no updating clauses, write procedures or DDL, subqueries included.
+    pub read_only: bool,
+    // updating clauses, write procedures and DDL of the last query.
+    pub writes: u32,
+    // syntax of the target, restricts the weights and DDL, see `with_dialect`.
+    pub(super) dialect: Dialect,
+    // queries run in an explicit transaction, which can not hold `CALL { } IN TRANSACTIONS`.
+    pub explicit_transaction: bool,
+    // oversized constructs to exhaust the resources of the engine.
+    pub stress: Option<StressConfig>,
+    // generating an expression of literals only, see `constant_query`.
+    pub(super) constant: bool,
+    // result fields of the last generated procedure, the ones YIELD can name.
+    procedure_fields: &'static [&'static str],
 }
 
 impl CypherGenerator {
+    #[cfg(test)]
     pub fn new_schema(graph_schema: &GraphSchema) -> Self {
+        Self::with_dialect(graph_schema, Dialect::default(), Weights::default())
+    }
+
+    /// A generator of the dialect, with the weights restricted to its productions.
+    pub fn with_dialect(graph_schema: &GraphSchema, dialect: Dialect, weights: Weights) -> Self {
         CypherGenerator {
             graph_schema: graph_schema.clone(),
             random: RandomGenerator::new(),
             variables: VariableGenerator::new(),
             limit: constants::DEFAULT_QUERY_LIMIT,
+            csv_files: vec![],
+            in_subquery: false,
+            in_match: false,
+            query_limit: constants::DEFAULT_QUERY_LIMIT,
+            weights: dialect.restrict(weights),
+            read_only: false,
+            writes: 0,
+            dialect,
+            explicit_transaction: false,
+            stress: None,
+            constant: false,
+            procedure_fields: &[],
         }
     }
 }
 
 impl CypherGenerator {
-    /// Generator Expr SubQuery.
+    /// Generator Expr SubQuery, its variables are dropped afterwards.
     pub fn exec(&mut self) -> Result<CypherNode, Diagnostic> {
-        self.variables = VariableGenerator::new();
-        self.visit_query()
+        let inner = self.variables.sub_scope(&[]);
+        let outer = std::mem::replace(&mut self.variables, inner);
+        // a CALL in EXISTS or COUNT is nested, it can not run IN TRANSACTIONS.
+        let outer_subquery = std::mem::replace(&mut self.in_subquery, true);
+        let query = self.visit_query();
+        self.in_subquery = outer_subquery;
+        let inner = std::mem::replace(&mut self.variables, outer);
+        self.variables.merge_scope(&inner, &[]);
+        query
     }
 
     /// Generator RegularQuery
     pub fn visit(&mut self) -> Result<CypherNode, Diagnostic> {
         // init the limit parameter each new cypher.
-        self.limit = constants::DEFAULT_QUERY_LIMIT;
+        self.limit = self.query_limit;
+        self.writes = 0;
         self.variables = VariableGenerator::new();
         self.visit_regular_query()
     }
 
     pub fn call_query(&mut self) -> Result<CypherNode, Diagnostic> {
+        if self.dialect.procedures().is_empty() {
+            return Err(Diagnostic::warn(
+                "Unsupported",
+                format!("{:?} has no procedures", self.dialect),
+            ));
+        }
         // StandaloneCall
-        self.limit = constants::DEFAULT_QUERY_LIMIT;
+        self.limit = self.query_limit;
+        self.writes = 0;
         self.variables = VariableGenerator::new();
         self.visit_standalone_call()
     }
 
+    pub fn schema_command(&mut self) -> Result<CypherNode, Diagnostic> {
+        if self.read_only {
+            return Err(Diagnostic::warn(
+                "Read only",
+                "index and constraint DDL is disabled".to_string(),
+            ));
+        }
+        if !self.dialect.schema_commands() {
+            return Err(Diagnostic::warn(
+                "Unsupported",
+                format!("{:?} has no index and constraint DDL", self.dialect),
+            ));
+        }
+        // Index and constraint DDL.
+        self.limit = self.query_limit;
+        self.writes = 1;
+        self.variables = VariableGenerator::new();
+        self.visit_schema_command()
+    }
+
+    /// `RETURN` of an expression of literals, operators and CASE, without variables,
+    /// subqueries or functions: the oracle of the expression evaluator.
+    pub fn constant_query(&mut self) -> Result<CypherNode, Diagnostic> {
+        self.limit = self.query_limit;
+        self.writes = 0;
+        self.variables = VariableGenerator::new();
+
+        let weights = self.weights.clone();
+        self.weights.count_star = 0;
+        self.weights.list_comprehension = 0;
+        self.weights.pattern_comprehension = 0;
+        self.weights.predicate_function = 0;
+        self.weights.relationships_pattern = 0;
+        self.weights.exists_subquery = 0;
+        self.weights.variable = 0;
+        self.weights.count_subquery = 0;
+        self.weights.property_lookup = 0;
+        self.weights.node_labels = 0;
+        self.constant = true;
+        let expression = ExprGenerator::new(self).visit();
+        self.constant = false;
+        self.weights = weights;
+
+        let variable = self.variables.new_kind_variable(expression.kind.get_kind());
+        let projection_body = CypherNode::ProjectionBody {
+            is_distinct: false,
+            projection_items: Box::new(CypherNode::ProjectionItems {
+                is_all: false,
+                expressions: vec![(expression, Some(variable))],
+            }),
+            order: None,
+            skip: None,
+            limit: None,
+        };
+        let single_query = CypherNode::SingleQuery {
+            part_query: Box::new(CypherNode::SinglePartQuery {
+                reading_clauses: vec![],
+                updating_clauses: vec![],
+                return_clause: Some(Box::new(CypherNode::Return {
+                    projection_body: Box::new(projection_body),
+                })),
+            }),
+        };
+        Ok(CypherNode::RegularQuery {
+            single_query: Box::new(single_query),
+            union_all: vec![],
+        })
+    }
+
     /// Pattern: RelationShipsPattern
     pub fn expr_relation_pattern(&mut self) -> Result<CypherNode, Diagnostic> {
         self.visit_pattern_element()
@@ -66,14 +193,27 @@ impl CypherGenerator {
         if self.limit <= 0 || self.random.bool() {
             return None;
         }
+        let property_predicate = self.random.d6() < 2;
         let mut expr_generator = ExprGenerator::new(self);
+        if property_predicate {
+            if let Some(expr) = expr_generator.property_predicate() {
+                return Some(expr);
+            }
+        }
         Some(expr_generator.visit())
     }
 }
 
 impl CypherGenerator {
+    /// The stress limit picked by `limit`, only with the stress chance of the profile.
+    pub(super) fn stress_limit(&mut self, limit: impl Fn(&StressConfig) -> u32) -> Option<u32> {
+        let stress = self.stress.as_ref()?;
+        let limit = limit(stress);
+        self.random.chance(stress.chance).then_some(limit)
+    }
+
     /// Generator Property Expression.
-    fn gen_property_expr(&mut self, kind: DataKind) -> Option<Expr> {
+    pub(super) fn gen_property_expr(&mut self, kind: DataKind) -> Option<Expr> {
         let var = self.variables.get_target_variable(kind.clone());
 
         match var {
@@ -95,6 +235,459 @@ impl CypherGenerator {
             _ => None,
         }
     }
+
+    /// Outer variables imported by a correlated subquery.
+    fn gen_import_variables(&mut self) -> Vec<Variable> {
+        let mut import_variables: Vec<Variable> = vec![];
+        if self.random.bool() {
+            return import_variables;
+        }
+
+        for _ in 0..self.random.d2() + 1 {
+            let var = match self.variables.get_target_variable(DataKind::Vertex) {
+                Ok(var) => var,
+                _ => match self.variables.get_old_variable() {
+                    Ok(var) => var,
+                    _ => break,
+                },
+            };
+            if import_variables
+                .iter()
+                .all(|x| x.get_name() != var.get_name())
+            {
+                import_variables.push(var);
+            }
+        }
+        import_variables
+    }
+
+    /// One part of a CALL subquery.
+    ///
+    /// Unit subquery: ReadingClause* UpdatingClause+, Returning subquery: ReadingClause+ Return.
+    /// The RETURN of a returning part names the `columns`, or fills them for the first part.
+    fn gen_subquery_part(
+        &mut self,
+        is_unit: bool,
+        columns: &mut Vec<Variable>,
+    ) -> Result<CypherNode, Diagnostic> {
+        let number = self.random.d2() + 1;
+        let reading_clauses = self.gen_reading_clauses(number)?;
+
+        let mut updating_clauses = vec![];
+        let return_clause = if is_unit {
+            let number = self.random.d2() + 1;
+            updating_clauses = self.gen_updating_clauses(number)?;
+            if updating_clauses.is_empty() {
+                return Err(Diagnostic::warn("need retry", None));
+            }
+            None
+        } else {
+            Some(Box::new(self.gen_subquery_return(columns)?))
+        };
+
+        Ok(CypherNode::SingleQuery {
+            part_query: Box::new(CypherNode::SinglePartQuery {
+                reading_clauses,
+                updating_clauses,
+                return_clause,
+            }),
+        })
+    }
+
+    /// Body of a CALL subquery, generated in its own variable scope.
+    ///
+    /// Returns the subquery and the inner scope.
+    fn gen_subquery_body(
+        &mut self,
+        import_variables: &[Variable],
+        is_unit: bool,
+    ) -> Result<(CypherNode, VariableGenerator), Diagnostic> {
+        let scope = self.variables.sub_scope(import_variables);
+        let outer = std::mem::replace(&mut self.variables, scope);
+        let query = self.gen_subquery_union(import_variables, is_unit);
+        let inner = std::mem::replace(&mut self.variables, outer);
+        Ok((query?, inner))
+    }
+
+    /// SubqueryPart (UNION ALL? SubqueryPart)*
+    fn gen_subquery_union(
+        &mut self,
+        import_variables: &[Variable],
+        is_unit: bool,
+    ) -> Result<CypherNode, Diagnostic> {
+        let mut columns = vec![];
+        let single_query = self.gen_subquery_part(is_unit, &mut columns)?;
+
+        let mut union_all = vec![];
+        for _ in 0..self.random.d2() {
+            let is_all = self.random.bool();
+            // UNION: every arm imports the same outer variables.
+            self.variables = self.variables.sub_scope(import_variables);
+            let sub_query = self.gen_subquery_part(is_unit, &mut columns)?;
+            union_all.push(Box::new(CypherNode::Union {
+                union_all: Some((is_all, Box::new(sub_query))),
+            }));
+        }
+
+        Ok(CypherNode::RegularQuery {
+            single_query: Box::new(single_query),
+            union_all,
+        })
+    }
+
+    /// ProjectionBody of the given items: DISTINCT? ProjectionItems Order? Skip? Limit?
+    fn gen_projection_body(
+        &mut self,
+        projection_items: CypherNode,
+    ) -> Result<CypherNode, Diagnostic> {
+        // DISTINCT
+        let is_distinct = self.random.d6() == 1;
+        let projection_items = Box::new(projection_items);
+
+        // order:
+        let order = if self.random.low_prob_bool() {
+            let order_node = self.visit_order()?;
+            Some(Box::new(order_node))
+        } else {
+            None
+        };
+        let skip = if self.random.low_prob_bool() {
+            let mut expr_generator = ExprGenerator::new(self);
+            let skip_expression = expr_generator.visit();
+            Some(skip_expression)
+        } else {
+            None
+        };
+        let limit = if self.random.low_prob_bool() {
+            let mut expr_generator = ExprGenerator::new(self);
+            let limit_expression = expr_generator.visit();
+            Some(limit_expression)
+        } else {
+            None
+        };
+        Ok(CypherNode::ProjectionBody {
+            is_distinct,
+            projection_items,
+            order,
+            skip,
+            limit,
+        })
+    }
+
+    /// RETURN of a subquery part: every item is aliased, and the arms of a UNION return
+    /// the same columns in the same order.
+    fn gen_subquery_return(
+        &mut self,
+        columns: &mut Vec<Variable>,
+    ) -> Result<CypherNode, Diagnostic> {
+        let mut expressions = vec![];
+        if columns.is_empty() {
+            for _ in 0..self.random.d2() + 1 {
+                let mut expr_generator = ExprGenerator::new(self);
+                let expression = expr_generator.visit();
+                let variable = self.variables.new_kind_variable(expression.kind.get_kind());
+                columns.push(variable.clone());
+                expressions.push((expression, Some(variable)));
+            }
+        } else {
+            for column in columns.iter() {
+                let mut expr_generator = ExprGenerator::new(self);
+                let expression = expr_generator.visit();
+                expressions.push((expression, Some(column.clone())));
+            }
+        }
+        let projection_items = CypherNode::ProjectionItems {
+            is_all: false,
+            expressions,
+        };
+        Ok(CypherNode::Return {
+            projection_body: Box::new(self.gen_projection_body(projection_items)?),
+        })
+    }
+}
+
+impl CypherGenerator {
+    /// Weights of Match, Unwind, CallSubquery and LoadCsv, which are 0 when unavailable.
+    fn reading_weights(&self) -> [u32; 4] {
+        let weights = &self.weights;
+        [
+            weights.match_clause + weights.optional_match,
+            weights.unwind,
+            if self.limit > 0 {
+                weights.call_subquery
+            } else {
+                0
+            },
+            if self.csv_files.is_empty() {
+                0
+            } else {
+                weights.load_csv
+            },
+        ]
+    }
+
+    /// Weights of Create, Merge, Delete, Set, Remove, Foreach and ConditionalUpdate.
+    fn updating_weights(&self) -> [u32; 7] {
+        if self.read_only {
+            return [0; 7];
+        }
+        let weights = &self.weights;
+        let nested = |weight: u32| if self.limit > 0 { weight } else { 0 };
+        [
+            weights.create,
+            weights.merge,
+            weights.delete,
+            weights.set,
+            weights.remove,
+            nested(weights.foreach),
+            nested(weights.conditional_update),
+        ]
+    }
+
+    /// The pattern element of a quantified path pattern and its WHERE, without variable
+    /// length relationships.
+    fn gen_quantified_element(&mut self) -> Result<(CypherNode, Option<Expr>), Diagnostic> {
+        let node_pattern = self.visit_node_pattern()?;
+        let mut pattern_element_chain = vec![];
+        for _ in 0..=self.random.d2() {
+            let mut relationship = self.visit_relationship_pattern()?;
+            fixed_length(&mut relationship);
+            let node = self.visit_node_pattern()?;
+            pattern_element_chain.push((Box::new(relationship), Box::new(node)));
+        }
+        let pattern_element = CypherNode::PatternElement {
+            parenthesis: false,
+            pattern_element: (Box::new(node_pattern), pattern_element_chain),
+        };
+        Ok((pattern_element, self.gen_where_expression()))
+    }
+
+    /// Path selectors and path modes of the MATCH pattern parts, GQL requires a
+    /// restrictive mode for unbounded quantifiers.
+    fn gen_path_prefixes(&mut self, pattern: &mut CypherNode) {
+        let pattern_parts = match pattern {
+            CypherNode::Pattern { pattern_parts } => pattern_parts,
+            _ => return,
+        };
+        for pattern_part in pattern_parts {
+            if let CypherNode::PatternPart {
+                selector,
+                path_mode,
+                pattern_element,
+                ..
+            } = pattern_part.as_mut()
+            {
+                if self.dialect.path_selectors() && self.random.chance(self.weights.path_selector) {
+                    let k = self.random.d2() as u32 + 1;
+                    *selector = Some(match self.random.d6() {
+                        0 => PathSelector::All,
+                        1 => PathSelector::Any(self.random.bool().then_some(k)),
+                        2 => PathSelector::AllShortest,
+                        3 => PathSelector::AnyShortest,
+                        4 => PathSelector::Shortest(k),
+                        _ => PathSelector::ShortestGroups(k),
+                    });
+                }
+
+                if !self.dialect.path_modes() {
+                    continue;
+                }
+                let mut modes = &PathMode::ALL[..];
+                if has_unbounded_range(pattern_element) {
+                    modes = &PathMode::ALL[1..];
+                } else if self.random.d6() > 1 {
+                    continue;
+                }
+                *path_mode = Some(modes[self.random.under(modes.len() as _) as usize]);
+            }
+        }
+    }
+
+    fn has_updating_clause(&self) -> bool {
+        self.updating_weights().iter().any(|weight| *weight > 0)
+    }
+
+    /// NameSpace.SymbolicName of a known procedure, write procedures are left out in read
+    /// only mode.
+    fn gen_procedure_name(&mut self) -> (NameSpace, Variable) {
+        let procedures = self
+            .dialect
+            .procedures()
+            .into_iter()
+            .filter(|(name_space, name, _)| {
+                !self.read_only || !is_write_procedure(name_space, name)
+            })
+            .collect::<Vec<_>>();
+        let (name_space, name, fields) =
+            procedures[self.random.under(procedures.len() as _) as usize];
+        self.procedure_fields = fields;
+        (
+            NameSpace::with_name(name_space.to_string()),
+            Variable::new(name.to_string()),
+        )
+    }
+
+    /// Write procedures are not allowed in read only mode.
+    fn check_procedure(&mut self, procedure: &CypherNode) -> Result<(), Diagnostic> {
+        let (name_space, name) = match procedure {
+            CypherNode::ExplicitProcedureInvocation { procedure_name, .. }
+            | CypherNode::ImplicitProcedureInvocation { procedure_name } => procedure_name,
+            _ => return Ok(()),
+        };
+        if is_write_procedure(&name_space.get_name(), &name.get_name()) {
+            if self.read_only {
+                return Err(Diagnostic::warn("need retry", None));
+            }
+            self.writes += 1;
+        }
+        Ok(())
+    }
+
+    /// `number` reading clauses, fewer if they get disabled.
+    fn gen_reading_clauses(&mut self, number: i32) -> Result<Vec<Box<CypherNode>>, Diagnostic> {
+        let mut reading_clauses = vec![];
+        for _ in 0..number {
+            if self.reading_weights().iter().all(|weight| *weight == 0) {
+                break;
+            }
+            reading_clauses.push(Box::new(self.visit_reading_clause()?));
+        }
+        Ok(reading_clauses)
+    }
+
+    /// `number` updating clauses, fewer if they get disabled.
+    fn gen_updating_clauses(&mut self, number: i32) -> Result<Vec<Box<CypherNode>>, Diagnostic> {
+        let mut updating_clauses = vec![];
+        for _ in 0..number {
+            if !self.has_updating_clause() {
+                break;
+            }
+            updating_clauses.push(Box::new(self.visit_updating_clause()?));
+        }
+        Ok(updating_clauses)
+    }
+}
+
+impl CypherGenerator {
+    /// Label of an index or constraint, mostly a vertex label.
+    fn gen_schema_label(&mut self) -> Label {
+        if self.graph_schema.edge_labels().is_empty() || self.random.d6() > 0 {
+            self.graph_schema.rand_vertex_label(&mut self.random)
+        } else {
+            self.graph_schema.rand_edge_label(&mut self.random)
+        }
+    }
+
+    /// Some distinct properties of the label.
+    fn gen_schema_properties(&mut self, label: &Label) -> Result<Vec<Property>, Diagnostic> {
+        let mut properties: Vec<Property> = vec![];
+        for prop in label.random_properties(self.random.d2() + 1, &mut self.random) {
+            if properties.iter().all(|x| x.name != prop.name) {
+                properties.push(prop);
+            }
+        }
+
+        if properties.is_empty() {
+            return Err(Diagnostic::warn("need retry", None));
+        }
+        Ok(properties)
+    }
+
+    /// A constraint derived from the schema: `is_pk` gives UNIQUE or NODE KEY, not `nullable` gives NOT NULL.
+    ///
+    /// Sometimes any constraint is picked, which the stored data may violate.
+    fn gen_constraint(
+        &mut self,
+        label: &Label,
+    ) -> Result<(Vec<Property>, ConstraintKind), Diagnostic> {
+        let candidates = label
+            .properties
+            .iter()
+            .filter(|prop| prop.is_pk || !prop.nullable)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() || self.random.d12() == 1 {
+            let kind = match self.random.d6() % 3 {
+                0 if label.is_vertex() => ConstraintKind::NodeKey,
+                1 => ConstraintKind::NotNull,
+                _ => ConstraintKind::Unique,
+            };
+            let mut properties = self.gen_schema_properties(label)?;
+            if kind == ConstraintKind::NotNull {
+                // NOT NULL only takes a single property.
+                properties.truncate(1);
+            }
+            return Ok((properties, kind));
+        }
+
+        let idx = self.random.under(candidates.len() as _);
+        let prop = candidates[idx as usize].clone();
+        let kind = if !prop.is_pk {
+            ConstraintKind::NotNull
+        } else if label.is_vertex() && self.random.bool() {
+            ConstraintKind::NodeKey
+        } else {
+            ConstraintKind::Unique
+        };
+
+        Ok((vec![prop], kind))
+    }
+}
+
+/// Index name derived from the schema, so a later DROP INDEX may hit it.
+fn index_name(label: &Label, properties: &[Property]) -> String {
+    let mut name = format!("index_{}", label.get_name());
+    for prop in properties {
+        name += "_";
+        name += &prop.get_name();
+    }
+    name
+}
+
+/// Constraint name derived from the schema, so a later DROP CONSTRAINT may hit it.
+fn constraint_name(label: &Label, properties: &[Property], kind: ConstraintKind) -> String {
+    let mut name = format!("constraint_{}", label.get_name());
+    for prop in properties {
+        name += "_";
+        name += &prop.get_name();
+    }
+    name += match kind {
+        ConstraintKind::Unique => "_unique",
+        ConstraintKind::NotNull => "_not_null",
+        ConstraintKind::NodeKey => "_node_key",
+    };
+    name
+}
+
+/// Variables returned by a RETURN clause of a subquery part.
+fn returned_variables(query: &CypherNode) -> Vec<Variable> {
+    if let CypherNode::RegularQuery { single_query, .. } = query {
+        if let CypherNode::SingleQuery { part_query } = single_query.as_ref() {
+            if let CypherNode::SinglePartQuery {
+                return_clause: Some(return_clause),
+                ..
+            } = part_query.as_ref()
+            {
+                if let CypherNode::Return { projection_body } = return_clause.as_ref() {
+                    if let CypherNode::ProjectionBody {
+                        projection_items, ..
+                    } = projection_body.as_ref()
+                    {
+                        if let CypherNode::ProjectionItems { expressions, .. } =
+                            projection_items.as_ref()
+                        {
+                            return expressions
+                                .iter()
+                                .filter_map(|(_, var)| var.clone())
+                                .collect();
+                        }
+                    }
+                }
+            }
+        }
+    }
+    vec![]
 }
 
 impl CypherNodeVisitor for CypherGenerator {
@@ -113,8 +706,12 @@ impl CypherNodeVisitor for CypherGenerator {
     fn visit_regular_query(&mut self) -> Self::Output {
         let single_query = self.visit_single_query()?;
 
+        let unions = match self.stress_limit(|stress| stress.union_arms) {
+            Some(union_arms) => self.random.under(union_arms as i32 + 1),
+            None => self.random.d2(),
+        };
         let mut union_all = vec![];
-        for _ in 0..self.random.d2() {
+        for _ in 0..unions {
             let single_union = self.visit_union()?;
             union_all.push(Box::new(single_union));
         }
@@ -132,8 +729,10 @@ impl CypherNodeVisitor for CypherGenerator {
         } else {
             self.visit_implicit_procedure_invocation()?
         };
+        self.check_procedure(&procedure_node)?;
 
-        let yield_items = if self.random.bool() {
+        // a procedure without results has nothing to yield.
+        let yield_items = if !self.procedure_fields.is_empty() && self.random.bool() {
             if self.random.bool() {
                 (true, None)
             } else {
@@ -178,13 +777,9 @@ impl CypherNodeVisitor for CypherGenerator {
 
     /// SinglePartQuery: ReadingClause* Return | ReadingClause* UpdatingClause+ Return?
     fn visit_single_part_query(&mut self) -> Self::Output {
-        if self.random.bool() {
+        if self.random.bool() || !self.has_updating_clause() {
             let reading_number = self.random.d2();
-            let mut reading_clauses = vec![];
-            for _ in 0..reading_number {
-                let reading_clause = self.visit_reading_clause()?;
-                reading_clauses.push(Box::new(reading_clause));
-            }
+            let reading_clauses = self.gen_reading_clauses(reading_number)?;
 
             let return_clause = self.visit_return()?;
 
@@ -194,19 +789,12 @@ impl CypherNodeVisitor for CypherGenerator {
                 return_clause: Some(Box::new(return_clause)),
             })
         } else {
-            let mut reading_clauses = vec![];
-            let mut updating_clauses = vec![];
-            for _ in 0..self.random.d2() {
-                let reading_clause = self.visit_reading_clause()?;
-                reading_clauses.push(Box::new(reading_clause));
-            }
-            let updating_clause = self.visit_updating_clause()?;
-            updating_clauses.push(Box::new(updating_clause));
-
-            for _ in 0..self.random.d2() {
-                let updating_clause = self.visit_updating_clause()?;
-
-                updating_clauses.push(Box::new(updating_clause));
+            let reading_number = self.random.d2();
+            let reading_clauses = self.gen_reading_clauses(reading_number)?;
+            let updating_number = self.random.d2() + 1;
+            let updating_clauses = self.gen_updating_clauses(updating_number)?;
+            if updating_clauses.is_empty() {
+                return Err(Diagnostic::warn("need retry", None));
             }
 
             let return_clause = if self.random.bool() {
@@ -231,20 +819,10 @@ impl CypherNodeVisitor for CypherGenerator {
 
         let with_number = self.random.d2();
         for _ in 0..with_number + 1 {
-            let mut reading_clause = vec![];
-            let mut updating_clause = vec![];
             let reading_number = self.random.d2();
             let updating_number = self.random.d2();
-
-            for _ in 0..reading_number {
-                let reading_query = self.visit_reading_clause()?;
-                reading_clause.push(Box::new(reading_query));
-            }
-
-            for _ in 0..updating_number {
-                let updating_query = self.visit_updating_clause()?;
-                updating_clause.push(Box::new(updating_query));
-            }
+            let reading_clause = self.gen_reading_clauses(reading_number)?;
+            let updating_clause = self.gen_updating_clauses(updating_number)?;
 
             let with_clause = self.visit_with()?;
             let with_query = Box::new(with_clause);
@@ -274,9 +852,10 @@ impl CypherNodeVisitor for CypherGenerator {
     // in_query_call: call procedure.
     fn visit_in_query_call(&mut self) -> Self::Output {
         let procedure_node = self.visit_explicit_procedure_invocation()?;
+        self.check_procedure(&procedure_node)?;
 
-        // YieldItems
-        let yield_items = if self.random.bool() {
+        // YieldItems: the results of a procedure called inside a query are named explicitly.
+        let yield_items = if !self.procedure_fields.is_empty() {
             let yield_items_node = self.visit_yield_items()?;
             Some(Box::new(yield_items_node))
         } else {
@@ -291,9 +870,7 @@ impl CypherNodeVisitor for CypherGenerator {
 
     /// ExplicitProcedureInvocation: ProcedureName ( Expression* )
     fn visit_explicit_procedure_invocation(&mut self) -> Self::Output {
-        // TODO: need to implement NameSpace and SybolicName.
-        let name_space = NameSpace::new();
-        let symbolic_name = self.variables.get_procedure_method();
+        let (name_space, symbolic_name) = self.gen_procedure_name();
 
         // expressions generator.
         let mut expressions = Vec::new();
@@ -313,8 +890,7 @@ impl CypherNodeVisitor for CypherGenerator {
     }
 
     fn visit_implicit_procedure_invocation(&mut self) -> Self::Output {
-        let name_space = NameSpace::new();
-        let symbolic_name = self.variables.get_procedure_method();
+        let (name_space, symbolic_name) = self.gen_procedure_name();
 
         Ok(CypherNode::ImplicitProcedureInvocation {
             procedure_name: (name_space, symbolic_name),
@@ -324,24 +900,14 @@ impl CypherNodeVisitor for CypherGenerator {
     fn visit_yield_items(&mut self) -> Self::Output {
         let mut yield_items = vec![];
 
-        // first yield_item: (ProcedureResultField AS)* variable.
-        let first_variable = self.variables.new_variable();
-        if self.random.bool() {
-            let procedure_result = self.variables.get_procedure_result();
-            yield_items.push((Some(procedure_result), first_variable));
-        } else {
-            yield_items.push((None, first_variable));
-        }
-
-        // yield_item*
-        for _ in 0..self.random.d2() {
+        // yield_item+: ProcedureResultField AS variable, distinct fields of the procedure.
+        let fields = self.procedure_fields;
+        let number = (self.random.d2() as usize + 1).min(fields.len());
+        let start = self.random.under(fields.len() as _) as usize;
+        for idx in 0..number {
+            let field = Variable::new(fields[(start + idx) % fields.len()].to_string());
             let variable = self.variables.new_variable();
-            if self.random.bool() {
-                let procedure_result = self.variables.get_procedure_result();
-                yield_items.push((Some(procedure_result), variable));
-            } else {
-                yield_items.push((None, variable));
-            }
+            yield_items.push((Some(field), variable));
         }
 
         // where_clause
@@ -359,12 +925,97 @@ impl CypherNodeVisitor for CypherGenerator {
         })
     }
 
+    /// SchemaCommand: CreateIndex | DropIndex | CreateConstraint | DropConstraint | ShowSchema
+    fn visit_schema_command(&mut self) -> Self::Output {
+        let command = match self.random.d12() {
+            0..=3 => self.visit_create_index()?,
+            4..=7 => self.visit_create_constraint()?,
+            8 => self.visit_drop_index()?,
+            9 => self.visit_drop_constraint()?,
+            _ => self.visit_show_schema()?,
+        };
+
+        Ok(CypherNode::SchemaCommand {
+            command: Box::new(command),
+        })
+    }
+
+    /// CreateIndex: CREATE INDEX name (IF NOT EXISTS)? FOR pattern ON (PropertyExpression+)
+    fn visit_create_index(&mut self) -> Self::Output {
+        let label = self.gen_schema_label();
+        let properties = self.gen_schema_properties(&label)?;
+        let kind = if label.is_vertex() {
+            DataKind::Vertex
+        } else {
+            DataKind::Edge
+        };
+
+        Ok(CypherNode::CreateIndex {
+            name: index_name(&label, &properties),
+            if_not_exists: self.random.bool(),
+            variable: self.variables.new_kind_variable(kind),
+            label,
+            properties,
+        })
+    }
+
+    /// DropIndex: DROP INDEX name (IF EXISTS)?
+    fn visit_drop_index(&mut self) -> Self::Output {
+        let label = self.gen_schema_label();
+        let properties = self.gen_schema_properties(&label)?;
+
+        Ok(CypherNode::DropIndex {
+            name: index_name(&label, &properties),
+            if_exists: self.random.d6() > 0,
+        })
+    }
+
+    /// CreateConstraint: CREATE CONSTRAINT name (IF NOT EXISTS)? FOR pattern REQUIRE PropertyExpression+ ConstraintKind
+    fn visit_create_constraint(&mut self) -> Self::Output {
+        let label = self.gen_schema_label();
+        let (properties, kind) = self.gen_constraint(&label)?;
+        let var_kind = if label.is_vertex() {
+            DataKind::Vertex
+        } else {
+            DataKind::Edge
+        };
+
+        Ok(CypherNode::CreateConstraint {
+            name: constraint_name(&label, &properties, kind),
+            if_not_exists: self.random.bool(),
+            variable: self.variables.new_kind_variable(var_kind),
+            label,
+            properties,
+            kind,
+        })
+    }
+
+    /// DropConstraint: DROP CONSTRAINT name (IF EXISTS)?
+    fn visit_drop_constraint(&mut self) -> Self::Output {
+        let label = self.gen_schema_label();
+        let (properties, kind) = self.gen_constraint(&label)?;
+
+        Ok(CypherNode::DropConstraint {
+            name: constraint_name(&label, &properties, kind),
+            if_exists: self.random.d6() > 0,
+        })
+    }
+
+    /// ShowSchema: SHOW (INDEXES|CONSTRAINTS)
+    fn visit_show_schema(&mut self) -> Self::Output {
+        Ok(CypherNode::ShowSchema {
+            show_constraints: self.random.bool(),
+        })
+    }
+
     fn visit_reading_clause(&mut self) -> Self::Output {
-        let reading_clause = match self.random.d6() {
-            0 => self.visit_match()?,
-            1 => self.visit_unwind()?,
-            // default: match clause.
-            _ => self.visit_match()?,
+        let weights = self.reading_weights();
+        let reading_clause = match self.random.weighted(&weights) {
+            Some(0) => self.visit_match()?,
+            Some(1) => self.visit_unwind()?,
+            Some(2) => self.visit_call_subquery()?,
+            Some(3) => self.visit_load_csv()?,
+            _ => return Err(Diagnostic::warn("reading clauses disabled", None)),
         };
 
         Ok(CypherNode::ReadingClause {
@@ -374,9 +1025,15 @@ impl CypherNodeVisitor for CypherGenerator {
 
     /// Match Clause: Optional MATCH **pattern** [WHERE clause]
     fn visit_match(&mut self) -> Self::Output {
-        let is_optional = self.random.bool();
-
-        let pattern_node = self.visit_pattern()?;
+        let weights = [self.weights.match_clause, self.weights.optional_match];
+        let is_optional = self.random.weighted(&weights) == Some(1);
+
+        let in_match = std::mem::replace(&mut self.in_match, true);
+        let pattern_node = self.visit_pattern();
+        self.in_match = in_match;
+        let mut pattern_node = pattern_node?;
+        fix_relationship_lengths(&mut pattern_node);
+        self.gen_path_prefixes(&mut pattern_node);
         let pattern = Box::new(pattern_node);
 
         // generator where expression.
@@ -402,15 +1059,87 @@ impl CypherNodeVisitor for CypherGenerator {
         })
     }
 
+    /// CallSubquery: CALL { (WITH Variable+)? RegularQuery } (IN TRANSACTIONS (OF n ROWS)?)?
+    fn visit_call_subquery(&mut self) -> Self::Output {
+        // every subquery consumes complexity, which bounds the nesting depth.
+        self.limit -= constants::DEFAULT_EXPRESSION_LIMIT;
+
+        let import_variables = self.gen_import_variables();
+        // unit subquery: no RETURN, only updating clauses.
+        let is_unit = self.has_updating_clause() && self.random.d6() == 1;
+
+        let outer_subquery = std::mem::replace(&mut self.in_subquery, true);
+        let body = self.gen_subquery_body(&import_variables, is_unit);
+        self.in_subquery = outer_subquery;
+        let (query, inner) = body?;
+
+        let exports = returned_variables(&query);
+        self.variables.merge_scope(&inner, &exports);
+
+        // IN TRANSACTIONS is only allowed for the outermost subquery.
+        let in_transactions = if !self.in_subquery
+            && !self.explicit_transaction
+            && self.dialect.in_transactions()
+            && self.random.d9() == 1
+        {
+            let rows = if self.random.bool() {
+                Some(self.random.range(1, 1000))
+            } else {
+                None
+            };
+            (true, rows)
+        } else {
+            (false, None)
+        };
+
+        Ok(CypherNode::CallSubquery {
+            import_variables,
+            query: Box::new(query),
+            in_transactions,
+        })
+    }
+
+    /// LoadCsv: LOAD CSV (WITH HEADERS)? FROM url AS Variable (FIELDTERMINATOR string)?
+    fn visit_load_csv(&mut self) -> Self::Output {
+        let with_headers = self.random.bool();
+
+        let idx = self.random.under(self.csv_files.len() as _);
+        let file = self.csv_files[idx as usize].clone();
+
+        // a row is a map with headers, otherwise a list of fields.
+        let variable = if with_headers {
+            self.variables.new_kind_variable(DataKind::Map)
+        } else {
+            self.variables.new_kind_variable(DataKind::List)
+        };
+
+        // fixtures are written with the default terminator.
+        let field_terminator = if self.random.d6() == 1 {
+            Some(",".to_string())
+        } else {
+            None
+        };
+
+        Ok(CypherNode::LoadCsv {
+            with_headers,
+            file,
+            variable,
+            field_terminator,
+        })
+    }
+
     fn visit_updating_clause(&mut self) -> Self::Output {
-        let updating_clause = match self.random.d6() {
-            0 => self.visit_create()?,
-            1 => self.visit_merge()?,
-            2 => self.visit_delete()?,
-            3 => self.visit_set()?,
-            4 => self.visit_remove()?,
-            // default: create clause.
-            _ => self.visit_create()?,
+        self.writes += 1;
+        let weights = self.updating_weights();
+        let updating_clause = match self.random.weighted(&weights) {
+            Some(0) => self.visit_create()?,
+            Some(1) => self.visit_merge()?,
+            Some(2) => self.visit_delete()?,
+            Some(3) => self.visit_set()?,
+            Some(4) => self.visit_remove()?,
+            Some(5) => self.visit_foreach()?,
+            Some(6) => self.visit_conditional_update()?,
+            _ => return Err(Diagnostic::warn("updating clauses disabled", None)),
         };
 
         Ok(CypherNode::UpdatingClause {
@@ -433,7 +1162,13 @@ impl CypherNodeVisitor for CypherGenerator {
 
         let mut merge_actions = Vec::new();
 
-        for _ in 0..self.random.d2() {
+        // ON MATCH SET / ON CREATE SET, disabled with SET.
+        let actions_number = if self.weights.set > 0 {
+            self.random.d2()
+        } else {
+            0
+        };
+        for _ in 0..actions_number {
             let merge_action = self.visit_set()?;
 
             let opt = if self.random.bool() {
@@ -450,6 +1185,71 @@ impl CypherNodeVisitor for CypherGenerator {
         })
     }
 
+    /// Foreach: FOREACH ( Variable IN Expression | UpdatingClause+ )
+    fn visit_foreach(&mut self) -> Self::Output {
+        // nested updating clauses consume complexity.
+        self.limit -= constants::DEFAULT_EXPRESSION_LIMIT;
+
+        let expression = if self.random.d6() == 1 {
+            // any expression, which may not be a list at all.
+            let mut expr_generator = ExprGenerator::new(self);
+            expr_generator.visit()
+        } else {
+            let mut expr_generator = ExprGenerator::new(self);
+            let elements = (0..expr_generator.random_list_length())
+                .map(|_| expr_generator.visit())
+                .collect::<Vec<_>>();
+            Expr::from(ExprKind::Lit(Literal::List(elements)))
+        };
+
+        // the loop variable and the variables of the nested clauses end with the FOREACH.
+        let scope = self.variables.nested_scope();
+        let outer = std::mem::replace(&mut self.variables, scope);
+        let variable = self.variables.new_kind_variable(DataKind::Null);
+        let number = self.random.d2() + 1;
+        let updating_clauses = self.gen_updating_clauses(number);
+        let inner = std::mem::replace(&mut self.variables, outer);
+        self.variables.merge_scope(&inner, &[]);
+
+        let updating_clauses = updating_clauses?;
+        if updating_clauses.is_empty() {
+            return Err(Diagnostic::warn("need retry", None));
+        }
+
+        Ok(CypherNode::Foreach {
+            variable,
+            expression,
+            updating_clauses,
+        })
+    }
+
+    /// ConditionalUpdate: FOREACH ( Variable IN CASE WHEN Expression THEN [1] ELSE [] END | UpdatingClause+ )
+    fn visit_conditional_update(&mut self) -> Self::Output {
+        self.limit -= constants::DEFAULT_EXPRESSION_LIMIT;
+
+        let mut expr_generator = ExprGenerator::new(self);
+        let condition = expr_generator.visit();
+        let variable = self.variables.new_tmp_variable();
+
+        let scope = self.variables.nested_scope();
+        let outer = std::mem::replace(&mut self.variables, scope);
+        let number = self.random.d2() + 1;
+        let updating_clauses = self.gen_updating_clauses(number);
+        let inner = std::mem::replace(&mut self.variables, outer);
+        self.variables.merge_scope(&inner, &[]);
+
+        let updating_clauses = updating_clauses?;
+        if updating_clauses.is_empty() {
+            return Err(Diagnostic::warn("need retry", None));
+        }
+
+        Ok(CypherNode::ConditionalUpdate {
+            variable,
+            condition,
+            updating_clauses,
+        })
+    }
+
     /// ### delete
     ///
     /// detach? delete Vec\<expressions>
@@ -497,10 +1297,26 @@ impl CypherNodeVisitor for CypherGenerator {
                     if property.is_none() {
                         continue;
                     }
+                    let property = property.unwrap();
 
                     let mut expr_generator = ExprGenerator::new(self);
                     let expression = expr_generator.visit();
-                    property_set.push((property.unwrap(), expression));
+                    let expression = if self.random.d6() == 1 {
+                        // conditional update: CASE WHEN cond THEN expr ELSE property END
+                        let mut expr_generator = ExprGenerator::new(self);
+                        let condition = expr_generator.visit();
+                        Expr::from(ExprKind::Case(
+                            None,
+                            vec![CaseAlternative {
+                                condition: Box::new(condition),
+                                value: Box::new(expression),
+                            }],
+                            Some(Box::new(property.clone())),
+                        ))
+                    } else {
+                        expression
+                    };
+                    property_set.push((property, expression));
                 }
                 1 => {
                     let variable = self.variables.get_old_variable()?;
@@ -600,41 +1416,8 @@ impl CypherNodeVisitor for CypherGenerator {
     }
 
     fn visit_projection_body(&mut self) -> Self::Output {
-        // DISTINCT
-        let is_distinct = self.random.d6() == 1;
-
-        // ProjectionItems
-        let projection_items_node = self.visit_projection_items()?;
-        let projection_items = Box::new(projection_items_node);
-
-        // order:
-        let order = if self.random.low_prob_bool() {
-            let order_node = self.visit_order()?;
-            Some(Box::new(order_node))
-        } else {
-            None
-        };
-        let skip = if self.random.low_prob_bool() {
-            let mut expr_generator = ExprGenerator::new(self);
-            let skip_expression = expr_generator.visit();
-            Some(skip_expression)
-        } else {
-            None
-        };
-        let limit = if self.random.low_prob_bool() {
-            let mut expr_generator = ExprGenerator::new(self);
-            let limit_expression = expr_generator.visit();
-            Some(limit_expression)
-        } else {
-            None
-        };
-        Ok(CypherNode::ProjectionBody {
-            is_distinct,
-            projection_items,
-            order,
-            skip,
-            limit,
-        })
+        let projection_items = self.visit_projection_items()?;
+        self.gen_projection_body(projection_items)
     }
 
     /// ProjectionItems: *(,ProjectionItem)*|ProjectionItem+
@@ -739,6 +1522,8 @@ impl CypherNodeVisitor for CypherGenerator {
         let pattern_element_node = self.visit_pattern_element()?;
         Ok(CypherNode::PatternPart {
             var,
+            selector: None,
+            path_mode: None,
             pattern_element: Box::new(pattern_element_node),
         })
     }
@@ -750,9 +1535,19 @@ impl CypherNodeVisitor for CypherGenerator {
         let node_pattern_node = self.visit_node_pattern()?;
         let node_pattern = Box::new(node_pattern_node);
 
+        let length = match self.stress_limit(|stress| stress.pattern_length) {
+            Some(pattern_length) => self.random.under(pattern_length as i32 + 1),
+            None => self.random.d2(),
+        };
         let mut pattern_element_chain = vec![];
-        for _ in 0..self.random.d2() {
-            let relationship_node = self.visit_relationship_pattern()?;
+        for _ in 0..length {
+            // quantified path patterns only in MATCH.
+            let relationship_node =
+                if self.in_match && self.random.chance(self.weights.quantified_path) {
+                    self.visit_quantified_path_pattern()?
+                } else {
+                    self.visit_relationship_pattern()?
+                };
             let node = self.visit_node_pattern()?;
 
             pattern_element_chain.push((Box::new(relationship_node), Box::new(node)));
@@ -794,7 +1589,7 @@ impl CypherNodeVisitor for CypherGenerator {
         // previous label's properties.
         let properties = if self.random.bool() {
             if let Some(prop) = node_property {
-                let property_value = prop.default_value();
+                let property_value = prop.random_value(&mut self.random);
                 Some((prop, property_value))
             } else {
                 None
@@ -850,7 +1645,11 @@ impl CypherNodeVisitor for CypherGenerator {
 
             let range_end = if self.random.bool() {
                 let is_range_end = true;
-                let range_end = if self.random.bool() {
+                let range_end = if let Some(max_hops) = self.stress_limit(|stress| stress.max_hops)
+                {
+                    let range_start = range_start.unwrap_or(0);
+                    Some(range_start + self.random.under(max_hops as i32 + 1))
+                } else if self.random.bool() {
                     if let Some(range_start) = range_start {
                         Some(range_start + self.random.d2())
                     } else {
@@ -873,7 +1672,7 @@ impl CypherNodeVisitor for CypherGenerator {
         let properties = if self.random.bool() && !edge_labels.is_empty() {
             let edge_property = edge_labels[0].clone().random_property(&mut self.random);
             if let Some(prop) = edge_property {
-                let property_value = prop.default_value();
+                let property_value = prop.random_value(&mut self.random);
                 Some((prop, property_value))
             } else {
                 None
@@ -891,4 +1690,153 @@ impl CypherNodeVisitor for CypherGenerator {
             properties,
         })
     }
+
+    /// QuantifiedPathPattern: `(` PatternElement (`WHERE` Expression)? `)` Quantifier?
+    ///
+    /// Quantified path patterns are not nested and hold no variable length relationships.
+    fn visit_quantified_path_pattern(&mut self) -> Self::Output {
+        let in_match = std::mem::replace(&mut self.in_match, false);
+        // the inner WHERE sees the variables of the inner pattern only.
+        let inner = self.variables.sub_scope(&[]);
+        let outer = std::mem::replace(&mut self.variables, inner);
+        let quantified = self.gen_quantified_element();
+        self.in_match = in_match;
+        let inner = std::mem::replace(&mut self.variables, outer);
+        let (pattern_element, where_clause) = match quantified {
+            Ok(quantified) => quantified,
+            Err(err) => {
+                self.variables.merge_scope(&inner, &[]);
+                return Err(err);
+            }
+        };
+
+        let lower = self.random.d2();
+        let quantifier = match self.random.d6() {
+            // grouped sub-pattern.
+            0 if where_clause.is_some() => None,
+            // `*` and `+`
+            1 => Some((None, None)),
+            2 => Some((Some(1), None)),
+            3 => Some((Some(lower), None)),
+            4 => Some((None, Some(lower + 1))),
+            _ => Some((Some(lower), Some(lower + self.random.d2()))),
+        };
+
+        // outside, the variables of a quantified pattern are group variables: lists of the
+        // values of each iteration.
+        let exports = pattern_variables(&pattern_element)
+            .into_iter()
+            .map(|var| match quantifier {
+                Some(_) => Variable::new_var(var.get_name(), DataKind::List),
+                None => var,
+            })
+            .collect::<Vec<_>>();
+        self.variables.merge_scope(&inner, &exports);
+
+        Ok(CypherNode::QuantifiedPathPattern {
+            pattern_element: Box::new(pattern_element),
+            where_clause,
+            quantifier,
+        })
+    }
+}
+
+/// Make a relationship pattern match exactly one relationship.
+fn fixed_length(relationship: &mut CypherNode) {
+    if let CypherNode::RelationshipPattern {
+        is_range, range, ..
+    } = relationship
+    {
+        *is_range = false;
+        *range = (None, None);
+    }
+}
+
+/// The variables of the nodes and relationships of a pattern element.
+fn pattern_variables(pattern_element: &CypherNode) -> Vec<Variable> {
+    let (node, chain) = match pattern_element {
+        CypherNode::PatternElement {
+            pattern_element: (node, chain),
+            ..
+        } => (node, chain),
+        _ => return vec![],
+    };
+    std::iter::once(node.as_ref())
+        .chain(
+            chain
+                .iter()
+                .flat_map(|(relationship, node)| [relationship.as_ref(), node.as_ref()]),
+        )
+        .filter_map(|node| match node {
+            CypherNode::NodePattern { var, .. } | CypherNode::RelationshipPattern { var, .. } => {
+                var.clone()
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// A MATCH pattern can not mix variable length relationships with quantified path
+/// patterns, the relationships of a pattern holding one match a single relationship.
+fn fix_relationship_lengths(pattern: &mut CypherNode) {
+    let pattern_parts = match pattern {
+        CypherNode::Pattern { pattern_parts } => pattern_parts,
+        _ => return,
+    };
+    let mut chains = pattern_parts
+        .iter_mut()
+        .filter_map(|pattern_part| match pattern_part.as_mut() {
+            CypherNode::PatternPart {
+                pattern_element, ..
+            } => match pattern_element.as_mut() {
+                CypherNode::PatternElement {
+                    pattern_element: (_, chain),
+                    ..
+                } => Some(chain),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let quantified = chains.iter().any(|chain| {
+        chain.iter().any(|(relationship, _)| {
+            matches!(
+                relationship.as_ref(),
+                CypherNode::QuantifiedPathPattern { .. }
+            )
+        })
+    });
+    if !quantified {
+        return;
+    }
+    for chain in chains.iter_mut() {
+        for (relationship, _) in chain.iter_mut() {
+            fixed_length(relationship);
+        }
+    }
+}
+
+/// A variable length relationship or quantified path pattern of the pattern element
+/// without an upper bound.
+fn has_unbounded_range(pattern_element: &CypherNode) -> bool {
+    let relationships = match pattern_element {
+        CypherNode::PatternElement {
+            pattern_element: (_, relationships),
+            ..
+        } => relationships,
+        _ => return false,
+    };
+    relationships.iter().any(|(relationship, _)| {
+        matches!(
+            relationship.as_ref(),
+            CypherNode::RelationshipPattern {
+                is_range: true,
+                range: (None, None) | (_, Some((_, None))) | (_, Some((false, _))),
+                ..
+            } | CypherNode::QuantifiedPathPattern {
+                quantifier: Some((_, None)),
+                ..
+            }
+        )
+    })
 }
@@ -0,0 +1,1079 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use serde_json::Value;
+
+use super::{rewrite::walk_expr, CypherNode};
+use crate::{
+    common::{BinOpKind, CaseAlternative, CmpKind, Expr, ExprKind, Literal, UnOpKind, ValueType},
+    meta::Label,
+};
+
+/// Value of a constant expression, or of an expression of a row of the reference engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Constant>),
+    Map(BTreeMap<String, Constant>),
+    /// A vertex of the reference engine, by its index.
+    Node(usize),
+    /// An edge of the reference engine, by its index.
+    Relationship(usize),
+}
+
+/// The values an expression of a row refers to, nothing for a constant expression.
+pub trait Scope {
+    /// The value of a variable.
+    fn variable(&self, _name: &str) -> Eval {
+        Err(EvalError::Unsupported)
+    }
+
+    /// The value of a property of a node or a relationship.
+    fn property(&self, _entity: &Constant, _name: &str) -> Eval {
+        Err(EvalError::Unsupported)
+    }
+
+    /// The node has the label.
+    fn has_label(&self, _entity: &Constant, _label: &Label) -> Eval {
+        Err(EvalError::Unsupported)
+    }
+
+    /// The value of an expression known as a whole, e.g. an aggregation of a group.
+    fn expression(&self, _expr: &Expr) -> Option<Eval> {
+        None
+    }
+}
+
+impl Scope for () {}
+
+/// A call of an aggregating function, `count(*)` has no argument.
+pub struct Aggregation<'a> {
+    /// `count`, `sum`, `avg`, `min`, `max` or `collect`.
+    pub function: String,
+    pub distinct: bool,
+    pub argument: Option<&'a Expr>,
+}
+
+const AGGREGATING_FUNCTIONS: &[&str] = &["count", "sum", "avg", "min", "max", "collect"];
+
+/// The expression is a call of an aggregating function.
+pub fn aggregation(expr: &Expr) -> Option<Aggregation<'_>> {
+    match &expr.kind {
+        // `COUNT (*)` is generated as a literal.
+        ExprKind::Lit(Literal::String(text)) if text.eq_ignore_ascii_case("count (*)") => {
+            Some(Aggregation {
+                function: "count".to_string(),
+                distinct: false,
+                argument: None,
+            })
+        }
+        ExprKind::Invocation(name, distinct, arguments) if arguments.len() == 1 => {
+            let function = name.to_string().to_lowercase();
+            AGGREGATING_FUNCTIONS
+                .contains(&function.as_str())
+                .then_some(Aggregation {
+                    function,
+                    distinct: *distinct,
+                    argument: arguments.first(),
+                })
+        }
+        _ => None,
+    }
+}
+
+/// The expression has an aggregation, e.g. `count(*) + 1`.
+pub fn aggregates(expr: &Expr) -> bool {
+    let mut found = false;
+    walk_expr(&mut expr.clone(), &mut |_| {}, &mut |expr| {
+        found |= aggregation(expr).is_some()
+    });
+    found
+}
+
+/// An expression without a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// The target must reject the expression, e.g. an integer overflow.
+    Error(String),
+    /// Beyond the evaluator: variables, functions, infinite floats, or semantics left
+    /// to the engine such as the short circuit of `AND` and `OR`.
+    Unsupported,
+}
+
+pub type Eval = Result<Constant, EvalError>;
+
+fn error(message: &str) -> EvalError {
+    EvalError::Error(message.to_string())
+}
+
+impl Constant {
+    /// The value in the JSON format of a result row.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Constant::Null => Value::Null,
+            Constant::Boolean(b) => Value::Bool(*b),
+            Constant::Integer(i) => Value::from(*i),
+            Constant::Float(f) => Value::from(*f),
+            Constant::String(s) => Value::String(s.clone()),
+            Constant::List(list) => Value::Array(list.iter().map(Constant::to_json).collect()),
+            Constant::Map(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.to_json()))
+                    .collect(),
+            ),
+            Constant::Node(idx) => serde_json::json!({ "node": idx }),
+            Constant::Relationship(idx) => serde_json::json!({ "relationship": idx }),
+        }
+    }
+
+    /// A node or a relationship, or a list or a map with one.
+    pub fn has_entity(&self) -> bool {
+        match self {
+            Constant::Node(_) | Constant::Relationship(_) => true,
+            Constant::List(list) => list.iter().any(Constant::has_entity),
+            Constant::Map(map) => map.values().any(Constant::has_entity),
+            _ => false,
+        }
+    }
+
+    /// The value returned by the target is this one, an integer is a JSON integer and
+    /// a float a JSON float up to rounding.
+    pub fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Constant::Null, Value::Null) => true,
+            (Constant::Boolean(lhs), Value::Bool(rhs)) => lhs == rhs,
+            (Constant::Integer(lhs), Value::Number(rhs)) => rhs.as_i64() == Some(*lhs),
+            (Constant::Float(lhs), Value::Number(rhs)) if rhs.is_f64() => {
+                rhs.as_f64().is_some_and(|rhs| close(*lhs, rhs))
+            }
+            (Constant::String(lhs), Value::String(rhs)) => lhs == rhs,
+            (Constant::List(lhs), Value::Array(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.matches(rhs))
+            }
+            (Constant::Map(lhs), Value::Object(rhs)) => {
+                lhs.len() == rhs.len()
+                    && lhs
+                        .iter()
+                        .all(|(key, lhs)| rhs.get(key).is_some_and(|rhs| lhs.matches(rhs)))
+            }
+            _ => false,
+        }
+    }
+
+    fn number(&self) -> Option<f64> {
+        match self {
+            Constant::Integer(i) => Some(*i as f64),
+            Constant::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        self.number().is_some()
+    }
+}
+
+fn close(lhs: f64, rhs: f64) -> bool {
+    (lhs - rhs).abs() <= 1e-9 * lhs.abs().max(rhs.abs()).max(1.0)
+}
+
+/// The values of the columns of a query of the form `RETURN expr, ..`, `None` if the
+/// query has another form or an expression is beyond the evaluator; `Err` with the
+/// reason if the target must reject it.
+pub fn fold(query: &CypherNode) -> Option<Result<Vec<Constant>, String>> {
+    let CypherNode::RegularQuery {
+        single_query,
+        union_all,
+    } = query
+    else {
+        return None;
+    };
+    let CypherNode::SingleQuery { part_query } = single_query.as_ref() else {
+        return None;
+    };
+    let CypherNode::SinglePartQuery {
+        reading_clauses,
+        updating_clauses,
+        return_clause: Some(return_clause),
+    } = part_query.as_ref()
+    else {
+        return None;
+    };
+    let CypherNode::Return { projection_body } = return_clause.as_ref() else {
+        return None;
+    };
+    let CypherNode::ProjectionBody {
+        projection_items,
+        order: None,
+        skip: None,
+        limit: None,
+        ..
+    } = projection_body.as_ref()
+    else {
+        return None;
+    };
+    let CypherNode::ProjectionItems {
+        is_all: false,
+        expressions,
+    } = projection_items.as_ref()
+    else {
+        return None;
+    };
+    if !union_all.is_empty() || !reading_clauses.is_empty() || !updating_clauses.is_empty() {
+        return None;
+    }
+
+    let values = expressions
+        .iter()
+        .map(|(expr, _)| evaluate(expr))
+        .collect::<Vec<_>>();
+    if values.contains(&Err(EvalError::Unsupported)) {
+        return None;
+    }
+    Some(
+        values
+            .into_iter()
+            .map(|value| {
+                value.map_err(|err| match err {
+                    EvalError::Error(message) => message,
+                    EvalError::Unsupported => unreachable!(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Evaluate an expression of literals with the semantics of openCypher.
+pub fn evaluate(expr: &Expr) -> Eval {
+    evaluate_in(expr, &())
+}
+
+/// Evaluate an expression of a row, the scope has the values of its variables.
+pub fn evaluate_in(expr: &Expr, scope: &dyn Scope) -> Eval {
+    if let Some(value) = scope.expression(expr) {
+        return value;
+    }
+    match &expr.kind {
+        ExprKind::Lit(literal) => literal_value(literal, scope),
+        ExprKind::UnOp(kind, expr) => unary(*kind, expr, scope),
+        ExprKind::BinOp(BinOpKind::And, lhs, rhs) => {
+            logical(lhs, rhs, scope, false, |lhs, rhs| match (lhs, rhs) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            })
+        }
+        ExprKind::BinOp(BinOpKind::Or, lhs, rhs) => {
+            logical(lhs, rhs, scope, true, |lhs, rhs| match (lhs, rhs) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            })
+        }
+        ExprKind::BinOp(BinOpKind::Xor, lhs, rhs) => {
+            let (lhs, rhs) = strict(evaluate_in(lhs, scope), evaluate_in(rhs, scope))?;
+            let value = match (truth(lhs)?, truth(rhs)?) {
+                (Some(lhs), Some(rhs)) => Constant::Boolean(lhs != rhs),
+                _ => Constant::Null,
+            };
+            Ok(value)
+        }
+        ExprKind::BinOp(BinOpKind::Index, lhs, rhs) => match &rhs.kind {
+            ExprKind::BinOp(BinOpKind::Range, start, end) => slice(lhs, start, end, scope),
+            _ => {
+                let (lhs, rhs) = strict(evaluate_in(lhs, scope), evaluate_in(rhs, scope))?;
+                index(lhs, rhs)
+            }
+        },
+        ExprKind::BinOp(kind, lhs, rhs) => {
+            let (lhs, rhs) = strict(evaluate_in(lhs, scope), evaluate_in(rhs, scope))?;
+            binary(*kind, lhs, rhs)
+        }
+        ExprKind::Cmp(first, tails) => {
+            let mut operands = vec![evaluate_in(first, scope)];
+            operands.extend(tails.iter().map(|(_, expr)| evaluate_in(expr, scope)));
+            let operands = strict_all(operands)?;
+            // `a < b < c` is `a < b AND b < c`.
+            let mut value = Some(true);
+            for (idx, (kind, _)) in tails.iter().enumerate() {
+                let result = compare(*kind, &operands[idx], &operands[idx + 1])?;
+                value = match (value, result) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                };
+            }
+            Ok(value.map_or(Constant::Null, Constant::Boolean))
+        }
+        ExprKind::Case(subject, alternatives, default) => {
+            case(subject, alternatives, default, scope)
+        }
+        ExprKind::TypePredicate(expr, negated, value_type) => {
+            let value = evaluate_in(expr, scope)?;
+            let typed = matches!(
+                (&value, value_type),
+                (Constant::Null, _)
+                    | (_, ValueType::Any)
+                    | (Constant::Boolean(_), ValueType::Boolean)
+                    | (Constant::Integer(_), ValueType::Integer)
+                    | (Constant::Float(_), ValueType::Float)
+                    | (Constant::String(_), ValueType::String)
+                    | (Constant::List(_), ValueType::List)
+                    | (Constant::Map(_), ValueType::Map)
+                    | (Constant::Node(_), ValueType::Node)
+                    | (Constant::Relationship(_), ValueType::Relationship)
+            );
+            // `NULL IS NOT :: T` is false, as `NULL IS :: T` is true.
+            Ok(Constant::Boolean(typed != *negated))
+        }
+        ExprKind::Variable(variable) => scope.variable(&variable.get_name()),
+        ExprKind::Property(expr, property) => match evaluate_in(expr, scope)? {
+            Constant::Null => Ok(Constant::Null),
+            Constant::Map(map) => Ok(map.get(&property.name).cloned().unwrap_or(Constant::Null)),
+            entity @ (Constant::Node(_) | Constant::Relationship(_)) => {
+                scope.property(&entity, &property.name)
+            }
+            _ => Err(EvalError::Unsupported),
+        },
+        ExprKind::Label(expr, label) => match evaluate_in(expr, scope)? {
+            Constant::Null => Ok(Constant::Null),
+            entity => scope.has_label(&entity, label),
+        },
+        _ => Err(EvalError::Unsupported),
+    }
+}
+
+/// A literal as the target reads its text, e.g. a whole double is printed as an integer.
+fn literal_value(literal: &Literal, scope: &dyn Scope) -> Eval {
+    match literal {
+        Literal::Null => Ok(Constant::Null),
+        Literal::Boolean(b) => Ok(Constant::Boolean(*b)),
+        Literal::Double(_) | Literal::Integer(_) | Literal::String(_) => {
+            token_value(&literal.to_string())
+        }
+        Literal::List(list) => Ok(Constant::List(strict_all(
+            list.iter().map(|item| evaluate_in(item, scope)).collect(),
+        )?)),
+        Literal::Map(entries) => {
+            let mut map = BTreeMap::new();
+            for (key, value) in entries {
+                let identifier = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !identifier
+                    || map
+                        .insert(key.clone(), evaluate_in(value, scope)?)
+                        .is_some()
+                {
+                    return Err(EvalError::Unsupported);
+                }
+            }
+            Ok(Constant::Map(map))
+        }
+        Literal::NullValue => Err(EvalError::Unsupported),
+    }
+}
+
+/// A number or a quoted string token.
+fn token_value(token: &str) -> Eval {
+    let mut chars = token.chars();
+    match chars.next() {
+        Some(quote @ ('\'' | '"')) => {
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => match chars.next() {
+                        Some(c @ ('\\' | '\'' | '"')) => value.push(c),
+                        _ => return Err(EvalError::Unsupported),
+                    },
+                    Some(c) if c == quote => break,
+                    Some(c) => value.push(c),
+                    None => return Err(EvalError::Unsupported),
+                }
+            }
+            match chars.next() {
+                None => Ok(Constant::String(value)),
+                Some(_) => Err(EvalError::Unsupported),
+            }
+        }
+        _ => {
+            if let Ok(i) = token.parse::<i64>() {
+                Ok(Constant::Integer(i))
+            } else if token.contains(['.', 'e', 'E']) {
+                token
+                    .parse::<f64>()
+                    .map_err(|_| EvalError::Unsupported)
+                    .and_then(float)
+            } else {
+                Err(EvalError::Unsupported)
+            }
+        }
+    }
+}
+
+/// A finite float, JSON has no infinities and NaN.
+fn float(f: f64) -> Eval {
+    if f.is_finite() {
+        Ok(Constant::Float(f))
+    } else {
+        Err(EvalError::Unsupported)
+    }
+}
+
+/// Both operands of an operator which evaluates both, see `strict_all`.
+fn strict(lhs: Eval, rhs: Eval) -> Result<(Constant, Constant), EvalError> {
+    let mut values = strict_all(vec![lhs, rhs])?;
+    let rhs = values.pop().unwrap_or(Constant::Null);
+    let lhs = values.pop().unwrap_or(Constant::Null);
+    Ok((lhs, rhs))
+}
+
+/// The operands of an operator which evaluates all of them, the first error wins,
+/// unless another operand is unsupported.
+fn strict_all(values: Vec<Eval>) -> Result<Vec<Constant>, EvalError> {
+    if values.contains(&Err(EvalError::Unsupported)) {
+        return Err(EvalError::Unsupported);
+    }
+    values.into_iter().collect()
+}
+
+fn truth(value: Constant) -> Result<Option<bool>, EvalError> {
+    match value {
+        Constant::Null => Ok(None),
+        Constant::Boolean(b) => Ok(Some(b)),
+        _ => Err(error("type mismatch: expected a boolean")),
+    }
+}
+
+/// `AND` and `OR` in ternary logic. An error next to the deciding operand depends on
+/// the evaluation order of the engine.
+fn logical(
+    lhs: &Expr,
+    rhs: &Expr,
+    scope: &dyn Scope,
+    deciding: bool,
+    op: fn(Option<bool>, Option<bool>) -> Option<bool>,
+) -> Eval {
+    let lhs = evaluate_in(lhs, scope).and_then(truth);
+    let rhs = evaluate_in(rhs, scope).and_then(truth);
+    match (lhs, rhs) {
+        (Ok(lhs), Ok(rhs)) => Ok(op(lhs, rhs).map_or(Constant::Null, Constant::Boolean)),
+        (Ok(Some(value)), Err(_)) | (Err(_), Ok(Some(value))) if value == deciding => {
+            Err(EvalError::Unsupported)
+        }
+        (Err(EvalError::Unsupported), _) | (_, Err(EvalError::Unsupported)) => {
+            Err(EvalError::Unsupported)
+        }
+        (Err(err), _) | (_, Err(err)) => Err(err),
+    }
+}
+
+fn unary(kind: UnOpKind, expr: &Expr, scope: &dyn Scope) -> Eval {
+    let value = evaluate_in(expr, scope)?;
+    match (kind, value) {
+        (UnOpKind::Parentheses, value) => Ok(value),
+        (UnOpKind::Null, value) => Ok(Constant::Boolean(value == Constant::Null)),
+        (UnOpKind::NotNull, value) => Ok(Constant::Boolean(value != Constant::Null)),
+        (UnOpKind::Not, value) => {
+            Ok(truth(value)?.map_or(Constant::Null, |b| Constant::Boolean(!b)))
+        }
+        (UnOpKind::Pos | UnOpKind::Neg, Constant::Null) => Ok(Constant::Null),
+        (UnOpKind::Pos, value) if value.is_number() => Ok(value),
+        (UnOpKind::Neg, Constant::Integer(i)) => i
+            .checked_neg()
+            .map(Constant::Integer)
+            .ok_or_else(|| error("integer overflow")),
+        (UnOpKind::Neg, Constant::Float(f)) => Ok(Constant::Float(-f)),
+        _ => Err(error("type mismatch: expected a number")),
+    }
+}
+
+fn binary(kind: BinOpKind, lhs: Constant, rhs: Constant) -> Eval {
+    use Constant::*;
+
+    match kind {
+        BinOpKind::Add => match (lhs, rhs) {
+            (List(mut lhs), List(rhs)) => {
+                lhs.extend(rhs);
+                Ok(List(lhs))
+            }
+            // `null` is appended to a list or is the result, depending on the engine.
+            (List(_), Null) | (Null, List(_)) => Err(EvalError::Unsupported),
+            (List(mut lhs), rhs) => {
+                lhs.push(rhs);
+                Ok(List(lhs))
+            }
+            (lhs, List(mut rhs)) => {
+                rhs.insert(0, lhs);
+                Ok(List(rhs))
+            }
+            (String(lhs), String(rhs)) => Ok(String(lhs + &rhs)),
+            (lhs, rhs) => arithmetic(kind, lhs, rhs),
+        },
+        BinOpKind::Sub | BinOpKind::Mul | BinOpKind::Div | BinOpKind::Mod | BinOpKind::Pow => {
+            arithmetic(kind, lhs, rhs)
+        }
+        BinOpKind::In => match rhs {
+            Null => Ok(Null),
+            List(list) => {
+                let mut value = Some(false);
+                for item in &list {
+                    match equals(&lhs, item) {
+                        Some(true) => return Ok(Boolean(true)),
+                        Some(false) => {}
+                        None => value = None,
+                    }
+                }
+                Ok(value.map_or(Null, Boolean))
+            }
+            _ => Err(error("type mismatch: expected a list")),
+        },
+        BinOpKind::StartsWith | BinOpKind::EndsWith | BinOpKind::Contains => match (lhs, rhs) {
+            (String(lhs), String(rhs)) => Ok(Boolean(match kind {
+                BinOpKind::StartsWith => lhs.starts_with(&rhs),
+                BinOpKind::EndsWith => lhs.ends_with(&rhs),
+                _ => lhs.contains(&rhs),
+            })),
+            (Null | String(_), Null | String(_)) => Ok(Null),
+            // null in openCypher, an error in some engines.
+            _ => Err(EvalError::Unsupported),
+        },
+        _ => Err(EvalError::Unsupported),
+    }
+}
+
+/// `- * / % ^` of numbers, and `+` of numbers after the list and string cases.
+fn arithmetic(kind: BinOpKind, lhs: Constant, rhs: Constant) -> Eval {
+    use Constant::*;
+
+    let overflow = || error("integer overflow");
+    match (lhs, rhs) {
+        (Null, Null) => Ok(Null),
+        (Null, value) | (value, Null) if value.is_number() => Ok(Null),
+        // null with other types, and strings with numbers, e.g. `'a' + 1`, are engine
+        // specific.
+        (Null, _) | (_, Null) => Err(EvalError::Unsupported),
+        (String(_), Integer(_) | Float(_)) | (Integer(_) | Float(_), String(_)) => {
+            Err(EvalError::Unsupported)
+        }
+        (Integer(lhs), Integer(rhs)) => match kind {
+            BinOpKind::Add => lhs.checked_add(rhs).map(Integer).ok_or_else(overflow),
+            BinOpKind::Sub => lhs.checked_sub(rhs).map(Integer).ok_or_else(overflow),
+            BinOpKind::Mul => lhs.checked_mul(rhs).map(Integer).ok_or_else(overflow),
+            BinOpKind::Div if rhs == 0 => Err(error("division by zero")),
+            BinOpKind::Div => lhs.checked_div(rhs).map(Integer).ok_or_else(overflow),
+            BinOpKind::Mod if rhs == 0 => Err(error("division by zero")),
+            // `i64::MIN % -1` is 0 on the JVM and an overflow elsewhere.
+            BinOpKind::Mod => lhs
+                .checked_rem(rhs)
+                .map(Integer)
+                .ok_or(EvalError::Unsupported),
+            _ => float((lhs as f64).powf(rhs as f64)),
+        },
+        (lhs, rhs) => match (lhs.number(), rhs.number()) {
+            (Some(lhs), Some(rhs)) => float(match kind {
+                BinOpKind::Add => lhs + rhs,
+                BinOpKind::Sub => lhs - rhs,
+                BinOpKind::Mul => lhs * rhs,
+                BinOpKind::Div => lhs / rhs,
+                BinOpKind::Mod => lhs % rhs,
+                _ => lhs.powf(rhs),
+            }),
+            _ => Err(error("type mismatch: expected numbers")),
+        },
+    }
+}
+
+/// `list[idx]` and `map[key]`.
+fn index(lhs: Constant, rhs: Constant) -> Eval {
+    match (lhs, rhs) {
+        (Constant::Null, _) | (_, Constant::Null) => Ok(Constant::Null),
+        (Constant::List(list), Constant::Integer(idx)) => {
+            let idx = if idx < 0 {
+                idx + list.len() as i64
+            } else {
+                idx
+            };
+            Ok(usize::try_from(idx)
+                .ok()
+                .and_then(|idx| list.get(idx).cloned())
+                .unwrap_or(Constant::Null))
+        }
+        (Constant::Map(map), Constant::String(key)) => {
+            Ok(map.get(&key).cloned().unwrap_or(Constant::Null))
+        }
+        _ => Err(EvalError::Unsupported),
+    }
+}
+
+/// `list[start..end]`, either bound may be left out.
+fn slice(list: &Expr, start: &Expr, end: &Expr, scope: &dyn Scope) -> Eval {
+    let bound = |expr: &Expr, default: i64| match &expr.kind {
+        ExprKind::Lit(Literal::NullValue) => Ok(Constant::Integer(default)),
+        _ => evaluate_in(expr, scope),
+    };
+    let values = strict_all(vec![
+        evaluate_in(list, scope),
+        bound(start, 0),
+        bound(end, i64::MAX),
+    ])?;
+    let list = match &values[0] {
+        Constant::Null => return Ok(Constant::Null),
+        Constant::List(list) => list,
+        _ => return Err(EvalError::Unsupported),
+    };
+    let len = list.len() as i64;
+    let mut positions = vec![];
+    for bound in &values[1..] {
+        match bound {
+            Constant::Null => return Ok(Constant::Null),
+            Constant::Integer(i) if *i < 0 => positions.push((len + i).max(0)),
+            Constant::Integer(i) => positions.push((*i).min(len)),
+            _ => return Err(EvalError::Unsupported),
+        }
+    }
+    let (start, end) = (positions[0] as usize, positions[1] as usize);
+    Ok(Constant::List(
+        list.get(start..end).unwrap_or_default().to_vec(),
+    ))
+}
+
+/// `=` in ternary logic, values of different types are not equal.
+pub fn equals(lhs: &Constant, rhs: &Constant) -> Option<bool> {
+    use Constant::*;
+
+    match (lhs, rhs) {
+        (Null, _) | (_, Null) => None,
+        (Integer(lhs), Integer(rhs)) => Some(lhs == rhs),
+        (Node(lhs), Node(rhs)) | (Relationship(lhs), Relationship(rhs)) => Some(lhs == rhs),
+        (Boolean(lhs), Boolean(rhs)) => Some(lhs == rhs),
+        (String(lhs), String(rhs)) => Some(lhs == rhs),
+        (List(lhs), List(rhs)) => {
+            if lhs.len() != rhs.len() {
+                return Some(false);
+            }
+            all(lhs.iter().zip(rhs).map(|(lhs, rhs)| equals(lhs, rhs)))
+        }
+        (Map(lhs), Map(rhs)) => {
+            if !lhs.keys().eq(rhs.keys()) {
+                return Some(false);
+            }
+            all(lhs
+                .values()
+                .zip(rhs.values())
+                .map(|(lhs, rhs)| equals(lhs, rhs)))
+        }
+        (lhs, rhs) => match (lhs.number(), rhs.number()) {
+            (Some(lhs), Some(rhs)) => Some(lhs == rhs),
+            _ => Some(false),
+        },
+    }
+}
+
+/// Ternary conjunction.
+fn all(values: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    let mut result = Some(true);
+    for value in values {
+        match value {
+            Some(false) => return Some(false),
+            None => result = None,
+            Some(true) => {}
+        }
+    }
+    result
+}
+
+fn compare(kind: CmpKind, lhs: &Constant, rhs: &Constant) -> Result<Option<bool>, EvalError> {
+    let ordering = match kind {
+        CmpKind::Eq => return Ok(equals(lhs, rhs)),
+        CmpKind::Ne => return Ok(equals(lhs, rhs).map(|b| !b)),
+        _ => match (lhs, rhs) {
+            (Constant::Null, _) | (_, Constant::Null) => None,
+            (Constant::Integer(lhs), Constant::Integer(rhs)) => Some(lhs.cmp(rhs)),
+            (Constant::String(lhs), Constant::String(rhs)) => Some(lhs.cmp(rhs)),
+            (Constant::Boolean(lhs), Constant::Boolean(rhs)) => Some(lhs.cmp(rhs)),
+            // the order of lists, maps and entities is engine specific.
+            (
+                Constant::List(_)
+                | Constant::Map(_)
+                | Constant::Node(_)
+                | Constant::Relationship(_),
+                _,
+            )
+            | (
+                _,
+                Constant::List(_)
+                | Constant::Map(_)
+                | Constant::Node(_)
+                | Constant::Relationship(_),
+            ) => return Err(EvalError::Unsupported),
+            (lhs, rhs) => match (lhs.number(), rhs.number()) {
+                (Some(lhs), Some(rhs)) => lhs.partial_cmp(&rhs),
+                // values of different types are not comparable.
+                _ => None,
+            },
+        },
+    };
+    Ok(ordering.map(|ordering| match kind {
+        CmpKind::Lt => ordering == Ordering::Less,
+        CmpKind::Gt => ordering == Ordering::Greater,
+        CmpKind::Le => ordering != Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    }))
+}
+
+/// `CASE`, only the taken alternative is evaluated by the engine, an error in another
+/// one may still be raised by its type checker.
+fn case(
+    subject: &Option<Box<Expr>>,
+    alternatives: &[CaseAlternative],
+    default: &Option<Box<Expr>>,
+    scope: &dyn Scope,
+) -> Eval {
+    // an error ends the evaluation, unless a later part is beyond the evaluator or
+    // raises an error of its own.
+    let end = |err: EvalError, rest: Vec<&Expr>| {
+        if rest
+            .into_iter()
+            .chain(default.as_deref())
+            .all(|expr| evaluate_in(expr, scope).is_ok())
+        {
+            err
+        } else {
+            EvalError::Unsupported
+        }
+    };
+    fn parts(alternatives: &[CaseAlternative]) -> Vec<&Expr> {
+        alternatives
+            .iter()
+            .flat_map(|alternative| [alternative.condition.as_ref(), alternative.value.as_ref()])
+            .collect()
+    }
+    let subject = subject
+        .as_ref()
+        .map(|subject| evaluate_in(subject, scope))
+        .transpose()
+        .map_err(|err| end(err, parts(alternatives)))?;
+    let mut taken = None;
+    let mut skipped = vec![];
+    for (idx, alternative) in alternatives.iter().enumerate() {
+        if taken.is_some() {
+            skipped.push(evaluate_in(&alternative.condition, scope).map(|_| ()));
+            skipped.push(evaluate_in(&alternative.value, scope).map(|_| ()));
+            continue;
+        }
+        let condition = evaluate_in(&alternative.condition, scope).map_err(|err| {
+            let mut rest = parts(&alternatives[idx + 1..]);
+            rest.push(&alternative.value);
+            end(err, rest)
+        })?;
+        let matched = match &subject {
+            Some(subject) => equals(subject, &condition) == Some(true),
+            None => match condition {
+                Constant::Boolean(b) => b,
+                Constant::Null => false,
+                _ => return Err(EvalError::Unsupported),
+            },
+        };
+        if matched {
+            taken = Some(evaluate_in(&alternative.value, scope));
+        } else {
+            skipped.push(evaluate_in(&alternative.value, scope).map(|_| ()));
+        }
+    }
+    let value = match taken {
+        Some(value) => {
+            if let Some(default) = default {
+                skipped.push(evaluate_in(default, scope).map(|_| ()));
+            }
+            value
+        }
+        None => default
+            .as_ref()
+            .map_or(Ok(Constant::Null), |default| evaluate_in(default, scope)),
+    };
+    if skipped.iter().any(|skipped| skipped.is_err()) {
+        return Err(EvalError::Unsupported);
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, token_value, Constant, EvalError};
+    use crate::common::{
+        BinOpKind, CaseAlternative, CmpKind, Expr, ExprKind, Literal, UnOpKind, ValueType, Variable,
+    };
+
+    fn lit(literal: Literal) -> Box<Expr> {
+        Box::new(Expr::from(ExprKind::Lit(literal)))
+    }
+
+    fn int(i: u64) -> Box<Expr> {
+        lit(Literal::Integer(i))
+    }
+
+    fn bin(kind: BinOpKind, lhs: Box<Expr>, rhs: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::from(ExprKind::BinOp(kind, lhs, rhs)))
+    }
+
+    fn eval(expr: Box<Expr>) -> Result<Constant, EvalError> {
+        evaluate(&expr)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let null = || lit(Literal::Null);
+        let boolean = |b| lit(Literal::Boolean(b));
+        let string = |s: &str| lit(Literal::String(format!("'{}'", s)));
+        let error = |message: &str| Err(EvalError::Error(message.to_string()));
+
+        // literals as the target reads them.
+        assert_eq!(
+            token_value("'a\\'b'"),
+            Ok(Constant::String("a'b".to_string()))
+        );
+        assert_eq!(eval(lit(Literal::Double(3.0))), Ok(Constant::Float(3.0)));
+        assert_eq!(eval(lit(Literal::Double(2.5))), Ok(Constant::Float(2.5)));
+        assert_eq!(
+            eval(lit(Literal::Integer(u64::MAX))),
+            Err(EvalError::Unsupported)
+        );
+        assert_eq!(
+            eval(lit(Literal::String("COUNT (*)".to_string()))),
+            Err(EvalError::Unsupported)
+        );
+
+        // ternary logic.
+        assert_eq!(
+            eval(bin(BinOpKind::And, null(), boolean(false))),
+            Ok(Constant::Boolean(false))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::And, null(), boolean(true))),
+            Ok(Constant::Null)
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Or, null(), boolean(true))),
+            Ok(Constant::Boolean(true))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Xor, null(), boolean(true))),
+            Ok(Constant::Null)
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::And, int(1), boolean(true))),
+            error("type mismatch: expected a boolean")
+        );
+        // the short circuit is up to the engine.
+        assert_eq!(
+            eval(bin(
+                BinOpKind::And,
+                boolean(false),
+                bin(BinOpKind::Div, int(1), int(0))
+            )),
+            Err(EvalError::Unsupported)
+        );
+
+        // integer overflow and division by zero.
+        let max = || int(i64::MAX as u64);
+        assert_eq!(
+            eval(bin(BinOpKind::Add, max(), int(1))),
+            error("integer overflow")
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Mul, max(), int(2))),
+            error("integer overflow")
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Sub, max(), int(1))),
+            Ok(Constant::Integer(i64::MAX - 1))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Mod, int(1), int(0))),
+            error("division by zero")
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Div, int(7), int(2))),
+            Ok(Constant::Integer(3))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Pow, int(2), int(3))),
+            Ok(Constant::Float(8.0))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Div, int(1), lit(Literal::Double(0.5)))),
+            Ok(Constant::Float(2.0))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Add, null(), int(1))),
+            Ok(Constant::Null)
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Sub, boolean(true), int(1))),
+            error("type mismatch: expected numbers")
+        );
+
+        // strings and lists.
+        assert_eq!(
+            eval(bin(BinOpKind::Add, string("ab"), string("c"))),
+            Ok(Constant::String("abc".to_string()))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::StartsWith, string("ab"), string("a"))),
+            Ok(Constant::Boolean(true))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Contains, string("ab"), null())),
+            Ok(Constant::Null)
+        );
+        let list = || lit(Literal::List(vec![*int(1), *int(2), *int(3)]));
+        let ints =
+            |ints: &[i64]| Constant::List(ints.iter().map(|i| Constant::Integer(*i)).collect());
+        assert_eq!(
+            eval(bin(BinOpKind::Add, list(), int(4))),
+            Ok(ints(&[1, 2, 3, 4]))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Add, int(0), list())),
+            Ok(ints(&[0, 1, 2, 3]))
+        );
+        assert_eq!(
+            eval(bin(
+                BinOpKind::Index,
+                list(),
+                Box::new(Expr::from(ExprKind::UnOp(UnOpKind::Neg, int(1))))
+            )),
+            Ok(Constant::Integer(3))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Index, list(), int(5))),
+            Ok(Constant::Null)
+        );
+        let range = |start, end| bin(BinOpKind::Range, start, end);
+        assert_eq!(
+            eval(bin(
+                BinOpKind::Index,
+                list(),
+                range(int(1), lit(Literal::NullValue))
+            )),
+            Ok(ints(&[2, 3]))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::Index, list(), range(int(2), int(1)))),
+            Ok(ints(&[]))
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::In, int(2), list())),
+            Ok(Constant::Boolean(true))
+        );
+        assert_eq!(
+            eval(bin(
+                BinOpKind::In,
+                int(4),
+                lit(Literal::List(vec![*int(1), *null()]))
+            )),
+            Ok(Constant::Null)
+        );
+        assert_eq!(
+            eval(bin(BinOpKind::In, null(), lit(Literal::List(vec![])))),
+            Ok(Constant::Boolean(false))
+        );
+
+        // comparisons across types and chains.
+        let cmp = |first, tails: Vec<(CmpKind, Box<Expr>)>| {
+            Box::new(Expr::from(ExprKind::Cmp(first, tails)))
+        };
+        assert_eq!(
+            eval(cmp(int(1), vec![(CmpKind::Eq, lit(Literal::Double(1.5)))])),
+            Ok(Constant::Boolean(false))
+        );
+        assert_eq!(
+            eval(cmp(int(1), vec![(CmpKind::Ne, string("1"))])),
+            Ok(Constant::Boolean(true))
+        );
+        assert_eq!(
+            eval(cmp(int(1), vec![(CmpKind::Lt, string("1"))])),
+            Ok(Constant::Null)
+        );
+        assert_eq!(
+            eval(cmp(
+                int(1),
+                vec![(CmpKind::Lt, int(2)), (CmpKind::Lt, int(2))]
+            )),
+            Ok(Constant::Boolean(false))
+        );
+        assert_eq!(
+            eval(cmp(list(), vec![(CmpKind::Eq, list())])),
+            Ok(Constant::Boolean(true))
+        );
+        assert_eq!(
+            eval(cmp(null(), vec![(CmpKind::Eq, null())])),
+            Ok(Constant::Null)
+        );
+
+        // CASE, IS NULL and type predicates.
+        let alternative = |condition, value| CaseAlternative { condition, value };
+        let case = Box::new(Expr::from(ExprKind::Case(
+            Some(int(2)),
+            vec![
+                alternative(int(1), string("a")),
+                alternative(int(2), string("b")),
+            ],
+            Some(string("c")),
+        )));
+        assert_eq!(eval(case), Ok(Constant::String("b".to_string())));
+        let case = Box::new(Expr::from(ExprKind::Case(
+            None,
+            vec![alternative(null(), int(1))],
+            None,
+        )));
+        assert_eq!(eval(case), Ok(Constant::Null));
+        // an error in the subject, unless an alternative is beyond the evaluator.
+        let variable = || {
+            Box::new(Expr::from(ExprKind::Variable(Variable::new(
+                "v0".to_string(),
+            ))))
+        };
+        let case = |value| {
+            Box::new(Expr::from(ExprKind::Case(
+                Some(bin(BinOpKind::Xor, int(2), boolean(false))),
+                vec![alternative(int(1), value)],
+                None,
+            )))
+        };
+        assert!(matches!(eval(case(int(1))), Err(EvalError::Error(_))));
+        assert_eq!(eval(case(variable())), Err(EvalError::Unsupported));
+        assert_eq!(
+            eval(Box::new(Expr::from(ExprKind::UnOp(UnOpKind::Null, null())))),
+            Ok(Constant::Boolean(true))
+        );
+        let typed = |expr, negated, value_type| {
+            Box::new(Expr::from(ExprKind::TypePredicate(
+                expr, negated, value_type,
+            )))
+        };
+        assert_eq!(
+            eval(typed(int(1), false, ValueType::Integer)),
+            Ok(Constant::Boolean(true))
+        );
+        assert_eq!(
+            eval(typed(null(), true, ValueType::Float)),
+            Ok(Constant::Boolean(false))
+        );
+        assert_eq!(
+            eval(Box::new(Expr::from(ExprKind::Variable(Variable::new(
+                "v0".to_string()
+            ))))),
+            Err(EvalError::Unsupported)
+        );
+
+        assert!(Constant::Float(0.1 + 0.2).matches(&serde_json::json!(0.3)));
+        assert!(ints(&[1, 2]).matches(&serde_json::json!([1, 2])));
+        assert!(!ints(&[1, 2]).matches(&serde_json::json!([1, 2.0])));
+        assert!(!Constant::Float(1.0).matches(&serde_json::json!(1)));
+        assert!(!Constant::String("1".to_string()).matches(&serde_json::json!(1)));
+    }
+}
@@ -1,7 +1,7 @@
 use super::{cypher_gen::CypherGenerator, ExpressionNodeVisitor};
 use crate::common::{
-    constants, BinOpKind, CaseAlternative, CmpKind, DataKind, Diagnostic, Expr, ExprKind, Literal,
-    PredicateFunctionKind, RandomGenerator, SubQueryKind, UnOpKind,
+    constants, BinOpKind, CaseAlternative, CmpKind, DataKind, DataType, Diagnostic, Expr, ExprKind,
+    Literal, PredicateFunctionKind, RandomGenerator, SubQueryKind, UnOpKind, ValueType,
 };
 
 pub struct ExprGenerator<'a> {
@@ -29,10 +29,37 @@ impl ExprGenerator<'_> {
     pub fn visit(&mut self) -> Expr {
         // self.complexity = 0;
         match self.visit_expression() {
-            Ok(epxr) => epxr,
+            Ok(epxr) => match self.cypher.stress_limit(|stress| stress.expression_depth) {
+                Some(depth) => {
+                    let depth = self.random.under(depth as i32 + 1);
+                    self.nest(epxr, depth)
+                }
+                None => epxr,
+            },
             _ => self.visit(),
         }
     }
+
+    /// Nest the expression `depth` levels deep, each level keeps its value and type:
+    /// `(e)` or `CASE WHEN true THEN e END`.
+    pub fn nest(&mut self, mut expr: Expr, depth: i32) -> Expr {
+        for _ in 0..depth {
+            let kind = if self.random.bool() {
+                ExprKind::UnOp(UnOpKind::Parentheses, Box::new(expr))
+            } else {
+                ExprKind::Case(
+                    None,
+                    vec![CaseAlternative {
+                        condition: Box::new(Expr::from(ExprKind::Lit(Literal::Boolean(true)))),
+                        value: Box::new(expr),
+                    }],
+                    None,
+                )
+            };
+            expr = Expr::from(kind);
+        }
+        expr
+    }
 }
 
 impl ExprGenerator<'_> {
@@ -77,8 +104,47 @@ impl ExprGenerator<'_> {
         kinds[self.random.d2() as usize % 2]
     }
 
+    /// `v.prop <cmp> literal`, the literal comes from the property domain.
+    pub fn property_predicate(&mut self) -> Option<Expr> {
+        let property = self.cypher.gen_property_expr(DataKind::Vertex)?;
+        let literal = self.property_literal(&property)?;
+        let kind = match &property.kind {
+            ExprKind::Property(_, prop) => match prop.prop_type {
+                DataType::Bool | DataType::String => {
+                    [CmpKind::Eq, CmpKind::Ne][self.random.d2() as usize % 2]
+                }
+                _ => self.random_cmp_kind(),
+            },
+            _ => self.random_cmp_kind(),
+        };
+
+        Some(Expr::from(ExprKind::Cmp(
+            Box::new(property),
+            vec![(kind, Box::new(literal))],
+        )))
+    }
+
+    /// A value of the property domain if the expression is a property lookup.
+    fn property_literal(&mut self, expr: &Expr) -> Option<Expr> {
+        match &expr.kind {
+            ExprKind::Property(_, prop) => Some(Expr::from(prop.random_value(&mut self.random))),
+            _ => None,
+        }
+    }
+
     // Random Literal Gen.
     pub fn random_literal(&mut self) -> Literal {
+        if let Some(list_length) = self.cypher.stress_limit(|stress| stress.list_length) {
+            let list = (0..self.random.under(list_length as i32 + 1))
+                .map(|_| Expr::from(ExprKind::Lit(Literal::Integer(self.random.under(5) as u64))))
+                .collect();
+            return Literal::List(list);
+        }
+        // the integers next to the overflow of 32 and 64 bits.
+        if self.cypher.constant && self.random.d6() == 1 {
+            let boundaries = [i64::MAX as u64, i32::MAX as u64, 1 << 31, 1 << 32];
+            return Literal::Integer(boundaries[self.random.under(4) as usize]);
+        }
         if self.random.bool() {
             Literal::Integer(self.random.under(5) as u64)
         } else if self.random.bool() {
@@ -86,7 +152,7 @@ impl ExprGenerator<'_> {
         } else if self.random.bool() {
             Literal::Double(self.random.under(5) as f64)
         } else if self.random.d6() == 1 {
-            Literal::String(self.random.under(5).to_string())
+            Literal::String(format!("'{}'", self.random.under(5)))
         } else if self.random.d12() == 1 {
             let expr = Expr::from(ExprKind::Lit(Literal::Integer(self.random.under(5) as u64)));
             Literal::List(vec![expr])
@@ -99,6 +165,11 @@ impl ExprGenerator<'_> {
         }
     }
 
+    /// Number of elements of a generated list.
+    pub fn random_list_length(&mut self) -> i32 {
+        self.random.range(1, 4)
+    }
+
     pub fn random_predicate_function_kind(&mut self) -> PredicateFunctionKind {
         let kinds = [
             PredicateFunctionKind::All,
@@ -127,7 +198,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
         // random loop
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self.random.chance(self.cypher.weights.or)) && (self.complexity < self.limit) {
                 // new or clause, increase complexity.
                 self.complexity += 1;
                 let rhs = self.visit_xor_expression()?;
@@ -147,7 +218,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
 
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self.random.chance(self.cypher.weights.xor)) && (self.complexity < self.limit) {
                 // new xor clause, increase complexity.
                 self.complexity += 1;
                 let rhs = self.visit_xor_expression()?;
@@ -167,7 +238,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
 
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self.random.chance(self.cypher.weights.and)) && (self.complexity < self.limit) {
                 // new and clause, increase complexity.
                 self.complexity += 1;
                 let rhs = self.visit_not_expression()?;
@@ -185,7 +256,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
     fn visit_not_expression(&mut self) -> Self::Output {
         let mut not_expr = self.visit_comparison_expression()?;
 
-        if (self.random.d12() == 1) && (self.complexity < self.limit) {
+        if (self.random.chance(self.cypher.weights.not)) && (self.complexity < self.limit) {
             // new not clause.
             self.complexity += 1;
             // new not expression.
@@ -204,11 +275,17 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
 
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self.random.chance(self.cypher.weights.comparison))
+                && (self.complexity < self.limit)
+            {
                 // new cmp clause, increase complexity.
                 self.complexity += 1;
                 let kind = self.random_cmp_kind();
-                let rhs = self.visit_add_or_subtract_expression()?;
+                // compare a property with its domain now and then.
+                let rhs = match self.property_literal(&cmp_expr) {
+                    Some(literal) if self.random.bool() => literal,
+                    _ => self.visit_add_or_subtract_expression()?,
+                };
                 tails.push((kind, Box::new(rhs)));
             }
         }
@@ -227,7 +304,9 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
         // random loop
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self.random.chance(self.cypher.weights.add_subtract))
+                && (self.complexity < self.limit)
+            {
                 // new add/subtract clause, increase complexity.
                 self.complexity += 1;
                 let rhs = self.visit_multiply_divide_modulo_expression()?;
@@ -252,7 +331,11 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
         // random loop
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self
+                .random
+                .chance(self.cypher.weights.multiply_divide_modulo))
+                && (self.complexity < self.limit)
+            {
                 // new *///% clause, increase complexity.
                 self.complexity += 1;
                 let rhs = self.visit_power_of_expression()?;
@@ -277,7 +360,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
         // random loop
         for _ in 0..self.loop_limit {
             // complexity limit.
-            if (self.random.d20() == 1) && (self.complexity < self.limit) {
+            if (self.random.chance(self.cypher.weights.power)) && (self.complexity < self.limit) {
                 // new power clause, increase complexity.
                 self.complexity += 1;
                 let rhs = self.visit_unary_add_or_subtract_expression()?;
@@ -295,7 +378,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
     fn visit_unary_add_or_subtract_expression(&mut self) -> Self::Output {
         let mut unary_expr = self.visit_string_list_null_operator_expression()?;
 
-        if (self.random.d9() == 1) && (self.complexity < self.limit) {
+        if (self.random.chance(self.cypher.weights.unary)) && (self.complexity < self.limit) {
             // new not clause.
             self.complexity += 1;
             // new not expression.
@@ -313,92 +396,102 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
 
         // expr loop
         for _ in 0..self.random.under(3) {
-            if self.random.d20() == 1 {
-                if (self.complexity < self.limit) && (self.random.d6() == 1) {
-                    // StringOperatorExpression
+            if (self.complexity < self.limit)
+                && self.random.chance(self.cypher.weights.string_operator)
+            {
+                // StringOperatorExpression
+                self.complexity += 1;
+                let string_expr = self.visit_property_or_labels_expression()?;
+                let kind = ExprKind::BinOp(
+                    self.random_string_kind(),
+                    Box::new(query_expr),
+                    Box::new(string_expr),
+                );
+                query_expr = Expr::from(kind);
+            } else if (self.complexity < self.limit)
+                && self.random.chance(self.cypher.weights.list_operator)
+            {
+                // ListOperatorExpression: In | [Expression] | [Expression..Expression]
+                if self.random.d6() > 2 {
+                    // In PropertyOrLabelsExpression
                     self.complexity += 1;
-                    let string_expr = self.visit_property_or_labels_expression()?;
+                    let list_expr = self.visit_property_or_labels_expression()?;
+                    let kind =
+                        ExprKind::BinOp(BinOpKind::In, Box::new(query_expr), Box::new(list_expr));
+                    query_expr = Expr::from(kind);
+                } else if self.random.d6() == 1 {
+                    // [Expression]
+                    self.complexity += 1;
+                    let list_expr = self.visit();
                     let kind = ExprKind::BinOp(
-                        self.random_string_kind(),
+                        BinOpKind::Index,
                         Box::new(query_expr),
-                        Box::new(string_expr),
+                        Box::new(list_expr),
                     );
                     query_expr = Expr::from(kind);
-                } else if (self.complexity < self.limit) && (self.random.d6() == 1) {
-                    // ListOperatorExpression: In | [Expression] | [Expression..Expression]
-                    if self.random.d6() > 2 {
-                        // In PropertyOrLabelsExpression
-                        self.complexity += 1;
-                        let list_expr = self.visit_property_or_labels_expression()?;
-                        let kind = ExprKind::BinOp(
-                            BinOpKind::In,
-                            Box::new(query_expr),
-                            Box::new(list_expr),
-                        );
-                        query_expr = Expr::from(kind);
-                    } else if self.random.d6() == 1 {
-                        // [Expression]
-                        self.complexity += 1;
-                        let list_expr = self.visit();
-                        let kind = ExprKind::BinOp(
-                            BinOpKind::Index,
-                            Box::new(query_expr),
-                            Box::new(list_expr),
-                        );
-                        query_expr = Expr::from(kind);
-                    } else if self.random.d12() == 1 {
-                        // [(Expression)?..(Expression)?]
-                        self.complexity += 1;
-                        let (start_expr, end_expr) = if self.random.bool() {
-                            let start_number = self.random.d2();
-                            if self.random.bool() {
-                                let end_number = start_number + self.random.d6();
-                                (
-                                    Expr::from(ExprKind::Lit(Literal::Integer(
-                                        start_number as u64,
-                                    ))),
-                                    Expr::from(ExprKind::Lit(Literal::Integer(end_number as u64))),
-                                )
-                            } else {
-                                (
-                                    Expr::from(ExprKind::Lit(Literal::Integer(
-                                        start_number as u64,
-                                    ))),
-                                    Expr::from(ExprKind::Lit(Literal::NullValue)),
-                                )
-                            }
-                        } else if self.random.bool() {
-                            let end_number = self.random.d6();
+                } else if self.random.d12() == 1 {
+                    // [(Expression)?..(Expression)?]
+                    self.complexity += 1;
+                    let (start_expr, end_expr) = if self.random.bool() {
+                        let start_number = self.random.d2();
+                        if self.random.bool() {
+                            let end_number = start_number + self.random.d6();
                             (
-                                Expr::from(ExprKind::Lit(Literal::NullValue)),
+                                Expr::from(ExprKind::Lit(Literal::Integer(start_number as u64))),
                                 Expr::from(ExprKind::Lit(Literal::Integer(end_number as u64))),
                             )
                         } else {
                             (
-                                Expr::from(ExprKind::Lit(Literal::NullValue)),
+                                Expr::from(ExprKind::Lit(Literal::Integer(start_number as u64))),
                                 Expr::from(ExprKind::Lit(Literal::NullValue)),
                             )
-                        };
-
-                        let list_expr = Expr::from(ExprKind::BinOp(
-                            BinOpKind::Range,
-                            Box::new(start_expr),
-                            Box::new(end_expr),
-                        ));
-
-                        let kind = ExprKind::BinOp(
-                            BinOpKind::Index,
-                            Box::new(query_expr),
-                            Box::new(list_expr),
-                        );
-                        query_expr = Expr::from(kind);
-                    }
-                } else if (self.complexity < self.limit) && (self.random.d6() == 1) {
-                    // NullOperatorExpression
-                    self.complexity += 1;
-                    let kind = ExprKind::UnOp(self.random_null_kind(), Box::new(query_expr));
+                        }
+                    } else if self.random.bool() {
+                        let end_number = self.random.d6();
+                        (
+                            Expr::from(ExprKind::Lit(Literal::NullValue)),
+                            Expr::from(ExprKind::Lit(Literal::Integer(end_number as u64))),
+                        )
+                    } else {
+                        (
+                            Expr::from(ExprKind::Lit(Literal::NullValue)),
+                            Expr::from(ExprKind::Lit(Literal::NullValue)),
+                        )
+                    };
+
+                    let list_expr = Expr::from(ExprKind::BinOp(
+                        BinOpKind::Range,
+                        Box::new(start_expr),
+                        Box::new(end_expr),
+                    ));
+
+                    let kind = ExprKind::BinOp(
+                        BinOpKind::Index,
+                        Box::new(query_expr),
+                        Box::new(list_expr),
+                    );
                     query_expr = Expr::from(kind);
                 }
+            } else if (self.complexity < self.limit)
+                && self.random.chance(self.cypher.weights.null_operator)
+            {
+                // NullOperatorExpression
+                self.complexity += 1;
+                let kind = ExprKind::UnOp(self.random_null_kind(), Box::new(query_expr));
+                query_expr = Expr::from(kind);
+            } else if (self.complexity < self.limit)
+                && self.random.chance(self.cypher.weights.type_predicate)
+            {
+                // TypePredicateExpression: `IS` `NOT`? `::` Type
+                self.complexity += 1;
+                let value_type =
+                    ValueType::ALL[self.random.under(ValueType::ALL.len() as _) as usize];
+                let kind = ExprKind::TypePredicate(
+                    Box::new(query_expr),
+                    self.random.d6() == 1,
+                    value_type,
+                );
+                query_expr = Expr::from(kind);
             }
         }
 
@@ -421,7 +514,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
         if (self.complexity < self.limit) && self.random.bool() {
             // PropertyLookup*
             for _ in 0..self.random.under(3) {
-                if self.random.d12() == 1 {
+                if self.random.chance(self.cypher.weights.property_lookup) {
                     let property = self
                         .cypher
                         .graph_schema
@@ -437,7 +530,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
         {
             // Nodelabels
             for _ in 0..self.random.under(3) {
-                if self.random.d12() == 1 {
+                if self.random.chance(self.cypher.weights.node_labels) {
                     let node_label = self.cypher.graph_schema.rand_vertex_label(&mut self.random);
                     query_expr = Expr::from(ExprKind::Label(Box::new(query_expr), node_label));
                 }
@@ -451,13 +544,26 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
     ///
     /// Literal | Parameter | Case Expression | COUNT (*)
     fn visit_atom(&mut self) -> Self::Output {
-        let select_number = self.random.d100();
+        let weights = &self.cypher.weights;
+        // subqueries only while the query limit lasts.
+        let nested = |weight: u32| if self.cypher.limit > 0 { weight } else { 0 };
+        let atom_weights = [
+            weights.literal,
+            weights.case,
+            weights.count_star,
+            weights.list_comprehension,
+            weights.pattern_comprehension,
+            weights.predicate_function,
+            weights.relationships_pattern,
+            weights.parenthesized,
+            nested(weights.exists_subquery),
+            weights.variable,
+            nested(weights.count_subquery),
+        ];
 
-        match select_number {
-            // Literal Expression
-            0..=10 => Ok(Expr::from(ExprKind::Lit(self.random_literal()))),
+        match self.random.weighted(&atom_weights) {
             // CaseExpression
-            11..=13 => {
+            Some(1) => {
                 self.complexity += 1;
                 let case_expr = if self.random.d6() == 1 {
                     Some(Box::new(self.visit()))
@@ -488,11 +594,11 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
                 )))
             }
             // COUNT (*)
-            14..=17 => Ok(Expr::from(ExprKind::Lit(Literal::String(
+            Some(2) => Ok(Expr::from(ExprKind::Lit(Literal::String(
                 "COUNT (*)".to_string(),
             )))),
             // ListComprehension: [FilterExpression (|Expression)? ]
-            21..=23 => {
+            Some(3) => {
                 self.complexity += 1;
 
                 let in_expression = self.visit();
@@ -520,21 +626,27 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
                 Ok(Expr::from(ExprKind::Lit(Literal::List(vec![filter_expr]))))
             }
             // PatternComprehension: [(variable =)? RelationShipsPattern (Where)? | Expression]
-            24..=29 => {
+            Some(4) => {
                 self.complexity += 1;
 
+                // the variables of the pattern are visible in its WHERE and projection only.
+                let scope = self.cypher.variables.nested_scope();
+                let outer = std::mem::replace(&mut self.cypher.variables, scope);
+                let pattern = self.cypher.expr_pattern();
                 let where_clause = if self.random.d20() == 1 {
                     Some(Box::new(self.visit()))
                 } else {
                     None
                 };
+                let rhs = self.visit();
+                let scope = std::mem::replace(&mut self.cypher.variables, outer);
+                self.cypher.variables.merge_scope(&scope, &[]);
 
                 let lhs = Expr::from(ExprKind::SubQuery(
                     SubQueryKind::PredicatePattern,
-                    Box::new(self.cypher.expr_pattern()?),
+                    Box::new(pattern?),
                     where_clause,
                 ));
-                let rhs = self.visit();
 
                 let list_expr = Expr::from(ExprKind::BinOp(
                     BinOpKind::Pipe,
@@ -546,7 +658,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
             }
             // ALL|ANY|NONE|SINGLE (FilterExpression)
             // FilterExpression: Variable IN Expression (Where Expression)?
-            30..=35 => {
+            Some(5) => {
                 self.complexity += 1;
 
                 let in_expression = self.visit();
@@ -569,10 +681,15 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
                 Ok(Expr::from(kind))
             }
             // RelationShipsPattern
-            36..=40 => {
+            Some(6) => {
                 self.complexity += 1;
 
-                let pattern_query = self.cypher.expr_relation_pattern()?;
+                let scope = self.cypher.variables.nested_scope();
+                let outer = std::mem::replace(&mut self.cypher.variables, scope);
+                let pattern_query = self.cypher.expr_relation_pattern();
+                let scope = std::mem::replace(&mut self.cypher.variables, outer);
+                self.cypher.variables.merge_scope(&scope, &[]);
+                let pattern_query = pattern_query?;
                 Ok(Expr::from(ExprKind::SubQuery(
                     SubQueryKind::RelationShipsPattern,
                     Box::new(pattern_query),
@@ -580,7 +697,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
                 )))
             }
             // ParenthesizedExpression
-            41..=43 => {
+            Some(7) => {
                 let expression = self.visit();
                 Ok(Expr::from(ExprKind::UnOp(
                     UnOpKind::Parentheses,
@@ -611,7 +728,7 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
             //     ))
             // }
             // ExistentialSubquery
-            66..=67 => {
+            Some(8) => {
                 self.complexity += 1;
 
                 // ExistentialSubquery: `EXISTS` `{` (RegularQuery|(Pattern where)) `}`
@@ -623,10 +740,23 @@ impl ExpressionNodeVisitor for ExprGenerator<'_> {
                 )))
             }
             // Variable
-            70..=99 => {
-                let var = self.cypher.variables.get_old_variable()?;
-                Ok(Expr::from(ExprKind::Variable(var)))
+            Some(9) => match self.cypher.variables.get_old_variable() {
+                Ok(var) => Ok(Expr::from(ExprKind::Variable(var))),
+                // no variable in scope yet.
+                Err(_) => Ok(Expr::from(ExprKind::Lit(self.random_literal()))),
+            },
+            // CountSubquery: `COUNT` `{` RegularQuery `}`
+            Some(10) => {
+                self.complexity += 1;
+
+                let query = self.cypher.exec()?;
+                Ok(Expr::from(ExprKind::SubQuery(
+                    SubQueryKind::Count,
+                    Box::new(query),
+                    None,
+                )))
             }
+            // Literal Expression, also when every atom is disabled.
             _ => Ok(Expr::from(ExprKind::Lit(self.random_literal()))),
         }
     }
@@ -0,0 +1,553 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::{BinOpKind, Expr, ExprKind, Literal, RandomGenerator, UnOpKind};
+
+/// Layout of a rendered query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layout {
+    /// One line, single spaced.
+    #[default]
+    Canonical,
+    /// One clause per line, the subqueries indented, for the reports.
+    Pretty,
+    /// Random keyword case, whitespace, comments, parentheses and backtick quoted
+    /// names, to exercise the lexer and the parser of the target.
+    Fuzzed,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 3] = [Layout::Canonical, Layout::Pretty, Layout::Fuzzed];
+
+    /// Lay out a canonical query.
+    pub fn apply(&self, query: &str) -> String {
+        match self {
+            Layout::Canonical => query.to_string(),
+            Layout::Pretty => pretty(&tokenize(query)),
+            Layout::Fuzzed => fuzz(&tokenize(query), &mut RandomGenerator::new()),
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Layout::ALL
+            .into_iter()
+            .find(|layout| format!("{:?}", layout).eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown layout `{}`", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Keywords and names.
+    Word,
+    /// A backtick quoted name.
+    Quoted,
+    String,
+    Number,
+    /// `$name`
+    Parameter,
+    Symbol,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    /// whitespace or a comment before the token.
+    pub spaced: bool,
+}
+
+/// Symbols of two characters, the others are single characters.
+const SYMBOLS: &[&str] = &["<>", "<=", ">=", "=~", "..", "->", "<-", "+=", "::"];
+
+/// Keywords of the dialects, the names of the types included.
+const KEYWORDS: &[&str] = &[
+    "ACYCLIC",
+    "ALL",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "ASCENDING",
+    "ASSERT",
+    "BOOL",
+    "BOOLEAN",
+    "BY",
+    "CALL",
+    "CASE",
+    "CONSTRAINT",
+    "CONSTRAINTS",
+    "CONTAINS",
+    "COUNT",
+    "CREATE",
+    "CSV",
+    "DATE",
+    "DELETE",
+    "DELIMITER",
+    "DESC",
+    "DESCENDING",
+    "DETACH",
+    "DISTINCT",
+    "DROP",
+    "EDGE",
+    "ELSE",
+    "END",
+    "ENDS",
+    "EXISTS",
+    "FALSE",
+    "FIELDTERMINATOR",
+    "FILTER",
+    "FLOAT",
+    "FOR",
+    "FOREACH",
+    "FROM",
+    "GROUPS",
+    "HEADER",
+    "HEADERS",
+    "IF",
+    "IN",
+    "INDEX",
+    "INDEXES",
+    "INSERT",
+    "INT",
+    "INTEGER",
+    "IS",
+    "KEY",
+    "LABELED",
+    "LET",
+    "LIMIT",
+    "LIST",
+    "LOAD",
+    "MAP",
+    "MATCH",
+    "MERGE",
+    "NEXT",
+    "NO",
+    "NODE",
+    "NONE",
+    "NOT",
+    "NULL",
+    "OF",
+    "ON",
+    "OPTIONAL",
+    "OR",
+    "ORDER",
+    "PATH",
+    "RECORD",
+    "RELATIONSHIP",
+    "REMOVE",
+    "REQUIRE",
+    "RETURN",
+    "ROWS",
+    "SET",
+    "SHORTEST",
+    "SHOW",
+    "SIMPLE",
+    "SINGLE",
+    "SKIP",
+    "STARTS",
+    "STRING",
+    "THEN",
+    "TRAIL",
+    "TRANSACTIONS",
+    "TRUE",
+    "TYPED",
+    "UNION",
+    "UNIQUE",
+    "UNWIND",
+    "VALUE",
+    "WALK",
+    "WHEN",
+    "WHERE",
+    "WITH",
+    "XOR",
+    "YIELD",
+];
+
+/// Keywords which are values.
+const LITERALS: &[&str] = &["TRUE", "FALSE", "NULL"];
+
+/// Keywords starting a clause, see `clause_start` for the exceptions.
+pub(super) const CLAUSES: &[&str] = &[
+    "CALL", "CREATE", "DELETE", "DETACH", "FILTER", "FOR", "FOREACH", "INSERT", "LET", "LIMIT",
+    "LOAD", "MATCH", "MERGE", "NEXT", "ON", "OPTIONAL", "ORDER", "REMOVE", "RETURN", "SET", "SKIP",
+    "UNION", "UNWIND", "WHERE", "WITH",
+];
+
+/// Split a query into tokens, the comments are dropped as whitespace.
+pub fn tokenize(query: &str) -> Vec<Token> {
+    let chars = query.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut spaced = false;
+    let mut idx = 0;
+
+    // end of the run of characters from `idx` matching `pred`.
+    let run = |idx: usize, pred: &dyn Fn(char) -> bool| {
+        idx + chars[idx..].iter().take_while(|c| pred(**c)).count()
+    };
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        let next = chars.get(idx + 1).copied();
+        let (kind, end) = match c {
+            _ if c.is_whitespace() => {
+                idx += 1;
+                spaced = true;
+                continue;
+            }
+            '/' if next == Some('/') => {
+                idx = run(idx, &|c| c != '\n');
+                spaced = true;
+                continue;
+            }
+            '/' if next == Some('*') => {
+                idx = (idx + 2..chars.len().saturating_sub(1))
+                    .find(|end| chars[*end] == '*' && chars[end + 1] == '/')
+                    .map_or(chars.len(), |end| end + 2);
+                spaced = true;
+                continue;
+            }
+            '\'' | '"' | '`' => {
+                let mut end = idx + 1;
+                while end < chars.len() && chars[end] != c {
+                    end += if chars[end] == '\\' { 2 } else { 1 };
+                }
+                let kind = match c {
+                    '`' => TokenKind::Quoted,
+                    _ => TokenKind::String,
+                };
+                (kind, (end + 1).min(chars.len()))
+            }
+            _ if c.is_ascii_digit() => {
+                let mut end = run(idx, &word);
+                // a fraction, not a range `1..2`.
+                if chars.get(end) == Some(&'.')
+                    && chars.get(end + 1).is_some_and(char::is_ascii_digit)
+                {
+                    end = run(end + 1, &word);
+                }
+                // signed exponent.
+                if matches!(chars[end - 1], 'e' | 'E')
+                    && matches!(chars.get(end), Some('+' | '-'))
+                    && chars.get(end + 1).is_some_and(char::is_ascii_digit)
+                {
+                    end = run(end + 1, &word);
+                }
+                (TokenKind::Number, end)
+            }
+            '$' => (TokenKind::Parameter, run(idx + 1, &word)),
+            _ if word(c) => (TokenKind::Word, run(idx, &word)),
+            _ => {
+                let pair = next.map(|next| format!("{}{}", c, next));
+                let len = match pair {
+                    Some(pair) if SYMBOLS.contains(&pair.as_str()) => 2,
+                    _ => 1,
+                };
+                (TokenKind::Symbol, idx + len)
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: chars[idx..end].iter().collect(),
+            spaced,
+        });
+        spaced = false;
+        idx = end;
+    }
+    tokens
+}
+
+/// Join the tokens, single spaced where the query had whitespace.
+pub fn join(tokens: &[Token]) -> String {
+    let mut query = String::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        if token.spaced && idx > 0 {
+            query.push(' ');
+        }
+        query += &token.text;
+    }
+    query
+}
+
+fn text(tokens: &[Token], idx: Option<usize>) -> &str {
+    idx.and_then(|idx| tokens.get(idx))
+        .map_or("", |token| token.text.as_str())
+}
+
+/// Keywords are case insensitive, e.g. `match` or `Match`.
+pub fn is_keyword_text(text: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(text))
+}
+
+/// A keyword, not a name: labels, property keys and map keys are never keywords, the
+/// literals `TRUE`, `FALSE` and `NULL` are keywords as map values too.
+fn is_keyword(tokens: &[Token], idx: usize) -> bool {
+    let token = &tokens[idx];
+    let literal = LITERALS
+        .iter()
+        .any(|literal| literal.eq_ignore_ascii_case(&token.text));
+    let prev = text(tokens, idx.checked_sub(1));
+    token.kind == TokenKind::Word
+        && is_keyword_text(&token.text)
+        && (literal || !matches!(prev, ":" | "&"))
+        && prev != "."
+        && text(tokens, Some(idx + 1)) != ":"
+}
+
+/// The keyword starting a clause.
+fn clause_start(tokens: &[Token], idx: usize) -> bool {
+    if !is_keyword(tokens, idx) || !CLAUSES.contains(&tokens[idx].text.as_str()) {
+        return false;
+    }
+    let prev = text(tokens, idx.checked_sub(1));
+    let next = text(tokens, Some(idx + 1));
+    match tokens[idx].text.as_str() {
+        "MATCH" => !matches!(prev, "OPTIONAL" | "ON"),
+        "CREATE" => prev != "ON",
+        "DELETE" => prev != "DETACH",
+        // `ON CREATE SET` and `ON MATCH SET` of MERGE.
+        "SET" => !(matches!(prev, "CREATE" | "MATCH") && text(tokens, idx.checked_sub(2)) == "ON"),
+        "ON" => matches!(next, "CREATE" | "MATCH"),
+        "WITH" => !matches!(prev, "STARTS" | "ENDS" | "CSV") && !next.starts_with("HEADER"),
+        // GQL `FOR x IN`, not `FOR (n:Label)` of the DDL.
+        "FOR" => text(tokens, Some(idx + 2)) == "IN",
+        _ => true,
+    }
+}
+
+/// One clause per line, the `{ }` subqueries indented.
+pub fn pretty(tokens: &[Token]) -> String {
+    // true for the subquery braces, false for maps, parentheses and brackets.
+    let mut blocks: Vec<bool> = vec![];
+    let mut query = String::new();
+    let newline = |query: &mut String, depth: usize| {
+        query.push('\n');
+        query.push_str(&"  ".repeat(depth));
+    };
+    let depth = |blocks: &[bool]| blocks.iter().filter(|block| **block).count();
+
+    // nothing but the indentation on the current line.
+    let mut line_start = true;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        let symbol = match token.kind {
+            TokenKind::Symbol => token.text.as_str(),
+            _ => "",
+        };
+        let in_block = blocks.last().is_none_or(|block| *block);
+        if symbol == "}" && blocks.last() == Some(&true) {
+            blocks.pop();
+            newline(&mut query, depth(&blocks));
+        } else if !line_start && in_block && clause_start(tokens, idx) {
+            newline(&mut query, depth(&blocks));
+        } else if token.spaced && !line_start {
+            query.push(' ');
+        }
+        query += &token.text;
+        line_start = false;
+
+        match symbol {
+            "{" => {
+                let block = clause_start(tokens, idx + 1);
+                blocks.push(block);
+                if block {
+                    newline(&mut query, depth(&blocks));
+                    line_start = true;
+                }
+            }
+            "(" | "[" => blocks.push(false),
+            "}" | ")" | "]" => {
+                blocks.pop();
+            }
+            _ => {}
+        }
+    }
+    query
+}
+
+/// The name of a function or a procedure: `f(`, `ns.f(` or `CALL ns.f`.
+fn is_call_name(tokens: &[Token], idx: usize) -> bool {
+    let mut end = idx + 1;
+    while text(tokens, Some(end)) == "."
+        && tokens
+            .get(end + 1)
+            .is_some_and(|t| t.kind == TokenKind::Word)
+    {
+        end += 2;
+    }
+    let mut start = idx;
+    while start >= 2 && text(tokens, Some(start - 1)) == "." {
+        start -= 2;
+    }
+    text(tokens, Some(end)) == "(" || text(tokens, start.checked_sub(1)) == "CALL"
+}
+
+/// Whitespace between two tokens, a comment at times.
+fn whitespace(random: &mut RandomGenerator) -> &'static str {
+    const WHITESPACE: &[&str] = &[
+        " ",
+        "  ",
+        "\n",
+        "\t",
+        "\r\n  ",
+        " /* comment */ ",
+        " /**/",
+        " // comment\n",
+        "\n\t /* MATCH (n) */\n",
+    ];
+    WHITESPACE[random.under(WHITESPACE.len() as _) as usize]
+}
+
+/// A keyword in lower, upper or mixed case.
+fn keyword_case(keyword: &str, random: &mut RandomGenerator) -> String {
+    match random.d6() {
+        0 | 1 => keyword.to_string(),
+        2 | 3 => keyword.to_lowercase(),
+        _ => keyword
+            .chars()
+            .map(|c| match random.bool() {
+                true => c.to_ascii_lowercase(),
+                false => c,
+            })
+            .collect(),
+    }
+}
+
+/// Randomize the case of the keywords, the whitespace and comments between the tokens
+/// and quote the names in backticks.
+pub fn fuzz(tokens: &[Token], random: &mut RandomGenerator) -> String {
+    let mut query = String::new();
+    for (idx, token) in tokens.iter().enumerate() {
+        if idx > 0 && (token.spaced || random.chance(20)) {
+            query += whitespace(random);
+        }
+        match token.kind {
+            TokenKind::Word if is_keyword(tokens, idx) => {
+                query += &keyword_case(&token.text, random)
+            }
+            // keywords are never quoted, not even as names.
+            TokenKind::Word
+                if !is_keyword_text(&token.text)
+                    && !is_call_name(tokens, idx)
+                    && random.chance(30) =>
+            {
+                query += &format!("`{}`", token.text)
+            }
+            _ => query += &token.text,
+        }
+    }
+    query
+}
+
+/// Wrap some of the operands of the expression in redundant parentheses.
+///
+/// The lists are left alone, they hold the list and pattern comprehensions.
+pub fn parenthesize(expr: &mut Expr, random: &mut RandomGenerator) {
+    let mut operands: Vec<&mut Expr> = vec![];
+    match &mut expr.kind {
+        ExprKind::BinOp(BinOpKind::Pipe | BinOpKind::Range, _, _) => {}
+        ExprKind::BinOp(_, lhs, rhs) => operands.extend([lhs.as_mut(), rhs.as_mut()]),
+        ExprKind::UnOp(_, operand) | ExprKind::TypePredicate(operand, _, _) => {
+            operands.push(operand)
+        }
+        ExprKind::Cmp(lhs, tails) => {
+            operands.push(lhs);
+            operands.extend(tails.iter_mut().map(|(_, rhs)| rhs.as_mut()));
+        }
+        ExprKind::Case(case_expr, alternatives, else_expr) => {
+            operands.extend(
+                case_expr
+                    .iter_mut()
+                    .chain(else_expr.iter_mut())
+                    .map(|e| e.as_mut()),
+            );
+            for alternative in alternatives {
+                operands.extend([alternative.condition.as_mut(), alternative.value.as_mut()]);
+            }
+        }
+        ExprKind::Invocation(_, _, params) => operands.extend(params.iter_mut()),
+        _ => {}
+    }
+    for operand in operands {
+        parenthesize(operand, random);
+        if random.chance(15) {
+            let inner = std::mem::replace(operand, Expr::from(ExprKind::Lit(Literal::Null)));
+            *operand = Expr::from(ExprKind::UnOp(UnOpKind::Parentheses, Box::new(inner)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fuzz, is_keyword_text, join, pretty, tokenize, Layout, TokenKind};
+    use crate::common::RandomGenerator;
+
+    #[test]
+    fn test_tokenize() {
+        let query = "MATCH (v0:Person)-[*1..3]->() WHERE v0.age <> 1.5e-3 AND v0.name =~ 'a\\'b' \
+                     RETURN count(*), $p AS `x y` // tail";
+        let tokens = tokenize(query);
+        let texts = tokens.iter().map(|t| t.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            texts[..14],
+            ["MATCH", "(", "v0", ":", "Person", ")", "-", "[", "*", "1", "..", "3", "]", "->"]
+        );
+        assert!(texts.contains(&"1.5e-3") && texts.contains(&"'a\\'b'") && texts.contains(&"<>"));
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Quoted);
+        assert_eq!(join(&tokens), query.trim_end_matches(" // tail"));
+    }
+
+    #[test]
+    fn test_pretty() {
+        let query = "MATCH  (v0) WHERE v0.p IN [1, 2] CALL { WITH v0 MATCH (v0)-->(v1) \
+                     RETURN v1 } WITH v1, EXISTS {(v1)-->()} AS e ORDER BY v1.p LIMIT 5 \
+                     RETURN v1.p STARTS WITH 'a'";
+        assert_eq!(
+            pretty(&tokenize(query)),
+            "MATCH (v0)\nWHERE v0.p IN [1, 2]\nCALL {\n  WITH v0\n  MATCH (v0)-->(v1)\n  \
+             RETURN v1\n}\nWITH v1, EXISTS {(v1)-->()} AS e\nORDER BY v1.p\nLIMIT 5\n\
+             RETURN v1.p STARTS WITH 'a'"
+        );
+        assert_eq!(
+            Layout::Pretty.apply("MERGE (v0) ON CREATE SET v0.p = 1 ON MATCH SET v0.p = 2"),
+            "MERGE (v0)\nON CREATE SET v0.p = 1\nON MATCH SET v0.p = 2"
+        );
+        assert_eq!("fuzzed".parse::<Layout>(), Ok(Layout::Fuzzed));
+    }
+
+    #[test]
+    fn test_fuzz() {
+        let query = "OPTIONAL MATCH (v0:Person {id: 1, ok: true})-[:Knows*2..]->(v1) \
+                     WHERE v0.name STARTS WITH 'x' Or v1.name Is Null CALL db.labels() \
+                     YIELD label RETURN DISTINCT toUpper(v1.name) AS n, False ORDER BY n DESC";
+        // equal tokens but for the case of the keywords and the quotes of the names.
+        let normalize = |query: &str| {
+            tokenize(query)
+                .into_iter()
+                .map(|token| match token.kind {
+                    TokenKind::Quoted => format!("name {}", token.text.trim_matches('`')),
+                    TokenKind::Word if is_keyword_text(&token.text) => token.text.to_uppercase(),
+                    TokenKind::Word => format!("name {}", token.text),
+                    _ => token.text,
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut random = RandomGenerator::new();
+        for _ in 0..100 {
+            let fuzzed = fuzz(&tokenize(query), &mut random);
+            assert_eq!(normalize(&fuzzed), normalize(query), "{}", fuzzed);
+            // the names of the functions and procedures are not quoted.
+            assert!(["`toUpper`", "`db`", "`labels`"]
+                .iter()
+                .all(|name| !fuzzed.contains(name)));
+        }
+    }
+}
@@ -1,22 +1,44 @@
 mod cypher;
 mod cypher_gen;
+mod eval;
 mod expr;
 mod expr_gen;
+mod format;
+mod mutate;
+mod rewrite;
 mod transform;
 
 // pub use expr::{Variable};
 pub use cypher::{CypherNode, LogVisitor};
 // pub use cypher_gen::CypherGenerator;
 pub use cypher_gen::CypherGenerator;
+pub use eval::{
+    aggregates, aggregation, equals, evaluate, evaluate_in, fold, Constant, Eval, EvalError, Scope,
+};
 pub use expr::ExpressionNodeVisitor;
 pub use expr_gen::ExprGenerator;
+pub use format::Layout;
+pub use mutate::{mutate, Mutation};
+pub use rewrite::{is_deterministic, rewrite, Rewrite};
 pub use transform::TransformVisitor;
 
 #[cfg(test)]
 mod tests {
 
-    use super::{CypherGenerator, ExprGenerator};
-    use crate::common::{constants, DataType, Property};
+    use super::{
+        fold,
+        format::{is_keyword_text, tokenize, TokenKind},
+        is_deterministic,
+        rewrite::{walk, walk_expr},
+        CypherGenerator, CypherNode, ExprGenerator, Layout, Rewrite, TransformVisitor,
+    };
+    use crate::common::{
+        constants, from_json, is_write_procedure, ConstraintKind, DataKind, DataType, Expr,
+        ExprKind, FieldValue, Literal, PathMode, PathSelector, Property, RandomGenerator,
+        RelationshipDirection, SubQueryKind, Variable,
+    };
+    use crate::config::{Dialect, StressConfig, Weights};
+    use crate::meta::testing::test_schema;
     use crate::meta::{GraphSchema, Label, LabelKind};
 
     #[test]
@@ -31,6 +53,7 @@ mod tests {
                 is_pk: true,
                 nullable: false,
                 is_delete: false,
+                domain: None,
             },
             Property {
                 name: "name".to_string(),
@@ -39,6 +62,7 @@ mod tests {
                 is_pk: false,
                 nullable: true,
                 is_delete: false,
+                domain: None,
             },
         ];
 
@@ -62,6 +86,7 @@ mod tests {
             is_pk: true,
             nullable: false,
             is_delete: false,
+            domain: None,
         }];
         labels.push(Label {
             label_name: "Knows".to_string(),
@@ -94,6 +119,7 @@ mod tests {
                 is_pk: true,
                 nullable: false,
                 is_delete: false,
+                domain: None,
             },
             Property {
                 name: "name".to_string(),
@@ -102,6 +128,7 @@ mod tests {
                 is_pk: false,
                 nullable: true,
                 is_delete: false,
+                domain: None,
             },
         ];
 
@@ -125,6 +152,7 @@ mod tests {
             is_pk: true,
             nullable: false,
             is_delete: false,
+            domain: None,
         }];
         labels.push(Label {
             label_name: "Knows".to_string(),
@@ -155,6 +183,7 @@ mod tests {
                 is_pk: true,
                 nullable: false,
                 is_delete: false,
+                domain: None,
             },
             Property {
                 name: "name".to_string(),
@@ -163,6 +192,7 @@ mod tests {
                 is_pk: false,
                 nullable: true,
                 is_delete: false,
+                domain: None,
             },
         ];
 
@@ -186,6 +216,7 @@ mod tests {
             is_pk: true,
             nullable: false,
             is_delete: false,
+            domain: None,
         }];
         labels.push(Label {
             label_name: "Knows".to_string(),
@@ -201,4 +232,946 @@ mod tests {
         let expression_string = generator.visit();
         println!("{:?}", expression_string);
     }
+
+    #[test]
+    fn call_subquery_transform_test() {
+        let return_part = |var: &str| {
+            Box::new(CypherNode::SingleQuery {
+                part_query: Box::new(CypherNode::SinglePartQuery {
+                    reading_clauses: vec![],
+                    updating_clauses: vec![],
+                    return_clause: Some(Box::new(CypherNode::Return {
+                        projection_body: Box::new(CypherNode::ProjectionBody {
+                            is_distinct: false,
+                            projection_items: Box::new(CypherNode::ProjectionItems {
+                                is_all: false,
+                                expressions: vec![(
+                                    Expr::from(ExprKind::Variable(Variable::new("v0".to_string()))),
+                                    Some(Variable::new(var.to_string())),
+                                )],
+                            }),
+                            order: None,
+                            skip: None,
+                            limit: None,
+                        }),
+                    })),
+                }),
+            })
+        };
+
+        let subquery = CypherNode::CallSubquery {
+            import_variables: vec![Variable::new("v0".to_string())],
+            query: Box::new(CypherNode::RegularQuery {
+                single_query: return_part("v1"),
+                union_all: vec![Box::new(CypherNode::Union {
+                    union_all: Some((true, return_part("v1"))),
+                })],
+            }),
+            in_transactions: (true, Some(10)),
+        };
+
+        let mut transformer = TransformVisitor::new();
+        assert_eq!(
+            transformer.exec(Box::new(subquery)),
+            "CALL { WITH v0 RETURN v0 AS v1 UNION ALL WITH v0 RETURN v0 AS v1 } IN TRANSACTIONS OF 10 ROWS"
+        );
+    }
+
+    #[test]
+    fn subquery_union_test() {
+        // aliases returned by an arm of a subquery, None for a unit arm.
+        fn columns(arm: &CypherNode) -> Option<Vec<String>> {
+            let part_query = match arm {
+                CypherNode::SingleQuery { part_query } => part_query,
+                _ => return None,
+            };
+            let return_clause = match part_query.as_ref() {
+                CypherNode::SinglePartQuery {
+                    return_clause: Some(return_clause),
+                    ..
+                } => return_clause,
+                _ => return None,
+            };
+            if let CypherNode::Return { projection_body } = return_clause.as_ref() {
+                if let CypherNode::ProjectionBody {
+                    projection_items, ..
+                } = projection_body.as_ref()
+                {
+                    if let CypherNode::ProjectionItems { expressions, .. } =
+                        projection_items.as_ref()
+                    {
+                        return Some(
+                            expressions
+                                .iter()
+                                .map(|(_, alias)| alias.as_ref().unwrap().get_name())
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            None
+        }
+
+        // columns of every arm of each CALL subquery.
+        fn arm_columns(node: &CypherNode, subqueries: &mut Vec<Vec<Option<Vec<String>>>>) {
+            match node {
+                CypherNode::RegularQuery {
+                    single_query,
+                    union_all,
+                } => {
+                    arm_columns(single_query, subqueries);
+                    for union in union_all {
+                        arm_columns(union, subqueries);
+                    }
+                }
+                CypherNode::Union {
+                    union_all: Some((_, query)),
+                } => arm_columns(query, subqueries),
+                CypherNode::SingleQuery { part_query } => arm_columns(part_query, subqueries),
+                CypherNode::SinglePartQuery {
+                    reading_clauses, ..
+                } => {
+                    for reading_clause in reading_clauses {
+                        arm_columns(reading_clause, subqueries);
+                    }
+                }
+                CypherNode::MultiPartQuery {
+                    multi_part,
+                    single_part,
+                } => {
+                    for (reading_clauses, _, _) in multi_part {
+                        for reading_clause in reading_clauses {
+                            arm_columns(reading_clause, subqueries);
+                        }
+                    }
+                    arm_columns(single_part, subqueries);
+                }
+                CypherNode::ReadingClause { reading_clause } => {
+                    arm_columns(reading_clause, subqueries)
+                }
+                CypherNode::CallSubquery { query, .. } => {
+                    if let CypherNode::RegularQuery {
+                        single_query,
+                        union_all,
+                    } = query.as_ref()
+                    {
+                        let mut arms = vec![columns(single_query)];
+                        for union in union_all {
+                            if let CypherNode::Union {
+                                union_all: Some((_, arm)),
+                            } = union.as_ref()
+                            {
+                                arms.push(columns(arm));
+                            }
+                        }
+                        subqueries.push(arms);
+                    }
+                    arm_columns(query, subqueries);
+                }
+                _ => {}
+            }
+        }
+
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        let mut subqueries = vec![];
+        for _ in 0..300 {
+            if let Ok(query) = generator.visit() {
+                arm_columns(&query, &mut subqueries);
+            }
+        }
+
+        assert!(subqueries.iter().any(|arms| arms.len() > 1));
+        for arms in subqueries {
+            // every returned expression is aliased, and all arms return the same columns.
+            assert!(arms.iter().all(|columns| columns == &arms[0]), "{:?}", arms);
+        }
+    }
+
+    #[test]
+    fn expression_subquery_transaction_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        let mut transformer = TransformVisitor::new();
+
+        // the query of an EXISTS or COUNT subquery.
+        for _ in 0..1000 {
+            generator.limit = constants::DEFAULT_QUERY_LIMIT;
+            if let Ok(query) = generator.exec() {
+                let query_string = transformer.exec(Box::new(query));
+                assert!(
+                    !query_string.contains("IN TRANSACTIONS"),
+                    "{}",
+                    query_string
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn foreach_transform_test() {
+        let variable = |name: &str| Variable::new(name.to_string());
+        let delete = Box::new(CypherNode::UpdatingClause {
+            updating_clause: Box::new(CypherNode::Delete {
+                is_detach: true,
+                expressions: vec![Expr::from(ExprKind::Variable(variable("v1")))],
+            }),
+        });
+
+        let foreach = CypherNode::Foreach {
+            variable: variable("v1"),
+            expression: Expr::from(ExprKind::Variable(variable("v0"))),
+            updating_clauses: vec![delete.clone()],
+        };
+        let conditional_update = CypherNode::ConditionalUpdate {
+            variable: variable("t0"),
+            condition: Expr::from(ExprKind::Lit(Literal::Boolean(true))),
+            updating_clauses: vec![delete],
+        };
+        let load_csv = CypherNode::LoadCsv {
+            with_headers: true,
+            file: "Person.csv".to_string(),
+            variable: variable("v2"),
+            field_terminator: Some(",".to_string()),
+        };
+
+        let mut transformer = TransformVisitor::new();
+        assert_eq!(
+            transformer.exec(Box::new(foreach)),
+            "FOREACH (v1 IN v0 | DETACH DELETE v1)"
+        );
+        assert_eq!(
+            transformer.exec(Box::new(conditional_update)),
+            "FOREACH (t0 IN CASE WHEN TRUE THEN [1] ELSE [] END | DETACH DELETE v1)"
+        );
+        assert_eq!(
+            transformer.exec(Box::new(load_csv)),
+            "LOAD CSV WITH HEADERS FROM 'file:///Person.csv' AS v2 FIELDTERMINATOR ','"
+        );
+    }
+
+    #[test]
+    fn schema_command_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        let mut transformer = TransformVisitor::new();
+
+        for _ in 0..100 {
+            let command = generator.schema_command().unwrap();
+            let command_string = transformer.exec(Box::new(command));
+            assert!(
+                [
+                    "CREATE INDEX",
+                    "CREATE CONSTRAINT",
+                    "DROP INDEX",
+                    "DROP CONSTRAINT",
+                    "SHOW"
+                ]
+                .iter()
+                .any(|prefix| command_string.starts_with(prefix)),
+                "{}",
+                command_string
+            );
+        }
+
+        let label = graph_schema.vertex_labels()[0].clone();
+        let constraint = CypherNode::CreateConstraint {
+            name: "constraint_Person_id_node_key".to_string(),
+            if_not_exists: true,
+            variable: Variable::new("v0".to_string()),
+            properties: label.properties.clone(),
+            label,
+            kind: ConstraintKind::NodeKey,
+        };
+        assert_eq!(
+            transformer.exec(Box::new(constraint)),
+            "CREATE CONSTRAINT `constraint_Person_id_node_key` IF NOT EXISTS FOR (v0:Person) REQUIRE (v0.id,v0.name) IS NODE KEY"
+        );
+    }
+
+    #[test]
+    fn explicit_transaction_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.weights.call_subquery = 100;
+        generator.explicit_transaction = true;
+        let mut transformer = TransformVisitor::new();
+
+        for _ in 0..200 {
+            if let Ok(query) = generator.visit() {
+                let query_string = transformer.exec(Box::new(query));
+                assert!(
+                    !query_string.contains("IN TRANSACTIONS"),
+                    "{}",
+                    query_string
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn disabled_weights_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.weights.create = 0;
+        generator.weights.merge = 0;
+        generator.weights.delete = 0;
+        generator.weights.set = 0;
+        generator.weights.remove = 0;
+        generator.weights.foreach = 0;
+        generator.weights.conditional_update = 0;
+        let mut transformer = TransformVisitor::new();
+
+        for _ in 0..100 {
+            let query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let query_string = transformer.exec(Box::new(query));
+            assert!(
+                ["CREATE", "MERGE", "DELETE", "SET ", "REMOVE", "FOREACH"]
+                    .iter()
+                    .all(|keyword| !query_string.contains(keyword)),
+                "{}",
+                query_string
+            );
+        }
+    }
+
+    #[test]
+    fn read_only_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.read_only = true;
+        let mut transformer = TransformVisitor::new();
+
+        assert!(generator.schema_command().is_err());
+        for idx in 0..200 {
+            let query = if idx % 4 == 0 {
+                generator.call_query()
+            } else {
+                generator.visit()
+            };
+            let query = match query {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let query_string = transformer.exec(Box::new(query));
+            assert!(
+                ["CREATE", "MERGE", "DELETE", "SET ", "REMOVE", "FOREACH", "DROP"]
+                    .iter()
+                    .all(|keyword| !query_string.contains(keyword)),
+                "{}",
+                query_string
+            );
+        }
+
+        assert!(is_write_procedure("db", "createLabel"));
+        assert!(!is_write_procedure("db", "createdNodes"));
+        assert!(!is_write_procedure("atlas", "shortestPath"));
+
+        // procedures: the known names, write procedures only outside read only mode.
+        let procedures = Dialect::Neo4j5.procedures();
+        let procedure = |query: &str| {
+            procedures
+                .iter()
+                .find(|(name_space, name, _)| query.contains(&format!("{}.{}", name_space, name)))
+                .map(|(name_space, name, _)| is_write_procedure(name_space, name))
+        };
+        let mut generator =
+            CypherGenerator::with_dialect(&graph_schema, Dialect::Neo4j5, Weights::default());
+        let mut writes = 0;
+        for read_only in [true, false] {
+            generator.read_only = read_only;
+            for _ in 0..200 {
+                let query_string = match generator.call_query() {
+                    Ok(query) => transformer.exec(Box::new(query)),
+                    Err(_) => continue,
+                };
+                let is_write = procedure(&query_string).expect(&query_string);
+                assert!(!(read_only && is_write), "{}", query_string);
+                writes += is_write as u32;
+            }
+        }
+        assert!(writes > 0);
+    }
+
+    #[test]
+    fn writes_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        let mut transformer = TransformVisitor::new();
+
+        generator.schema_command().unwrap();
+        assert_eq!(generator.writes, 1);
+        for _ in 0..100 {
+            let query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let query_string = transformer.exec(Box::new(query));
+            if generator.writes == 0 {
+                assert!(!query_string.contains("CREATE"), "{}", query_string);
+            }
+        }
+    }
+
+    #[test]
+    fn serde_round_trip_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+
+        for _ in 0..100 {
+            let query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let json = serde_json::to_string(&query).unwrap();
+            let reloaded = from_json::<CypherNode>(&json).unwrap();
+            assert_eq!(
+                TransformVisitor::new().exec(Box::new(query)),
+                TransformVisitor::new().exec(Box::new(reloaded)),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn dialect_test() {
+        let graph_schema = test_schema();
+
+        // constructs the dialect does not support are never generated.
+        let mut generator =
+            CypherGenerator::with_dialect(&graph_schema, Dialect::Memgraph, Weights::default());
+        generator.weights.relationships_pattern = 50;
+        let mut transformer = TransformVisitor::with_dialect(Dialect::Memgraph);
+        assert!(generator.schema_command().is_err());
+        for _ in 0..100 {
+            let query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let query_string = transformer.exec(Box::new(query));
+            assert!(
+                !query_string.contains("EXISTS {") && !query_string.contains("COUNT {"),
+                "{}",
+                query_string
+            );
+        }
+
+        let mut generator =
+            CypherGenerator::with_dialect(&graph_schema, Dialect::Neo4j5, Weights::default());
+        generator.weights.count_subquery = 10;
+        generator.weights.type_predicate = 50;
+        let mut transformer = TransformVisitor::with_dialect(Dialect::Neo4j5);
+        let query_strings = (0..100)
+            .filter_map(|_| generator.visit().ok())
+            .map(|query| transformer.exec(Box::new(query)))
+            .collect::<Vec<_>>();
+        assert!(query_strings.iter().any(|query| query.contains("COUNT {")));
+        assert!(query_strings.iter().any(|query| query.contains(" IS ")));
+
+        // one AST, rendered for each target.
+        let label = graph_schema.vertex_labels()[0].clone();
+        let constraint = CypherNode::CreateConstraint {
+            name: "constraint_Person_id_unique".to_string(),
+            if_not_exists: false,
+            variable: Variable::new("v0".to_string()),
+            properties: vec![label.properties[0].clone()],
+            label,
+            kind: ConstraintKind::Unique,
+        };
+        assert_eq!(
+            TransformVisitor::with_dialect(Dialect::Neo4j4).exec(Box::new(constraint.clone())),
+            "CREATE CONSTRAINT `constraint_Person_id_unique` ON (v0:Person) ASSERT v0.id IS UNIQUE"
+        );
+        assert_eq!(
+            TransformVisitor::with_dialect(Dialect::Neo4j5).exec(Box::new(constraint)),
+            "CREATE CONSTRAINT `constraint_Person_id_unique` FOR (v0:Person) REQUIRE v0.id IS UNIQUE"
+        );
+
+        // a file url of the import directory, or the path of the fixture for Memgraph.
+        let load_csv = CypherNode::LoadCsv {
+            with_headers: true,
+            file: "Person.csv".to_string(),
+            variable: Variable::new("v0".to_string()),
+            field_terminator: None,
+        };
+        assert_eq!(
+            TransformVisitor::with_dialect(Dialect::Neo4j5)
+                .csv_dir("/tmp/fixtures")
+                .exec(Box::new(load_csv.clone())),
+            "LOAD CSV WITH HEADERS FROM 'file:///Person.csv' AS v0"
+        );
+        assert_eq!(
+            TransformVisitor::with_dialect(Dialect::Memgraph)
+                .csv_dir("/tmp/fixtures")
+                .exec(Box::new(load_csv)),
+            "LOAD CSV FROM '/tmp/fixtures/Person.csv' WITH HEADER AS v0"
+        );
+    }
+
+    #[test]
+    fn gql_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.dialect = Dialect::Gql;
+        generator.weights = Dialect::Gql.restrict(generator.weights.clone());
+        let mut transformer = TransformVisitor::with_dialect(Dialect::Gql);
+
+        for _ in 0..100 {
+            let query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let query_string = transformer.exec(Box::new(query));
+            assert!(
+                ["UNWIND", "MERGE", "FOREACH", "CREATE", "LOAD CSV", "]*", " % ", " ^ "]
+                    .iter()
+                    .all(|keyword| !query_string.contains(keyword)),
+                "{}",
+                query_string
+            );
+        }
+        assert!(generator.call_query().is_err());
+        let date = Expr::from(FieldValue::Date(0));
+        assert_eq!(date.display(Dialect::Gql).to_string(), "DATE('1970-01-01')");
+        assert_eq!(
+            date.display(Dialect::Neo4j5).to_string(),
+            "date('1970-01-01')"
+        );
+
+        let relationship = |range| CypherNode::RelationshipPattern {
+            direction: RelationshipDirection::Right,
+            var: None,
+            edge_labels: vec![],
+            is_range: true,
+            range,
+            properties: None,
+        };
+        for (range, cypher, gql) in [
+            ((None, None), "-[*]->", "-[]->{1,}"),
+            ((Some(2), None), "-[*2]->", "-[]->{2}"),
+            ((Some(2), Some((true, None))), "-[*2..]->", "-[]->{2,}"),
+            ((None, Some((true, Some(3)))), "-[*..3]->", "-[]->{1,3}"),
+        ] {
+            assert_eq!(transformer.exec(Box::new(relationship(range))), gql);
+            assert_eq!(
+                TransformVisitor::new().exec(Box::new(relationship(range))),
+                cypher
+            );
+        }
+
+        let pattern_part = CypherNode::PatternPart {
+            var: Some(Variable::new("p".to_string())),
+            selector: None,
+            path_mode: Some(PathMode::Trail),
+            pattern_element: Box::new(CypherNode::PatternElement {
+                parenthesis: false,
+                pattern_element: (
+                    Box::new(CypherNode::NodePattern {
+                        var: None,
+                        vertex_labels: vec![],
+                        properties: None,
+                    }),
+                    vec![],
+                ),
+            }),
+        };
+        let unwind = CypherNode::Unwind {
+            expression: Expr::from(ExprKind::Lit(Literal::Integer(1))),
+            variable: Variable::new("v".to_string()),
+        };
+        assert_eq!(
+            transformer.exec(Box::new(pattern_part.clone())),
+            "p=TRAIL ()"
+        );
+        assert_eq!(TransformVisitor::new().exec(Box::new(pattern_part)), "p=()");
+        assert_eq!(transformer.exec(Box::new(unwind)), "FOR v IN 1");
+    }
+
+    #[test]
+    fn quantified_path_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.dialect = Dialect::Neo4j5;
+        generator.weights = Dialect::Neo4j5.restrict(generator.weights.clone());
+        generator.weights.quantified_path = 100;
+        generator.weights.path_selector = 100;
+        let mut transformer = TransformVisitor::with_dialect(Dialect::Neo4j5);
+
+        let mut quantified = 0;
+        for _ in 0..100 {
+            let mut query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let mut patterns = vec![];
+            walk(
+                &mut query,
+                &mut |node| {
+                    if let CypherNode::Match { pattern, .. } = node {
+                        patterns.push(pattern.as_ref().clone());
+                    }
+                },
+                &mut |_| {},
+            );
+            for mut pattern in patterns {
+                let (mut quantified_paths, mut ranges) = (vec![], 0);
+                walk(
+                    &mut pattern,
+                    &mut |node| match node {
+                        CypherNode::QuantifiedPathPattern { .. } => {
+                            quantified_paths.push(node.clone())
+                        }
+                        CypherNode::RelationshipPattern { is_range: true, .. } => ranges += 1,
+                        _ => {}
+                    },
+                    // the patterns of the subqueries are checked on their own.
+                    &mut |expr| {
+                        if let ExprKind::SubQuery(..) = expr.kind {
+                            *expr = Expr::from(ExprKind::Lit(Literal::Null));
+                        }
+                    },
+                );
+                // no variable length relationships next to or inside a quantified path.
+                assert!(quantified_paths.is_empty() || ranges == 0);
+                quantified += quantified_paths.len();
+
+                for quantified_path in quantified_paths {
+                    let (pattern_element, where_clause) = match quantified_path {
+                        CypherNode::QuantifiedPathPattern {
+                            pattern_element,
+                            where_clause: Some(where_clause),
+                            ..
+                        } => (pattern_element, where_clause),
+                        _ => continue,
+                    };
+                    let mut inner = vec![];
+                    walk(
+                        &mut pattern_element.clone(),
+                        &mut |node| match node {
+                            CypherNode::NodePattern { var: Some(var), .. }
+                            | CypherNode::RelationshipPattern { var: Some(var), .. } => {
+                                inner.push(var.get_name())
+                            }
+                            _ => {}
+                        },
+                        &mut |_| {},
+                    );
+                    // the inner WHERE refers to the inner variables and to the ones of its
+                    // pattern expressions, EXISTS and COUNT subqueries bind their own.
+                    let mut referred = vec![];
+                    walk_expr(
+                        &mut where_clause.clone(),
+                        &mut |node| match node {
+                            CypherNode::PatternPart { var: Some(var), .. }
+                            | CypherNode::NodePattern { var: Some(var), .. }
+                            | CypherNode::RelationshipPattern { var: Some(var), .. } => {
+                                inner.push(var.get_name())
+                            }
+                            _ => {}
+                        },
+                        &mut |expr| match &expr.kind {
+                            ExprKind::SubQuery(SubQueryKind::Exists | SubQueryKind::Count, ..) => {
+                                *expr = Expr::from(ExprKind::Lit(Literal::Null))
+                            }
+                            ExprKind::Variable(var) => referred.push(var.get_name()),
+                            _ => {}
+                        },
+                    );
+                    assert!(referred.iter().all(|var| inner.contains(var)));
+                }
+            }
+            transformer.exec(Box::new(query));
+        }
+        assert!(quantified > 0);
+
+        let node = |name: &str| {
+            Box::new(CypherNode::NodePattern {
+                var: Some(Variable::new(name.to_string())),
+                vertex_labels: vec![],
+                properties: None,
+            })
+        };
+        let relationship = Box::new(CypherNode::RelationshipPattern {
+            direction: RelationshipDirection::Right,
+            var: None,
+            edge_labels: vec![],
+            is_range: false,
+            range: (None, None),
+            properties: None,
+        });
+        let quantified_path = |where_clause: Option<Expr>, quantifier| {
+            Box::new(CypherNode::QuantifiedPathPattern {
+                pattern_element: Box::new(CypherNode::PatternElement {
+                    parenthesis: false,
+                    pattern_element: (node("a"), vec![(relationship.clone(), node("b"))]),
+                }),
+                where_clause,
+                quantifier,
+            })
+        };
+        let pattern_part = |selector, quantifier| CypherNode::PatternPart {
+            var: Some(Variable::new("p".to_string())),
+            selector,
+            path_mode: None,
+            pattern_element: Box::new(CypherNode::PatternElement {
+                parenthesis: false,
+                pattern_element: (
+                    node("x"),
+                    vec![(quantified_path(None, quantifier), node("y"))],
+                ),
+            }),
+        };
+
+        for (quantifier, rendered) in [
+            (Some((None, None)), "((a) -[]-> (b))*"),
+            (Some((Some(1), None)), "((a) -[]-> (b))+"),
+            (Some((Some(2), None)), "((a) -[]-> (b)){2,}"),
+            (Some((None, Some(3))), "((a) -[]-> (b)){,3}"),
+            (Some((Some(2), Some(2))), "((a) -[]-> (b)){2}"),
+            (Some((Some(2), Some(5))), "((a) -[]-> (b)){2,5}"),
+        ] {
+            assert_eq!(
+                transformer.exec(quantified_path(None, quantifier)),
+                rendered
+            );
+        }
+        let grouped = quantified_path(
+            Some(Expr::from(ExprKind::Lit(Literal::Boolean(true)))),
+            None,
+        );
+        assert_eq!(transformer.exec(grouped), "((a) -[]-> (b) WHERE TRUE)");
+
+        assert_eq!(
+            transformer.exec(Box::new(pattern_part(
+                Some(PathSelector::Shortest(2)),
+                Some((Some(1), None))
+            ))),
+            "p=SHORTEST 2 (x) ((a) -[]-> (b))+ (y)"
+        );
+        assert_eq!(
+            transformer.exec(Box::new(pattern_part(
+                Some(PathSelector::AnyShortest),
+                None
+            ))),
+            "p=ANY SHORTEST (x) ((a) -[]-> (b)) (y)"
+        );
+        // selectors are dropped for the dialects without them.
+        assert_eq!(
+            TransformVisitor::with_dialect(Dialect::Neo4j4)
+                .exec(Box::new(pattern_part(Some(PathSelector::All), None))),
+            "p=(x) ((a) -[]-> (b)) (y)"
+        );
+    }
+
+    #[test]
+    fn layout_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        let texts = |query: &str| {
+            tokenize(query)
+                .into_iter()
+                .map(|token| token.text)
+                .collect::<Vec<_>>()
+        };
+        // the tokens but for the parentheses, the case of the keywords and the quotes of
+        // the names.
+        let normalize = |query: &str| {
+            tokenize(query)
+                .into_iter()
+                .map(|token| match token.kind {
+                    TokenKind::Quoted => format!("name {}", token.text.trim_matches('`')),
+                    TokenKind::Word if is_keyword_text(&token.text) => token.text.to_uppercase(),
+                    TokenKind::Word => format!("name {}", token.text),
+                    _ => token.text,
+                })
+                .filter(|text| text != "(" && text != ")")
+                .collect::<Vec<_>>()
+        };
+
+        for _ in 0..100 {
+            let query = match generator.visit() {
+                Ok(query) => Box::new(query),
+                Err(_) => continue,
+            };
+            let canonical = TransformVisitor::new().exec(query.clone());
+            let pretty = TransformVisitor::new()
+                .layout(Layout::Pretty)
+                .exec(query.clone());
+            let fuzzed = TransformVisitor::new().layout(Layout::Fuzzed).exec(query);
+            assert_eq!(texts(&pretty), texts(&canonical));
+            assert_eq!(normalize(&fuzzed), normalize(&canonical), "{}", fuzzed);
+        }
+    }
+
+    #[test]
+    fn rewrite_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.read_only = true;
+        let mut random = RandomGenerator::new();
+        let render = |query: &CypherNode| TransformVisitor::new().exec(Box::new(query.clone()));
+        let sorted = |query: &str| {
+            let mut texts = tokenize(query)
+                .into_iter()
+                .map(|token| token.text)
+                .collect::<Vec<_>>();
+            texts.sort();
+            texts
+        };
+
+        let mut applied = vec![];
+        for _ in 0..300 {
+            let query = match generator.visit() {
+                Ok(query) => query,
+                Err(_) => continue,
+            };
+            let original = render(&query);
+            for rewrite in Rewrite::ALL {
+                let Some(rewritten) = rewrite.apply(&query, &mut random) else {
+                    continue;
+                };
+                applied.push(rewrite);
+                let rewritten = render(&rewritten);
+                let count = |query: &str, text: &str| query.matches(text).count();
+                match rewrite {
+                    Rewrite::SwapOperands => {
+                        assert_ne!(rewritten, original);
+                        assert_eq!(sorted(&rewritten), sorted(&original))
+                    }
+                    // the same tokens but for the directions of the arrows.
+                    Rewrite::ReversePattern => assert_eq!(
+                        sorted(&rewritten.replace("<-", "-").replace("->", "-")),
+                        sorted(&original.replace("<-", "-").replace("->", "-"))
+                    ),
+                    Rewrite::SplitMatch => {
+                        assert_eq!(count(&rewritten, "MATCH "), count(&original, "MATCH ") + 1)
+                    }
+                    Rewrite::WhereToWith => {
+                        assert_eq!(
+                            count(&rewritten, "WITH * WHERE "),
+                            1 + count(&original, "WITH * WHERE ")
+                        );
+                        assert_eq!(count(&rewritten, "WHERE "), count(&original, "WHERE "));
+                    }
+                    Rewrite::WithStar => {
+                        assert_eq!(count(&rewritten, "WITH *"), count(&original, "WITH *") + 1)
+                    }
+                    Rewrite::WrapCall => {
+                        assert!(rewritten.starts_with("CALL { "), "{}", rewritten);
+                        let outer = rewritten.rsplit('}').next().unwrap();
+                        assert!(outer.trim_start().starts_with("RETURN "), "{}", rewritten);
+                    }
+                }
+            }
+        }
+        for rewrite in Rewrite::ALL {
+            assert!(applied.contains(&rewrite), "{:?}", rewrite);
+        }
+
+        // queries which write or pick some of the rows are never compared.
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        for _ in 0..100 {
+            if let Ok(query) = generator.visit() {
+                let rendered = render(&query);
+                if [
+                    "CREATE ", "MERGE ", "DELETE ", "SET ", "REMOVE ", "SKIP ", "LIMIT ",
+                ]
+                .iter()
+                .any(|keyword| rendered.contains(keyword))
+                {
+                    assert!(!is_deterministic(&query), "{}", rendered);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn constant_folding_test() {
+        let graph_schema = test_schema();
+        let mut generator = CypherGenerator::new_schema(&graph_schema);
+        generator.dialect = Dialect::Neo4j5;
+        generator.weights = Dialect::Neo4j5.restrict(generator.weights.clone());
+        let weights = generator.weights.clone();
+
+        let (mut values, mut errors) = (0, 0);
+        for _ in 0..300 {
+            let query = generator.constant_query().unwrap();
+            let rendered = TransformVisitor::new().exec(Box::new(query.clone()));
+            assert!(rendered.trim_start().starts_with("RETURN "), "{}", rendered);
+            for keyword in ["MATCH", "COUNT", "EXISTS", "v0.", "[t"] {
+                assert!(!rendered.contains(keyword), "{}", rendered);
+            }
+            match fold(&query) {
+                Some(Ok(row)) => {
+                    assert_eq!(row.len(), 1);
+                    values += 1;
+                }
+                Some(Err(_)) => errors += 1,
+                None => {}
+            }
+        }
+        assert!(values > 60 && errors > 0, "{} {}", values, errors);
+        assert_eq!(generator.weights, weights);
+
+        // only the `RETURN` of constant expressions is folded.
+        for _ in 0..100 {
+            if let Ok(query) = generator.visit() {
+                let rendered = TransformVisitor::new().exec(Box::new(query.clone()));
+                if rendered.contains("MATCH") || rendered.contains("UNION") {
+                    assert_eq!(fold(&query), None, "{}", rendered);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stress_test() {
+        // the nested expressions are rendered recursively, as in `main`.
+        let stress = std::thread::Builder::new()
+            .stack_size(1 << 28)
+            .spawn(|| {
+                // a seed reproduces the queries, whose unions and lengths vary a lot.
+                RandomGenerator::seed(1);
+                let graph_schema = test_schema();
+                let mut generator = CypherGenerator::new_schema(&graph_schema);
+                generator.stress = Some(StressConfig {
+                    chance: 100,
+                    expression_depth: 100,
+                    union_arms: 50,
+                    pattern_length: 20,
+                    list_length: 500,
+                    max_hops: 1000,
+                    timeout_ms: 1000,
+                });
+                let mut transformer = TransformVisitor::new();
+
+                let (mut unions, mut longest) = (0, 0);
+                for _ in 0..20 {
+                    let query = match generator.visit() {
+                        Ok(query) => query,
+                        Err(_) => continue,
+                    };
+                    let query_string = transformer.exec(Box::new(query));
+                    unions = unions.max(query_string.matches(" UNION ").count());
+                    longest = longest.max(query_string.len());
+                }
+                assert!(unions > 10, "{}", unions);
+                assert!(longest > 10000, "{}", longest);
+
+                // the nested expression keeps the type of the expression.
+                let mut expr_generator = ExprGenerator::new(&mut generator);
+                let expr = Expr::from(ExprKind::Lit(Literal::Integer(1)));
+                let nested = expr_generator.nest(expr, 50);
+                assert_eq!(nested.kind.get_kind(), DataKind::Numerical);
+                let nested = nested.to_string();
+                assert_eq!(nested.matches('1').count(), 1);
+                assert!(nested.len() > 50 * 2, "{}", nested);
+            })
+            .unwrap();
+        stress.join().unwrap();
+    }
 }
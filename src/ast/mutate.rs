@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+
+use super::format::{is_keyword_text, join, tokenize, Token, TokenKind, CLAUSES};
+use crate::common::{constants, RandomGenerator};
+
+/// Token level mutation of a valid query into a malformed one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mutation {
+    /// Drop a token.
+    Drop,
+    /// Repeat a token.
+    Duplicate,
+    /// Swap two adjacent tokens.
+    Swap,
+    /// Drop a closing bracket or add an opening one.
+    Unbalance,
+    /// End the query inside a string literal.
+    TruncateString,
+    /// Insert a name of tens of thousands of characters.
+    HugeIdentifier,
+    /// Insert thousands of opening brackets.
+    DeepNesting,
+}
+
+impl Mutation {
+    pub const ALL: [Mutation; 7] = [
+        Mutation::Drop,
+        Mutation::Duplicate,
+        Mutation::Swap,
+        Mutation::Unbalance,
+        Mutation::TruncateString,
+        Mutation::HugeIdentifier,
+        Mutation::DeepNesting,
+    ];
+
+    /// Apply the mutation, `None` if the query has nothing to mutate, e.g. no string.
+    pub fn apply(&self, query: &str, random: &mut RandomGenerator) -> Option<String> {
+        let mut tokens = tokenize(query);
+        // the final `;` is optional, dropping or repeating it keeps the query valid.
+        let end = match tokens.last() {
+            Some(token) if token.text == ";" => tokens.len() - 1,
+            _ => tokens.len(),
+        };
+        if end < 2 {
+            return None;
+        }
+        let symbol = |text: &str| Token {
+            kind: TokenKind::Symbol,
+            text: text.to_string(),
+            spaced: false,
+        };
+
+        match self {
+            Mutation::Drop => {
+                let idx = pick(random, end);
+                remove(&mut tokens, idx);
+            }
+            Mutation::Duplicate => {
+                let idx = pick(random, end);
+                let token = Token {
+                    spaced: true,
+                    ..tokens[idx].clone()
+                };
+                tokens.insert(idx + 1, token);
+            }
+            Mutation::Swap => {
+                let candidates = (1..tokens.len())
+                    .filter(|idx| tokens[*idx - 1].text != tokens[*idx].text)
+                    .collect::<Vec<_>>();
+                let idx = *candidates.get(pick(random, candidates.len()))?;
+                let (lhs, rhs) = (tokens[idx - 1].text.clone(), tokens[idx].text.clone());
+                tokens[idx - 1].text = rhs;
+                tokens[idx].text = lhs;
+                // the swapped tokens are not joined into one, e.g. `v0.p` into `.v0p`.
+                for token in tokens.iter_mut().skip(idx).take(2) {
+                    token.spaced = true;
+                }
+            }
+            Mutation::Unbalance => {
+                let closing = positions(&tokens, |token| {
+                    token.kind == TokenKind::Symbol
+                        && matches!(token.text.as_str(), ")" | "]" | "}")
+                });
+                if !closing.is_empty() && random.bool() {
+                    let idx = closing[pick(random, closing.len())];
+                    remove(&mut tokens, idx);
+                } else {
+                    let bracket = ["(", "[", "{"][pick(random, 3)];
+                    tokens.insert(pick(random, end + 1), symbol(bracket));
+                }
+            }
+            Mutation::TruncateString => {
+                let strings = positions(&tokens, |token| token.kind == TokenKind::String);
+                let idx = *strings.get(pick(random, strings.len()))?;
+                // the opening quote and a part of the literal, without the closing quote.
+                let text = tokens[idx].text.chars().collect::<Vec<_>>();
+                let len = 1 + pick(random, text.len() - 1);
+                tokens[idx].text = text[..len].iter().collect();
+                tokens.truncate(idx + 1);
+            }
+            Mutation::HugeIdentifier => {
+                let len = 4096 + pick(random, 60 * 1024);
+                let name = "v".repeat(len);
+                let idx = 1 + pick(random, end - 1);
+                tokens.insert(
+                    idx,
+                    Token {
+                        kind: TokenKind::Word,
+                        text: name,
+                        spaced: true,
+                    },
+                );
+            }
+            Mutation::DeepNesting => {
+                let depth = 1000 + pick(random, 9000);
+                let bracket = ["(", "["][pick(random, 2)];
+                let idx = pick(random, end);
+                tokens.insert(idx, symbol(&bracket.repeat(depth)));
+            }
+        }
+        Some(join(&tokens))
+    }
+}
+
+/// Mutate the query by one of the mutations which apply to it, into a query which
+/// provably breaks the grammar, see `is_malformed`.
+///
+/// Dropping, repeating or swapping tokens may keep a query valid, e.g. a dropped `DISTINCT`,
+/// such a mutation is tried again at other tokens.
+pub fn mutate(query: &str, random: &mut RandomGenerator) -> Option<(String, Mutation)> {
+    let mut mutations = Mutation::ALL.to_vec();
+    while !mutations.is_empty() {
+        let mutation = mutations.remove(random.under(mutations.len() as _) as usize);
+        for _ in 0..constants::DEFAULT_MUTATION_ATTEMPTS {
+            match mutation.apply(query, random) {
+                Some(mutated) if is_malformed(&mutated) => return Some((mutated, mutation)),
+                Some(_) => continue,
+                None => break,
+            }
+        }
+    }
+    None
+}
+
+/// The query breaks the grammar of every dialect: unbalanced brackets, an unterminated
+/// string or name, two operands without an operator between them or a clause keyword
+/// repeated, e.g. `RETURN v0 v1` or `MATCH MATCH`.
+pub fn is_malformed(query: &str) -> bool {
+    let tokens = tokenize(query);
+    let mut brackets = vec![];
+    for token in tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::Symbol)
+    {
+        for c in token.text.chars() {
+            let open = match c {
+                '(' | '[' | '{' => {
+                    brackets.push(c);
+                    continue;
+                }
+                ')' => '(',
+                ']' => '[',
+                '}' => '{',
+                _ => continue,
+            };
+            if brackets.pop() != Some(open) {
+                return true;
+            }
+        }
+    }
+    if !brackets.is_empty() {
+        return true;
+    }
+
+    let is_operand = |token: &Token| match token.kind {
+        TokenKind::Word => !is_keyword_text(&token.text),
+        TokenKind::Quoted | TokenKind::String | TokenKind::Number | TokenKind::Parameter => true,
+        TokenKind::Symbol => false,
+    };
+    let is_clause = |token: &Token| {
+        token.kind == TokenKind::Word
+            && CLAUSES
+                .iter()
+                .any(|clause| clause.eq_ignore_ascii_case(&token.text))
+    };
+    tokens.iter().any(|token| {
+        matches!(token.kind, TokenKind::String | TokenKind::Quoted) && !is_terminated(&token.text)
+    }) || tokens.windows(2).any(|pair| {
+        (is_operand(&pair[0]) && is_operand(&pair[1]))
+            || (is_clause(&pair[0]) && pair[0].text.eq_ignore_ascii_case(&pair[1].text))
+    })
+}
+
+/// A quoted token ends with its quote, which is not escaped.
+fn is_terminated(text: &str) -> bool {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut idx = 1;
+    while idx < chars.len() {
+        if chars[idx] == chars[0] {
+            return idx == chars.len() - 1;
+        }
+        idx += if chars[idx] == '\\' { 2 } else { 1 };
+    }
+    false
+}
+
+/// Remove a token, its neighbours stay two tokens.
+fn remove(tokens: &mut Vec<Token>, idx: usize) {
+    tokens.remove(idx);
+    if let Some(token) = tokens.get_mut(idx) {
+        token.spaced = true;
+    }
+}
+
+fn pick(random: &mut RandomGenerator, len: usize) -> usize {
+    random.under(len as _) as usize
+}
+
+fn positions(tokens: &[Token], pred: impl Fn(&Token) -> bool) -> Vec<usize> {
+    (0..tokens.len())
+        .filter(|idx| pred(&tokens[*idx]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_malformed, mutate, Mutation};
+    use crate::{ast::format::tokenize, common::RandomGenerator};
+
+    #[test]
+    fn test_mutation() {
+        let query = "MATCH (v0:Person {name: 'Alice'}) RETURN v0.name AS v1;";
+        let len = tokenize(query).len();
+        let mut random = RandomGenerator::new();
+        let mut apply = |mutation: Mutation| mutation.apply(query, &mut random).unwrap();
+
+        for _ in 0..20 {
+            assert_eq!(tokenize(&apply(Mutation::Drop)).len(), len - 1);
+            assert_eq!(tokenize(&apply(Mutation::Duplicate)).len(), len + 1);
+            assert_ne!(apply(Mutation::Swap), query);
+
+            let unbalanced = apply(Mutation::Unbalance);
+            let count = |query: &str, brackets: &[char]| {
+                query.chars().filter(|c| brackets.contains(c)).count()
+            };
+            assert_ne!(
+                count(&unbalanced, &['(', '[', '{']),
+                count(&unbalanced, &[')', ']', '}'])
+            );
+
+            let truncated = apply(Mutation::TruncateString);
+            assert!(truncated.starts_with("MATCH (v0:Person {name: '"));
+            assert!(!truncated.contains("Alice'"));
+
+            assert!(apply(Mutation::HugeIdentifier).len() > query.len() + 4096);
+            assert!(apply(Mutation::DeepNesting).len() > query.len() + 1000);
+        }
+
+        // no string to truncate.
+        assert_eq!(
+            Mutation::TruncateString.apply("RETURN 1;", &mut random),
+            None
+        );
+        assert_eq!(mutate("", &mut random), None);
+        let (mutated, mutation) = mutate("RETURN 1;", &mut random).unwrap();
+        assert_ne!(mutation, Mutation::TruncateString);
+        assert_ne!(mutated, "RETURN 1;");
+
+        for _ in 0..100 {
+            let (mutated, _) = mutate(query, &mut random).unwrap();
+            assert!(is_malformed(&mutated), "{}", mutated);
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        for query in [
+            "MATCH (v0) RETURN v0",
+            "MATCH (v0:Person {name: 'it\\'s'}) WHERE NOT NOT v0.ok RETURN count(*) AS n",
+            "RETURN CASE WHEN TRUE THEN CASE WHEN FALSE THEN 1 END END AS v0",
+            "MATCH p = SHORTEST 2 GROUPS (v0) ((v1)-->(v2)){1,3} (v3) RETURN p",
+            "CREATE INDEX index_Person_id FOR (v0:Person) ON (v0.id)",
+            "RETURN $p IS :: LIST<ANY> AS `a b`",
+        ] {
+            assert!(!is_malformed(query), "{}", query);
+        }
+        for query in [
+            "MATCH (v0 RETURN v0",
+            "MATCH (v0)) RETURN v0",
+            "MATCH [v0) RETURN v0",
+            "RETURN 'abc",
+            "RETURN 'abc\\'",
+            "RETURN v0.name v1",
+            "RETURN 1 2",
+            "MATCH MATCH (v0) RETURN v0",
+            "MATCH (v0) return RETURN v0",
+        ] {
+            assert!(is_malformed(query), "{}", query);
+        }
+    }
+}
@@ -0,0 +1,791 @@
+use serde::{Deserialize, Serialize};
+
+use super::CypherNode;
+use crate::common::{
+    BinOpKind, Expr, ExprKind, Literal, RandomGenerator, RelationshipDirection, Variable,
+};
+
+/// Functions whose value depends on the evaluation order or on the time of the evaluation.
+const NONDETERMINISTIC_FUNCTIONS: &[&str] = &[
+    "rand",
+    "randomuuid",
+    "timestamp",
+    "datetime",
+    "localdatetime",
+    "date",
+    "time",
+    "localtime",
+    "collect",
+];
+
+/// Semantics preserving rewrite of a query, the rewritten query returns the same rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rewrite {
+    /// Swap the different operands of `AND` and `OR`.
+    SwapOperands,
+    /// Match the relationship chains from their other end, the arrows reversed.
+    ReversePattern,
+    /// Match a node pattern of a MATCH in a MATCH of its own.
+    SplitMatch,
+    /// Filter with `WITH * WHERE` after the MATCH instead of its WHERE.
+    WhereToWith,
+    /// Insert a `WITH *` after a reading clause.
+    WithStar,
+    /// Run the query in a `CALL { }` subquery and return its columns.
+    WrapCall,
+}
+
+impl Rewrite {
+    pub const ALL: [Rewrite; 6] = [
+        Rewrite::SwapOperands,
+        Rewrite::ReversePattern,
+        Rewrite::SplitMatch,
+        Rewrite::WhereToWith,
+        Rewrite::WithStar,
+        Rewrite::WrapCall,
+    ];
+
+    /// Apply the rewrite to a `RegularQuery`, `None` if it does not apply to the query.
+    pub fn apply(&self, query: &CypherNode, random: &mut RandomGenerator) -> Option<CypherNode> {
+        if !matches!(query, CypherNode::RegularQuery { .. }) {
+            return None;
+        }
+        let mut query = query.clone();
+        let rewritten = match self {
+            Rewrite::SwapOperands => {
+                let mut swapped = 0;
+                walk(&mut query, &mut |_| {}, &mut |expr| {
+                    if let ExprKind::BinOp(BinOpKind::And | BinOpKind::Or, lhs, rhs) =
+                        &mut expr.kind
+                    {
+                        // swapping equal operands leaves the query as it was.
+                        if lhs.to_string() == rhs.to_string() {
+                            return;
+                        }
+                        std::mem::swap(lhs, rhs);
+                        swapped += 1;
+                    }
+                });
+                swapped > 0
+            }
+            Rewrite::ReversePattern => {
+                let mut reversed = 0;
+                walk(
+                    &mut query,
+                    &mut |node| {
+                        if let CypherNode::Match { pattern, .. } = node {
+                            if let CypherNode::Pattern { pattern_parts } = pattern.as_mut() {
+                                for part in pattern_parts.iter_mut() {
+                                    reversed += reverse_pattern_part(part) as u32;
+                                }
+                            }
+                        }
+                    },
+                    &mut |_| {},
+                );
+                reversed > 0
+            }
+            Rewrite::SplitMatch => {
+                let sites = sites(&mut query, |clause| {
+                    split_part(clause).is_some() && !is_optional(clause)
+                });
+                match pick(random, &sites) {
+                    Some((arm, part, idx)) => {
+                        let clauses = part_clauses(arm_mut(&mut query, arm), part);
+                        let (node, pattern_part) = split_part(&clauses[idx]).unwrap();
+                        if let Some(pattern) = pattern_mut(&mut clauses[idx]) {
+                            pattern.remove(pattern_part);
+                        }
+                        clauses.insert(idx, reading_match(node));
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Rewrite::WhereToWith => {
+                let sites = sites(&mut query, |clause| {
+                    !is_optional(clause) && where_mut(clause).is_some()
+                });
+                match pick(random, &sites) {
+                    Some((arm, part, idx)) => {
+                        let single_query = arm_mut(&mut query, arm);
+                        let clauses = part_clauses(single_query, part);
+                        let where_clause = where_mut(&mut clauses[idx]).and_then(Option::take);
+                        insert_with(single_query, part, idx, where_clause);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Rewrite::WithStar => {
+                let sites = sites(&mut query, binds_variables);
+                match pick(random, &sites) {
+                    Some((arm, part, idx)) => {
+                        insert_with(arm_mut(&mut query, arm), part, idx, None);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Rewrite::WrapCall => return wrap_call(query),
+        };
+        rewritten.then_some(query)
+    }
+}
+
+/// Rewrite the query by one of the rewrites which apply to it.
+pub fn rewrite(query: &CypherNode, random: &mut RandomGenerator) -> Option<(CypherNode, Rewrite)> {
+    let mut rewrites = Rewrite::ALL.to_vec();
+    while !rewrites.is_empty() {
+        let rewrite = rewrites.remove(random.under(rewrites.len() as _) as usize);
+        if let Some(rewritten) = rewrite.apply(query, random) {
+            return Some((rewritten, rewrite));
+        }
+    }
+    None
+}
+
+/// The rows of the query do not depend on the plan: it does not write, has no
+/// `SKIP` or `LIMIT` and calls no function depending on the order of the rows.
+pub fn is_deterministic(query: &CypherNode) -> bool {
+    let (mut nodes, mut exprs) = (true, true);
+    walk(
+        &mut query.clone(),
+        &mut |node| match node {
+            CypherNode::ProjectionBody { skip, limit, .. } => {
+                nodes &= skip.is_none() && limit.is_none();
+            }
+            CypherNode::UpdatingClause { .. }
+            | CypherNode::SchemaCommand { .. }
+            | CypherNode::StandaloneCall { .. }
+            | CypherNode::InQueryCall { .. } => nodes = false,
+            _ => {}
+        },
+        &mut |expr| {
+            if let ExprKind::Invocation(name, _, _) = &expr.kind {
+                let name = name.to_string().to_lowercase();
+                exprs &= !NONDETERMINISTIC_FUNCTIONS.contains(&name.as_str());
+            }
+        },
+    );
+    nodes && exprs
+}
+
+/// Visit every node and every expression, the subqueries of the expressions included.
+pub(super) fn walk(
+    node: &mut CypherNode,
+    on_node: &mut dyn FnMut(&mut CypherNode),
+    on_expr: &mut dyn FnMut(&mut Expr),
+) {
+    on_node(node);
+    let mut nodes: Vec<&mut Box<CypherNode>> = vec![];
+    let mut exprs: Vec<&mut Expr> = vec![];
+    match node {
+        CypherNode::Query { query } => nodes.push(query),
+        CypherNode::RegularQuery {
+            single_query,
+            union_all,
+        } => {
+            nodes.push(single_query);
+            nodes.extend(union_all.iter_mut());
+        }
+        CypherNode::StandaloneCall {
+            procedure,
+            yield_items,
+        } => {
+            nodes.push(procedure);
+            nodes.extend(yield_items.1.iter_mut());
+        }
+        CypherNode::SingleQuery { part_query } => nodes.push(part_query),
+        CypherNode::SinglePartQuery {
+            reading_clauses,
+            updating_clauses,
+            return_clause,
+        } => {
+            nodes.extend(reading_clauses.iter_mut());
+            nodes.extend(updating_clauses.iter_mut());
+            nodes.extend(return_clause.iter_mut());
+        }
+        CypherNode::MultiPartQuery {
+            multi_part,
+            single_part,
+        } => {
+            for (reading_clauses, updating_clauses, with) in multi_part.iter_mut() {
+                nodes.extend(reading_clauses.iter_mut());
+                nodes.extend(updating_clauses.iter_mut());
+                nodes.push(with);
+            }
+            nodes.push(single_part);
+        }
+        CypherNode::With {
+            projection_body,
+            where_clause,
+        } => {
+            nodes.push(projection_body);
+            exprs.extend(where_clause.iter_mut());
+        }
+        CypherNode::Union { union_all } => {
+            if let Some((_, query)) = union_all {
+                nodes.push(query);
+            }
+        }
+        CypherNode::ReadingClause { reading_clause } => nodes.push(reading_clause),
+        CypherNode::UpdatingClause { updating_clause } => nodes.push(updating_clause),
+        CypherNode::Return { projection_body } => nodes.push(projection_body),
+        CypherNode::ProjectionBody {
+            projection_items,
+            order,
+            skip,
+            limit,
+            ..
+        } => {
+            nodes.push(projection_items);
+            nodes.extend(order.iter_mut());
+            exprs.extend(skip.iter_mut());
+            exprs.extend(limit.iter_mut());
+        }
+        CypherNode::ProjectionItems { expressions, .. } => {
+            exprs.extend(expressions.iter_mut().map(|(expr, _)| expr))
+        }
+        CypherNode::Order { sort_items } => {
+            exprs.extend(sort_items.iter_mut().map(|(expr, _)| expr))
+        }
+        CypherNode::Match {
+            pattern,
+            where_clause,
+            ..
+        } => {
+            nodes.push(pattern);
+            exprs.extend(where_clause.iter_mut());
+        }
+        CypherNode::Unwind { expression, .. } => exprs.push(expression),
+        CypherNode::InQueryCall {
+            explicit_proceduce_invocation,
+            yield_items,
+        } => {
+            nodes.push(explicit_proceduce_invocation);
+            nodes.extend(yield_items.iter_mut());
+        }
+        CypherNode::CallSubquery { query, .. } => nodes.push(query),
+        CypherNode::Create { pattern } => nodes.push(pattern),
+        CypherNode::Merge {
+            pattern_part,
+            merge_actions,
+        } => {
+            nodes.push(pattern_part);
+            nodes.extend(merge_actions.iter_mut().map(|(_, action)| action));
+        }
+        CypherNode::Foreach {
+            expression,
+            updating_clauses,
+            ..
+        } => {
+            exprs.push(expression);
+            nodes.extend(updating_clauses.iter_mut());
+        }
+        CypherNode::ConditionalUpdate {
+            condition,
+            updating_clauses,
+            ..
+        } => {
+            exprs.push(condition);
+            nodes.extend(updating_clauses.iter_mut());
+        }
+        CypherNode::Delete { expressions, .. } => exprs.extend(expressions.iter_mut()),
+        CypherNode::Set {
+            property_set,
+            variable_set,
+            variable_add,
+            ..
+        } => {
+            for (property, value) in property_set.iter_mut() {
+                exprs.push(property);
+                exprs.push(value);
+            }
+            exprs.extend(
+                variable_set
+                    .iter_mut()
+                    .chain(variable_add.iter_mut())
+                    .map(|(_, expr)| expr),
+            );
+        }
+        CypherNode::ExplicitProcedureInvocation { expressions, .. } => {
+            exprs.extend(expressions.iter_mut())
+        }
+        CypherNode::YieldItems { where_clause, .. } => exprs.extend(where_clause.iter_mut()),
+        CypherNode::Remove {
+            property_remove, ..
+        } => exprs.extend(property_remove.iter_mut()),
+        CypherNode::SchemaCommand { command } => nodes.push(command),
+        CypherNode::Pattern { pattern_parts } => nodes.extend(pattern_parts.iter_mut()),
+        CypherNode::PatternPart {
+            pattern_element, ..
+        } => nodes.push(pattern_element),
+        CypherNode::PatternElement {
+            pattern_element: (node, chain),
+            ..
+        } => {
+            nodes.push(node);
+            for (relationship, node) in chain.iter_mut() {
+                nodes.push(relationship);
+                nodes.push(node);
+            }
+        }
+        CypherNode::QuantifiedPathPattern {
+            pattern_element,
+            where_clause,
+            ..
+        } => {
+            nodes.push(pattern_element);
+            exprs.extend(where_clause.iter_mut());
+        }
+        CypherNode::LoadCsv { .. }
+        | CypherNode::ImplicitProcedureInvocation { .. }
+        | CypherNode::CreateIndex { .. }
+        | CypherNode::DropIndex { .. }
+        | CypherNode::CreateConstraint { .. }
+        | CypherNode::DropConstraint { .. }
+        | CypherNode::ShowSchema { .. }
+        | CypherNode::NodePattern { .. }
+        | CypherNode::RelationshipPattern { .. } => {}
+    }
+    for node in nodes {
+        walk(node, on_node, on_expr);
+    }
+    for expr in exprs {
+        walk_expr(expr, on_node, on_expr);
+    }
+}
+
+pub(super) fn walk_expr(
+    expr: &mut Expr,
+    on_node: &mut dyn FnMut(&mut CypherNode),
+    on_expr: &mut dyn FnMut(&mut Expr),
+) {
+    on_expr(expr);
+    let mut exprs: Vec<&mut Expr> = vec![];
+    match &mut expr.kind {
+        ExprKind::BinOp(_, lhs, rhs) => {
+            exprs.push(lhs);
+            exprs.push(rhs);
+        }
+        ExprKind::UnOp(_, expr)
+        | ExprKind::Property(expr, _)
+        | ExprKind::Label(expr, _)
+        | ExprKind::PredicateFunction(_, expr)
+        | ExprKind::TypePredicate(expr, _, _) => exprs.push(expr),
+        ExprKind::Cmp(expr, tails) => {
+            exprs.push(expr);
+            exprs.extend(tails.iter_mut().map(|(_, expr)| expr.as_mut()));
+        }
+        ExprKind::Lit(Literal::List(items)) => exprs.extend(items.iter_mut()),
+        ExprKind::Lit(Literal::Map(entries)) => {
+            exprs.extend(entries.iter_mut().map(|(_, expr)| expr))
+        }
+        ExprKind::Lit(_) | ExprKind::Variable(_) | ExprKind::PredicateVariable(_) => {}
+        ExprKind::Case(expr, alternatives, else_expr) => {
+            exprs.extend(expr.iter_mut().map(|expr| expr.as_mut()));
+            for alternative in alternatives.iter_mut() {
+                exprs.push(&mut alternative.condition);
+                exprs.push(&mut alternative.value);
+            }
+            exprs.extend(else_expr.iter_mut().map(|expr| expr.as_mut()));
+        }
+        ExprKind::Invocation(_, _, args) => exprs.extend(args.iter_mut()),
+        ExprKind::FilterExpression(_, in_expr, where_expr) => {
+            exprs.push(in_expr);
+            exprs.extend(where_expr.iter_mut().map(|expr| expr.as_mut()));
+        }
+        ExprKind::SubQuery(_, query, where_clause) => {
+            walk(query, on_node, on_expr);
+            exprs.extend(where_clause.iter_mut().map(|expr| expr.as_mut()));
+        }
+    }
+    for expr in exprs {
+        walk_expr(expr, on_node, on_expr);
+    }
+}
+
+/// Reverse `(a)-[r]->(b)<-[s]-(c)` into `(c)-[s]->(b)<-[r]-(a)`. Not for named paths,
+/// path selectors, quantified path patterns and variable length relationships bound to
+/// a variable, their values depend on the direction of the pattern.
+fn reverse_pattern_part(part: &mut CypherNode) -> bool {
+    let CypherNode::PatternPart {
+        var: None,
+        selector: None,
+        pattern_element,
+        ..
+    } = part
+    else {
+        return false;
+    };
+    let CypherNode::PatternElement {
+        pattern_element: (head, chain),
+        ..
+    } = pattern_element.as_mut()
+    else {
+        return false;
+    };
+    let reversible = chain.iter().all(|(relationship, _)| {
+        matches!(relationship.as_ref(), CypherNode::RelationshipPattern { var, is_range, .. }
+            if var.is_none() || !is_range)
+    });
+    if chain.is_empty() || !reversible {
+        return false;
+    }
+
+    let mut nodes = vec![head.clone()];
+    let mut relationships = vec![];
+    for (relationship, node) in chain.drain(..) {
+        relationships.push(relationship);
+        nodes.push(node);
+    }
+    *head = nodes.pop().unwrap();
+    while let Some(mut relationship) = relationships.pop() {
+        if let CypherNode::RelationshipPattern { direction, .. } = relationship.as_mut() {
+            *direction = match direction {
+                RelationshipDirection::Left => RelationshipDirection::Right,
+                RelationshipDirection::Right => RelationshipDirection::Left,
+                RelationshipDirection::Both => RelationshipDirection::Both,
+                RelationshipDirection::None => RelationshipDirection::None,
+            };
+        }
+        chain.push((relationship, nodes.pop().unwrap()));
+    }
+    true
+}
+
+/// The reading clauses of each part of a `SingleQuery`, the parts end with a WITH.
+fn parts(single_query: &mut CypherNode) -> Vec<&mut Vec<Box<CypherNode>>> {
+    let CypherNode::SingleQuery { part_query } = single_query else {
+        return vec![];
+    };
+    let (multi_part, single_part) = match part_query.as_mut() {
+        CypherNode::MultiPartQuery {
+            multi_part,
+            single_part,
+        } => (Some(multi_part), single_part.as_mut()),
+        single_part => (None, single_part),
+    };
+    let mut parts = vec![];
+    for (reading_clauses, _, _) in multi_part.into_iter().flatten() {
+        parts.push(reading_clauses);
+    }
+    if let CypherNode::SinglePartQuery {
+        reading_clauses, ..
+    } = single_part
+    {
+        parts.push(reading_clauses);
+    }
+    parts
+}
+
+fn part_clauses(single_query: &mut CypherNode, part: usize) -> &mut Vec<Box<CypherNode>> {
+    parts(single_query).swap_remove(part)
+}
+
+/// The single queries of the UNION arms.
+fn arms(query: &mut CypherNode) -> Vec<&mut CypherNode> {
+    let CypherNode::RegularQuery {
+        single_query,
+        union_all,
+    } = query
+    else {
+        return vec![];
+    };
+    let mut arms = vec![single_query.as_mut()];
+    for union in union_all.iter_mut() {
+        if let CypherNode::Union {
+            union_all: Some((_, single_query)),
+        } = union.as_mut()
+        {
+            arms.push(single_query.as_mut());
+        }
+    }
+    arms
+}
+
+fn arm_mut(query: &mut CypherNode, arm: usize) -> &mut CypherNode {
+    arms(query).swap_remove(arm)
+}
+
+/// (arm, part, index) of the reading clauses satisfying `pred`.
+fn sites(
+    query: &mut CypherNode,
+    pred: impl Fn(&mut CypherNode) -> bool,
+) -> Vec<(usize, usize, usize)> {
+    let mut sites = vec![];
+    for (arm, single_query) in arms(query).into_iter().enumerate() {
+        for (part, reading_clauses) in parts(single_query).into_iter().enumerate() {
+            for (idx, clause) in reading_clauses.iter_mut().enumerate() {
+                if pred(clause) {
+                    sites.push((arm, part, idx));
+                }
+            }
+        }
+    }
+    sites
+}
+
+fn pick<T: Copy>(random: &mut RandomGenerator, items: &[T]) -> Option<T> {
+    (!items.is_empty()).then(|| items[random.under(items.len() as _) as usize])
+}
+
+fn match_mut(clause: &mut CypherNode) -> Option<&mut CypherNode> {
+    match clause {
+        CypherNode::ReadingClause { reading_clause } => match reading_clause.as_mut() {
+            node @ CypherNode::Match { .. } => Some(node),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_optional(clause: &mut CypherNode) -> bool {
+    matches!(
+        match_mut(clause),
+        Some(CypherNode::Match {
+            is_optional: true,
+            ..
+        })
+    )
+}
+
+fn where_mut(clause: &mut CypherNode) -> Option<&mut Option<Expr>> {
+    match match_mut(clause)? {
+        CypherNode::Match {
+            where_clause: where_clause @ Some(_),
+            ..
+        } => Some(where_clause),
+        _ => None,
+    }
+}
+
+fn pattern_mut(clause: &mut CypherNode) -> Option<&mut Vec<Box<CypherNode>>> {
+    match match_mut(clause)? {
+        CypherNode::Match { pattern, .. } => match pattern.as_mut() {
+            CypherNode::Pattern { pattern_parts } => Some(pattern_parts),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A pattern part of a single node and its index, when the MATCH has other parts. The
+/// node binds no relationship, matching it apart keeps the relationships unique.
+fn split_part(clause: &CypherNode) -> Option<(Box<CypherNode>, usize)> {
+    let mut clause = clause.clone();
+    let pattern_parts = pattern_mut(&mut clause)?;
+    if pattern_parts.len() < 2 {
+        return None;
+    }
+    pattern_parts
+        .iter()
+        .position(|part| {
+            matches!(part.as_ref(), CypherNode::PatternPart { pattern_element, .. }
+                if matches!(pattern_element.as_ref(), CypherNode::PatternElement {
+                    pattern_element: (_, chain), ..
+                } if chain.is_empty()))
+        })
+        .map(|idx| (pattern_parts[idx].clone(), idx))
+}
+
+fn reading_match(pattern_part: Box<CypherNode>) -> Box<CypherNode> {
+    Box::new(CypherNode::ReadingClause {
+        reading_clause: Box::new(CypherNode::Match {
+            is_optional: false,
+            pattern: Box::new(CypherNode::Pattern {
+                pattern_parts: vec![pattern_part],
+            }),
+            where_clause: None,
+        }),
+    })
+}
+
+/// A reading clause binding at least one variable, `WITH *` needs one in scope.
+fn binds_variables(clause: &mut CypherNode) -> bool {
+    let CypherNode::ReadingClause { reading_clause } = clause else {
+        return false;
+    };
+    match reading_clause.as_mut() {
+        CypherNode::Unwind { .. } | CypherNode::LoadCsv { .. } => true,
+        node @ CypherNode::Match { .. } => {
+            let mut binds = false;
+            walk(
+                node,
+                &mut |node| {
+                    binds |= matches!(
+                        node,
+                        CypherNode::NodePattern { var: Some(_), .. }
+                            | CypherNode::RelationshipPattern { var: Some(_), .. }
+                            | CypherNode::PatternPart { var: Some(_), .. }
+                    )
+                },
+                &mut |_| {},
+            );
+            binds
+        }
+        _ => false,
+    }
+}
+
+/// `WITH *` ProjectionBody.
+fn projection_all() -> Box<CypherNode> {
+    Box::new(CypherNode::ProjectionBody {
+        is_distinct: false,
+        projection_items: Box::new(CypherNode::ProjectionItems {
+            is_all: true,
+            expressions: vec![],
+        }),
+        order: None,
+        skip: None,
+        limit: None,
+    })
+}
+
+/// Insert `WITH * (WHERE ..)?` after the reading clause `idx` of a part, the part is
+/// split in two at the WITH.
+fn insert_with(single_query: &mut CypherNode, part: usize, idx: usize, where_clause: Option<Expr>) {
+    let CypherNode::SingleQuery { part_query } = single_query else {
+        return;
+    };
+    // the parts are moved out, an empty UNION stands in until the query is rebuilt.
+    let (mut multi_part, single_part) =
+        match std::mem::replace(part_query.as_mut(), CypherNode::Union { union_all: None }) {
+            CypherNode::MultiPartQuery {
+                multi_part,
+                single_part,
+            } => (multi_part, *single_part),
+            single_part => (vec![], single_part),
+        };
+    let CypherNode::SinglePartQuery {
+        reading_clauses,
+        updating_clauses,
+        return_clause,
+    } = single_part
+    else {
+        unreachable!("the last part of a query is a SinglePartQuery");
+    };
+
+    let with = Box::new(CypherNode::With {
+        projection_body: projection_all(),
+        where_clause,
+    });
+    let (reading_clauses, updating_clauses) = if part < multi_part.len() {
+        let (reading, updating, end) = multi_part.remove(part);
+        let (head, tail) = split_at(reading, idx + 1);
+        multi_part.insert(part, (tail, updating, end));
+        multi_part.insert(part, (head, vec![], with));
+        (reading_clauses, updating_clauses)
+    } else {
+        let (head, tail) = split_at(reading_clauses, idx + 1);
+        multi_part.push((head, vec![], with));
+        (tail, updating_clauses)
+    };
+    **part_query = CypherNode::MultiPartQuery {
+        multi_part,
+        single_part: Box::new(CypherNode::SinglePartQuery {
+            reading_clauses,
+            updating_clauses,
+            return_clause,
+        }),
+    };
+}
+
+fn split_at<T>(mut items: Vec<T>, idx: usize) -> (Vec<T>, Vec<T>) {
+    let tail = items.split_off(idx);
+    (items, tail)
+}
+
+/// `CALL {` query `} RETURN` columns, each column of the arms is named.
+fn wrap_call(mut query: CypherNode) -> Option<CypherNode> {
+    let mut columns: Vec<String> = vec![];
+    for single_query in arms(&mut query) {
+        let CypherNode::SingleQuery { part_query } = single_query else {
+            return None;
+        };
+        let single_part = match part_query.as_mut() {
+            CypherNode::MultiPartQuery { single_part, .. } => single_part.as_mut(),
+            single_part => single_part,
+        };
+        let CypherNode::SinglePartQuery {
+            return_clause: Some(return_clause),
+            ..
+        } = single_part
+        else {
+            return None;
+        };
+        let CypherNode::Return { projection_body } = return_clause.as_mut() else {
+            return None;
+        };
+        let CypherNode::ProjectionBody {
+            projection_items, ..
+        } = projection_body.as_mut()
+        else {
+            return None;
+        };
+        let CypherNode::ProjectionItems {
+            is_all: false,
+            expressions,
+        } = projection_items.as_mut()
+        else {
+            return None;
+        };
+
+        for (idx, (expr, alias)) in expressions.iter_mut().enumerate() {
+            let name = match (alias.as_ref(), &expr.kind) {
+                (Some(alias), _) => alias.get_name(),
+                (None, ExprKind::Variable(var)) => var.get_name(),
+                (None, _) => format!("c{}", idx),
+            };
+            match columns.get(idx) {
+                // the arms of a UNION return the same columns.
+                Some(column) if *column != name => {
+                    *alias = Some(Variable::new(column.clone()));
+                }
+                Some(_) => {}
+                None => {
+                    if alias.is_none() && !matches!(expr.kind, ExprKind::Variable(_)) {
+                        *alias = Some(Variable::new(name.clone()));
+                    }
+                    columns.push(name);
+                }
+            }
+        }
+    }
+    if columns.is_empty() {
+        return None;
+    }
+
+    let return_clause = CypherNode::Return {
+        projection_body: Box::new(CypherNode::ProjectionBody {
+            is_distinct: false,
+            projection_items: Box::new(CypherNode::ProjectionItems {
+                is_all: false,
+                expressions: columns
+                    .into_iter()
+                    .map(|column| (Expr::from(ExprKind::Variable(Variable::new(column))), None))
+                    .collect(),
+            }),
+            order: None,
+            skip: None,
+            limit: None,
+        }),
+    };
+    Some(CypherNode::RegularQuery {
+        single_query: Box::new(CypherNode::SingleQuery {
+            part_query: Box::new(CypherNode::SinglePartQuery {
+                reading_clauses: vec![Box::new(CypherNode::ReadingClause {
+                    reading_clause: Box::new(CypherNode::CallSubquery {
+                        import_variables: vec![],
+                        query: Box::new(query),
+                        in_transactions: (false, None),
+                    }),
+                })],
+                updating_clauses: vec![],
+                return_clause: Some(Box::new(return_clause)),
+            }),
+        }),
+        union_all: vec![],
+    })
+}
@@ -1,23 +1,142 @@
-use super::cypher::{ConvertVisitor, CypherNode};
+use super::{
+    cypher::{ConvertVisitor, CypherNode},
+    format::{self, Layout},
+};
 
 use crate::{
-    common::{Expr, FieldValue, NameSpace, Property, RelationshipDirection, Variable},
+    common::{
+        escape_name, ConstraintKind, Expr, FieldValue, NameSpace, PathMode, PathSelector, Property,
+        RandomGenerator, RelationshipDirection, Variable,
+    },
+    config::Dialect,
     meta::Label,
 };
 
 pub struct TransformVisitor {
-    // cypher_string: String,
+    // syntax of the rendered query.
+    dialect: Dialect,
+    layout: Layout,
+    // directory of the csv fixtures read by LOAD CSV.
+    csv_dir: String,
 }
 
 impl TransformVisitor {
+    // the tests render in the default dialect.
+    #[cfg(test)]
     pub fn new() -> TransformVisitor {
+        Self::with_dialect(Dialect::default())
+    }
+
+    pub fn with_dialect(dialect: Dialect) -> TransformVisitor {
         Self {
-            // cypher_string: "".to_string(),
+            dialect,
+            layout: Layout::default(),
+            csv_dir: String::new(),
+        }
+    }
+
+    pub fn layout(mut self, layout: Layout) -> TransformVisitor {
+        self.layout = layout;
+        self
+    }
+
+    pub fn csv_dir(mut self, csv_dir: &str) -> TransformVisitor {
+        self.csv_dir = csv_dir.to_string();
+        self
+    }
+
+    /// Expression in the syntax of the dialect, redundant parentheses in the fuzzed layout.
+    fn expr(&self, expr: &Expr) -> String {
+        if self.layout == Layout::Fuzzed {
+            let mut expr = expr.clone();
+            format::parenthesize(&mut expr, &mut RandomGenerator::new());
+            return expr.display(self.dialect).to_string();
         }
+        expr.display(self.dialect).to_string()
     }
 
     pub fn exec(&mut self, query: Box<CypherNode>) -> String {
-        self.visit(query)
+        let query = self.visit(query);
+        self.layout.apply(&query)
+    }
+
+    /// Pattern of an index or constraint: `(v:Label)` or `()-[v:Label]-()`.
+    fn schema_pattern(&self, variable: &Variable, label: &Label) -> String {
+        if label.is_vertex() {
+            format!("({}:{})", variable, label)
+        } else {
+            format!("()-[{}:{}]-()", variable, label)
+        }
+    }
+
+    /// PropertyExpression+: `v.a` or `(v.a,v.b)`.
+    fn schema_properties(&self, variable: &Variable, properties: Vec<Property>) -> String {
+        let properties_string = properties
+            .into_iter()
+            .map(|prop| format!("{}.{}", variable, prop))
+            .collect::<Vec<_>>();
+        if properties_string.len() == 1 {
+            properties_string[0].clone()
+        } else {
+            format!("({})", properties_string.join(","))
+        }
+    }
+
+    /// GQL `LET` Variable `=` Expression (`,` Variable `=` Expression)* of a
+    /// `WITH *, Expression AS Variable, ..`, which only adds variables.
+    fn let_bindings(&self, projection_body: &CypherNode) -> Option<String> {
+        if let CypherNode::ProjectionBody {
+            is_distinct: false,
+            projection_items,
+            order: None,
+            skip: None,
+            limit: None,
+        } = projection_body
+        {
+            if let CypherNode::ProjectionItems {
+                is_all: true,
+                expressions,
+            } = projection_items.as_ref()
+            {
+                let bindings = expressions
+                    .iter()
+                    .map(|(expr, var)| {
+                        var.as_ref()
+                            .map(|var| format!("{} = {}", var.get_name(), self.expr(expr)))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                if !bindings.is_empty() {
+                    return Some(format!("LET {}", bindings.join(", ")));
+                }
+            }
+        }
+        None
+    }
+
+    /// Labels of a SET or REMOVE item, GQL sets one label per item.
+    fn label_items(&self, var: &Variable, labels: Vec<Label>) -> String {
+        if self.dialect == Dialect::Gql {
+            return labels
+                .into_iter()
+                .map(|label| format!("{}:{}", var.get_name(), label.get_name()))
+                .collect::<Vec<_>>()
+                .join(",");
+        }
+        let mut x = var.get_name();
+        for label in labels {
+            x += ":";
+            x += &label.get_name();
+        }
+        x
+    }
+
+    /// UpdatingClause+ separated by spaces.
+    fn visit_updating_clauses(&mut self, updating_clauses: Vec<Box<CypherNode>>) -> String {
+        updating_clauses
+            .into_iter()
+            .map(|updating_clause| self.visit(updating_clause))
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 }
 
@@ -82,24 +201,21 @@ impl ConvertVisitor for TransformVisitor {
         updating_clauses: Vec<Box<CypherNode>>,
         return_clause: Option<Box<CypherNode>>,
     ) -> Self::Output {
-        let mut query_string = String::new();
+        let mut clauses = vec![];
 
         for reading_clause in reading_clauses {
-            query_string += &self.visit(reading_clause);
-            query_string += " ";
+            clauses.push(self.visit(reading_clause));
         }
 
         for updating_clause in updating_clauses {
-            query_string += &self.visit_updating_clause(updating_clause);
-            query_string += " ";
+            clauses.push(self.visit_updating_clause(updating_clause));
         }
 
         if let Some(return_clause) = return_clause {
-            query_string += " ";
-            query_string += &self.visit(return_clause);
+            clauses.push(self.visit(return_clause));
         }
 
-        query_string
+        clauses.join(" ")
     }
 
     /// MultiPartQuery: (ReadingClause* UpdatingClause* With)+ SinglePartQuery
@@ -132,19 +248,34 @@ impl ConvertVisitor for TransformVisitor {
     }
 
     /// With: `WITH` ProjectionBody Where?
+    ///
+    /// GQL: `LET` bindings or `RETURN` ProjectionBody `NEXT`, then `FILTER` Expression.
     fn visit_with(
         &mut self,
         projection_body: Box<CypherNode>,
         where_clause: Option<Expr>,
     ) -> Self::Output {
+        let mut with_string;
+        if self.dialect == Dialect::Gql {
+            with_string = match self.let_bindings(&projection_body) {
+                Some(let_string) => let_string,
+                None => format!("RETURN{} NEXT", self.visit(projection_body)),
+            };
+            if let Some(where_clause) = where_clause {
+                with_string += " FILTER ";
+                with_string += &self.expr(&where_clause);
+            }
+            return with_string;
+        }
+
         // WITH ProjectionBody
-        let mut with_string = "WITH".to_string();
+        with_string = "WITH".to_string();
         with_string += &self.visit(projection_body);
 
         // Where Expr
         if let Some(where_clause) = where_clause {
             with_string += " WHERE ";
-            with_string += &where_clause.to_string();
+            with_string += &self.expr(&where_clause);
         }
 
         with_string
@@ -208,11 +339,11 @@ impl ConvertVisitor for TransformVisitor {
         }
         if let Some(skip_expr) = skip {
             query_string += " ";
-            query_string += &skip_expr.to_string();
+            query_string += &self.expr(&skip_expr);
         }
         if let Some(limit_expr) = limit {
             query_string += " ";
-            query_string += &limit_expr.to_string();
+            query_string += &self.expr(&limit_expr);
         }
 
         query_string
@@ -232,7 +363,7 @@ impl ConvertVisitor for TransformVisitor {
         let expr_string = expressions
             .into_iter()
             .map(|(expr, var)| {
-                let mut x = expr.to_string();
+                let mut x = self.expr(&expr);
                 if let Some(var) = var {
                     x += " AS ";
                     x += &var.get_name();
@@ -263,8 +394,11 @@ impl ConvertVisitor for TransformVisitor {
             unreachable!()
         }
 
-        for (expr, rule) in sort_items.into_iter() {
-            query_string += &expr.to_string();
+        for (idx, (expr, rule)) in sort_items.into_iter().enumerate() {
+            if idx > 0 {
+                query_string += ", ";
+            }
+            query_string += &self.expr(&expr);
             if let Some(rule) = rule {
                 query_string += " ";
                 query_string += &rule;
@@ -292,19 +426,29 @@ impl ConvertVisitor for TransformVisitor {
         // Pattern
         query_string += &self.visit(pattern);
 
-        // Where Clause
+        // Where Clause, a FILTER statement in GQL unless the match is optional.
         if let Some(where_clause) = where_clause {
-            query_string += " WHERE ";
-            query_string += &where_clause.to_string();
+            query_string += if self.dialect == Dialect::Gql && !is_optional {
+                " FILTER "
+            } else {
+                " WHERE "
+            };
+            query_string += &self.expr(&where_clause);
         }
 
         query_string
     }
 
     /// UNWIND: `UNWIND` expression AS variable.
+    ///
+    /// GQL: `FOR` variable `IN` expression.
     fn visit_unwind(&mut self, expression: Expr, variable: Variable) -> Self::Output {
+        if self.dialect == Dialect::Gql {
+            return format!("FOR {} IN {}", variable.get_name(), self.expr(&expression));
+        }
+
         let mut unwind_string = "UNWIND ".to_string();
-        unwind_string += &expression.to_string();
+        unwind_string += &self.expr(&expression);
         unwind_string += " AS ";
         unwind_string += &variable.get_name();
         unwind_string
@@ -328,9 +472,129 @@ impl ConvertVisitor for TransformVisitor {
         query_string
     }
 
-    /// Create: Create Pattern
+    /// CallSubquery: `CALL` `{` (`WITH` Variable+)? RegularQuery `}` (`IN TRANSACTIONS` (`OF` n `ROWS`)?)?
+    ///
+    /// The importing WITH is repeated at the start of every union arm, GQL imports
+    /// the variables by a scope clause: `CALL` `(` Variable* `)` `{` .. `}`.
+    fn visit_call_subquery(
+        &mut self,
+        import_variables: Vec<Variable>,
+        query: Box<CypherNode>,
+        in_transactions: (bool, Option<i32>),
+    ) -> Self::Output {
+        let mut query_string = "CALL { ".to_string();
+        let mut import_string = String::new();
+        if self.dialect == Dialect::Gql {
+            query_string = format!(
+                "CALL ({}) {{ ",
+                import_variables
+                    .iter()
+                    .map(|var| var.get_name())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            );
+        } else if !import_variables.is_empty() {
+            import_string += "WITH ";
+            import_string += &import_variables
+                .into_iter()
+                .map(|var| var.get_name())
+                .collect::<Vec<_>>()
+                .join(",");
+            import_string += " ";
+        }
+
+        match *query {
+            CypherNode::RegularQuery {
+                single_query,
+                union_all,
+            } => {
+                query_string += &import_string;
+                query_string += &self.visit(single_query);
+
+                for union_node in union_all {
+                    if let CypherNode::Union {
+                        union_all: Some((is_all, single_part)),
+                    } = *union_node
+                    {
+                        query_string += if is_all { " UNION ALL " } else { " UNION " };
+                        query_string += &import_string;
+                        query_string += &self.visit(single_part);
+                    }
+                }
+            }
+            query => {
+                query_string += &import_string;
+                query_string += &self.visit(query);
+            }
+        }
+        query_string += " }";
+
+        // IN TRANSACTIONS (OF n ROWS)?
+        if in_transactions.0 {
+            query_string += " IN TRANSACTIONS";
+            if let Some(rows) = in_transactions.1 {
+                query_string += " OF ";
+                query_string += &rows.to_string();
+                query_string += " ROWS";
+            }
+        }
+
+        query_string
+    }
+
+    /// LoadCsv: `LOAD CSV` (`WITH HEADERS`)? `FROM` url `AS` Variable (`FIELDTERMINATOR` string)?
+    ///
+    /// Memgraph: `LOAD CSV FROM` url (`WITH`|`NO`) `HEADER` (`DELIMITER` string)? `AS` Variable
+    fn visit_load_csv(
+        &mut self,
+        with_headers: bool,
+        file: String,
+        variable: Variable,
+        field_terminator: Option<String>,
+    ) -> Self::Output {
+        let url = self.dialect.csv_location(&self.csv_dir, &file);
+        if self.dialect == Dialect::Memgraph {
+            let mut query_string = format!("LOAD CSV FROM '{}' ", url);
+            query_string += if with_headers {
+                "WITH HEADER"
+            } else {
+                "NO HEADER"
+            };
+            if let Some(field_terminator) = field_terminator {
+                query_string += &format!(" DELIMITER '{}'", field_terminator);
+            }
+            query_string += " AS ";
+            query_string += &variable.get_name();
+            return query_string;
+        }
+
+        let mut query_string = "LOAD CSV ".to_string();
+
+        if with_headers {
+            query_string += "WITH HEADERS ";
+        }
+
+        query_string += "FROM '";
+        query_string += &url;
+        query_string += "' AS ";
+        query_string += &variable.get_name();
+
+        if let Some(field_terminator) = field_terminator {
+            query_string += " FIELDTERMINATOR '";
+            query_string += &field_terminator;
+            query_string += "'";
+        }
+
+        query_string
+    }
+
+    /// Create: Create Pattern, `INSERT` Pattern in GQL.
     fn visit_create(&mut self, pattern: Box<CypherNode>) -> Self::Output {
-        let mut create_string = "CREATE ".to_string();
+        let mut create_string = if self.dialect == Dialect::Gql {
+            "INSERT ".to_string()
+        } else {
+            "CREATE ".to_string()
+        };
         create_string += &self.visit(pattern);
         create_string
     }
@@ -357,6 +621,44 @@ impl ConvertVisitor for TransformVisitor {
         merge_string
     }
 
+    /// Foreach: `FOREACH` `(` Variable `IN` Expression `|` UpdatingClause+ `)`
+    fn visit_foreach(
+        &mut self,
+        variable: Variable,
+        expression: Expr,
+        updating_clauses: Vec<Box<CypherNode>>,
+    ) -> Self::Output {
+        let mut foreach_string = "FOREACH (".to_string();
+        foreach_string += &variable.get_name();
+        foreach_string += " IN ";
+        foreach_string += &self.expr(&expression);
+        foreach_string += " | ";
+        foreach_string += &self.visit_updating_clauses(updating_clauses);
+        foreach_string += ")";
+
+        foreach_string
+    }
+
+    /// ConditionalUpdate
+    ///
+    /// `FOREACH` `(` Variable `IN` `CASE WHEN` condition `THEN [1] ELSE [] END` `|` UpdatingClause+ `)`
+    fn visit_conditional_update(
+        &mut self,
+        variable: Variable,
+        condition: Expr,
+        updating_clauses: Vec<Box<CypherNode>>,
+    ) -> Self::Output {
+        let mut foreach_string = "FOREACH (".to_string();
+        foreach_string += &variable.get_name();
+        foreach_string += " IN CASE WHEN ";
+        foreach_string += &self.expr(&condition);
+        foreach_string += " THEN [1] ELSE [] END | ";
+        foreach_string += &self.visit_updating_clauses(updating_clauses);
+        foreach_string += ")";
+
+        foreach_string
+    }
+
     /// ### Delete
     ///
     /// `Detach`? Delete (Expression)*
@@ -373,7 +675,7 @@ impl ConvertVisitor for TransformVisitor {
 
         let exprs = expressions
             .into_iter()
-            .map(|expr| expr.to_string())
+            .map(|expr| self.expr(&expr))
             .collect::<Vec<_>>()
             .join(",");
 
@@ -397,9 +699,9 @@ impl ConvertVisitor for TransformVisitor {
         let mut set_string = "SET ".to_string();
 
         let property_string = property_set.into_iter().map(|(property, expr)| {
-            let mut ret = property.to_string();
+            let mut ret = self.expr(&property);
             ret += "=";
-            ret += &expr.to_string();
+            ret += &self.expr(&expr);
             ret
         });
 
@@ -407,7 +709,7 @@ impl ConvertVisitor for TransformVisitor {
         let variable_string = variable_set.into_iter().map(|(var, expr)| {
             let mut ret = var.get_name();
             ret += "=";
-            ret += &expr.to_string();
+            ret += &self.expr(&expr);
             ret
         });
 
@@ -415,19 +717,14 @@ impl ConvertVisitor for TransformVisitor {
         let variable_add_string = variable_add.into_iter().map(|(var, expr)| {
             let mut ret = var.get_name();
             ret += "+=";
-            ret += &expr.to_string();
+            ret += &self.expr(&expr);
             ret
         });
 
         // Variable = NodeLabels
-        let label_string = label_set.into_iter().map(|(var, labels)| {
-            let mut ret = var.get_name();
-            for label in labels {
-                ret += ":";
-                ret += &label.get_name();
-            }
-            ret
-        });
+        let label_string = label_set
+            .into_iter()
+            .map(|(var, labels)| self.label_items(&var, labels));
 
         // collect string
         let set_items_string = property_string
@@ -459,7 +756,7 @@ impl ConvertVisitor for TransformVisitor {
         if !expressions.is_empty() {
             let exprs = expressions
                 .into_iter()
-                .map(|expr| expr.to_string())
+                .map(|expr| self.expr(&expr))
                 .collect::<Vec<_>>()
                 .join(",");
             query_string += &exprs;
@@ -514,7 +811,7 @@ impl ConvertVisitor for TransformVisitor {
         // Where Clasue
         if let Some(where_clause) = where_clause {
             query_string += " WHERE ";
-            query_string += &where_clause.to_string();
+            query_string += &self.expr(&where_clause);
         }
 
         query_string
@@ -537,18 +834,13 @@ impl ConvertVisitor for TransformVisitor {
             unreachable!()
         }
 
-        let variable = variable_remove.into_iter().map(|(var, labels)| {
-            let mut x = var.get_name();
-            for label in labels {
-                x += ":";
-                x += &label.get_name();
-            }
-            x
-        });
+        let variable = variable_remove
+            .into_iter()
+            .map(|(var, labels)| self.label_items(&var, labels));
 
         let property = property_remove
             .into_iter()
-            .map(|property| property.to_string());
+            .map(|property| self.expr(&property));
 
         let res_chain = variable.chain(property).collect::<Vec<_>>().join(",");
 
@@ -557,6 +849,105 @@ impl ConvertVisitor for TransformVisitor {
         remove_string
     }
 
+    /// SchemaCommand: CreateIndex | DropIndex | CreateConstraint | DropConstraint | ShowSchema
+    fn visit_schema_command(&mut self, command: Box<CypherNode>) -> Self::Output {
+        self.visit(command)
+    }
+
+    /// CreateIndex: `CREATE INDEX` name (`IF NOT EXISTS`)? `FOR` pattern `ON` `(` PropertyExpression+ `)`
+    fn visit_create_index(
+        &mut self,
+        name: String,
+        if_not_exists: bool,
+        variable: Variable,
+        label: Label,
+        properties: Vec<Property>,
+    ) -> Self::Output {
+        let mut query_string = "CREATE INDEX ".to_string();
+        query_string += &escape_name(&name);
+        if if_not_exists {
+            query_string += " IF NOT EXISTS";
+        }
+
+        query_string += " FOR ";
+        query_string += &self.schema_pattern(&variable, &label);
+
+        query_string += " ON (";
+        query_string += &properties
+            .into_iter()
+            .map(|prop| format!("{}.{}", variable, prop))
+            .collect::<Vec<_>>()
+            .join(",");
+        query_string += ")";
+
+        query_string
+    }
+
+    /// DropIndex: `DROP INDEX` name (`IF EXISTS`)?
+    fn visit_drop_index(&mut self, name: String, if_exists: bool) -> Self::Output {
+        let mut query_string = "DROP INDEX ".to_string();
+        query_string += &escape_name(&name);
+        if if_exists {
+            query_string += " IF EXISTS";
+        }
+        query_string
+    }
+
+    /// CreateConstraint
+    ///
+    /// `CREATE CONSTRAINT` name (`IF NOT EXISTS`)? `FOR` pattern `REQUIRE` PropertyExpression+ ConstraintKind
+    ///
+    /// Neo4j 4 spells it `ON` pattern `ASSERT`.
+    fn visit_create_constraint(
+        &mut self,
+        name: String,
+        if_not_exists: bool,
+        variable: Variable,
+        label: Label,
+        properties: Vec<Property>,
+        kind: ConstraintKind,
+    ) -> Self::Output {
+        let mut query_string = "CREATE CONSTRAINT ".to_string();
+        query_string += &escape_name(&name);
+        if if_not_exists {
+            query_string += " IF NOT EXISTS";
+        }
+
+        let (for_keyword, require_keyword) = self.dialect.constraint_keywords();
+        query_string += " ";
+        query_string += for_keyword;
+        query_string += " ";
+        query_string += &self.schema_pattern(&variable, &label);
+
+        query_string += " ";
+        query_string += require_keyword;
+        query_string += " ";
+        query_string += &self.schema_properties(&variable, properties);
+        query_string += " ";
+        query_string += &kind.to_string();
+
+        query_string
+    }
+
+    /// DropConstraint: `DROP CONSTRAINT` name (`IF EXISTS`)?
+    fn visit_drop_constraint(&mut self, name: String, if_exists: bool) -> Self::Output {
+        let mut query_string = "DROP CONSTRAINT ".to_string();
+        query_string += &escape_name(&name);
+        if if_exists {
+            query_string += " IF EXISTS";
+        }
+        query_string
+    }
+
+    /// ShowSchema: `SHOW` (`INDEXES`|`CONSTRAINTS`)
+    fn visit_show_schema(&mut self, show_constraints: bool) -> Self::Output {
+        if show_constraints {
+            "SHOW CONSTRAINTS".to_string()
+        } else {
+            "SHOW INDEXES".to_string()
+        }
+    }
+
     /// Pattern: PatternPart+
     fn visit_pattern(&mut self, pattern_parts: Vec<Box<CypherNode>>) -> Self::Output {
         let mut query_string = String::new();
@@ -585,6 +976,8 @@ impl ConvertVisitor for TransformVisitor {
     fn visit_pattern_part(
         &mut self,
         var: Option<Variable>,
+        selector: Option<PathSelector>,
+        path_mode: Option<PathMode>,
         pattern_element: Box<CypherNode>,
     ) -> Self::Output {
         let mut query_string = String::new();
@@ -594,6 +987,18 @@ impl ConvertVisitor for TransformVisitor {
             query_string += "=";
         }
 
+        // PathSelector
+        if let Some(selector) = selector.filter(|_| self.dialect.path_selectors()) {
+            query_string += &selector.to_string();
+            query_string += " ";
+        }
+
+        // PathMode, GQL only.
+        if let Some(path_mode) = path_mode.filter(|_| self.dialect.path_modes()) {
+            query_string += &path_mode.to_string();
+            query_string += " ";
+        }
+
         query_string += &self.visit(pattern_element);
 
         query_string
@@ -637,6 +1042,38 @@ impl ConvertVisitor for TransformVisitor {
         query_string
     }
 
+    /// QuantifiedPathPattern
+    ///
+    /// `(` PatternElement (`WHERE` Expression)? `)` Quantifier?
+    fn visit_quantified_path_pattern(
+        &mut self,
+        pattern_element: Box<CypherNode>,
+        where_clause: Option<Expr>,
+        quantifier: Option<(Option<i32>, Option<i32>)>,
+    ) -> Self::Output {
+        let mut query_string = "(".to_string();
+        query_string += &self.visit(pattern_element);
+
+        if let Some(where_clause) = where_clause {
+            query_string += " WHERE ";
+            query_string += &self.expr(&where_clause);
+        }
+        query_string += ")";
+
+        // no quantifier for a grouped sub-pattern.
+        query_string += &match quantifier {
+            None => String::new(),
+            Some((None, None)) => "*".to_string(),
+            Some((Some(1), None)) => "+".to_string(),
+            Some((Some(lower), None)) => format!("{{{},}}", lower),
+            Some((None, Some(upper))) => format!("{{,{}}}", upper),
+            Some((Some(lower), Some(upper))) if lower == upper => format!("{{{}}}", lower),
+            Some((Some(lower), Some(upper))) => format!("{{{},{}}}", lower, upper),
+        };
+
+        query_string
+    }
+
     /// ### NodePattern
     ///
     /// (Varibale? (:label)*, Properties)
@@ -689,15 +1126,31 @@ impl ConvertVisitor for TransformVisitor {
             .into_iter()
             .map(|label| label.get_name())
             .collect::<Vec<_>>()
-            .join("|:");
+            .join(self.dialect.relationship_types_separator());
 
-        if labels_string.is_empty() {
+        if !labels_string.is_empty() {
             query_string += ":";
             query_string += &labels_string;
         }
 
-        // *RangeStart..RangeEnd
-        if is_range {
+        // *RangeStart..RangeEnd, a `{m,n}` quantifier after the edge in GQL.
+        let quantifier = if is_range && self.dialect == Dialect::Gql {
+            let (range_start, range_end) = range;
+            Some(match range_end {
+                Some((true, range_end)) => format!(
+                    "{{{},{}}}",
+                    range_start.unwrap_or(1),
+                    range_end.map(|end| end.to_string()).unwrap_or_default()
+                ),
+                _ => match range_start {
+                    Some(range_start) => format!("{{{}}}", range_start),
+                    None => "{1,}".to_string(),
+                },
+            })
+        } else {
+            None
+        };
+        if is_range && quantifier.is_none() {
             query_string += "*";
             let (range_start, range_end) = range;
             if let Some(range_start) = range_start {
@@ -723,6 +1176,9 @@ impl ConvertVisitor for TransformVisitor {
         }
 
         query_string += &direction.right_string();
+        if let Some(quantifier) = quantifier {
+            query_string += &quantifier;
+        }
         query_string
     }
 }
@@ -0,0 +1,137 @@
+use rand::distributions::{Alphanumeric, Uniform};
+use rand_regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{DataType, FieldValue, RandomGenerator};
+
+/// Null probability of nullable properties without a `null_ratio`.
+const DEFAULT_NULL_RATIO: f64 = 1.0 / 12.0;
+
+/// Optional hints on the values of a property.
+///
+/// Used by the data populator and the literals compared with the property,
+/// so generated predicates hit the stored data.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValueDomain {
+    // inclusive bounds of numeric, date and datetime values, given together.
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    // closed set of values, the other hints are ignored.
+    pub values: Vec<Value>,
+    // regex of string values.
+    pub pattern: Option<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    // probability of null for nullable properties.
+    pub null_ratio: Option<f64>,
+    // known values picked half of the time.
+    pub samples: Vec<Value>,
+}
+
+impl ValueDomain {
+    pub fn null_ratio(&self) -> f64 {
+        self.null_ratio.unwrap_or(DEFAULT_NULL_RATIO)
+    }
+
+    /// Random value of the domain, `None` if it gives no hint for the type.
+    pub fn random_value(
+        &self,
+        d_type: DataType,
+        random: &mut RandomGenerator,
+    ) -> Option<FieldValue> {
+        if !self.values.is_empty() {
+            let value = &self.values[random.under(self.values.len() as _) as usize];
+            return Some(FieldValue::from_json(value, d_type));
+        }
+        if !self.samples.is_empty() && random.bool() {
+            let value = &self.samples[random.under(self.samples.len() as _) as usize];
+            return Some(FieldValue::from_json(value, d_type));
+        }
+
+        match d_type {
+            DataType::Int32 | DataType::Int64 | DataType::Date | DataType::Datetime => {
+                let (min, max) = self.bounds()?;
+                let value = random.sample(Uniform::new_inclusive(min as i64, max as i64));
+                Some(match d_type {
+                    DataType::Int32 => FieldValue::Int32(value as _),
+                    DataType::Int64 => FieldValue::Int64(value),
+                    DataType::Date => FieldValue::Date(value),
+                    _ => FieldValue::Datetime(value),
+                })
+            }
+            DataType::Float | DataType::Double => {
+                let (min, max) = self.bounds()?;
+                let value = min + random.probability() * (max - min);
+                Some(match d_type {
+                    DataType::Float => FieldValue::Float(value as _),
+                    _ => FieldValue::Double(value),
+                })
+            }
+            DataType::String => self.random_string(random).map(FieldValue::String),
+            DataType::Null | DataType::Bool => None,
+        }
+    }
+
+    fn bounds(&self) -> Option<(f64, f64)> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => Some((min.min(max), max.max(min))),
+            _ => None,
+        }
+    }
+
+    fn random_string(&self, random: &mut RandomGenerator) -> Option<String> {
+        let max_length = self.max_length.unwrap_or(8);
+        if let Some(pattern) = &self.pattern {
+            // an invalid pattern falls back to the length hints.
+            if let Ok(regex) = Regex::compile(pattern, max_length as u32) {
+                let value: String = random.sample(&regex);
+                return Some(value.chars().take(max_length).collect());
+            }
+        }
+        if self.min_length.is_none() && self.max_length.is_none() {
+            return None;
+        }
+
+        let min_length = self.min_length.unwrap_or(1).min(max_length);
+        let length = random.range(min_length as _, max_length as i32 + 1) as usize;
+        Some(
+            (0..length)
+                .map(|_| random.sample(Alphanumeric) as char)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueDomain;
+    use crate::common::{DataType, FieldValue, RandomGenerator};
+
+    #[test]
+    fn test_value_domain() {
+        let mut random = RandomGenerator::new();
+        let domain = serde_json::from_str::<ValueDomain>(
+            r#"{"min": 18, "max": 60, "pattern": "[a-z]{3}-[0-9]{2}"}"#,
+        )
+        .unwrap();
+        for _ in 0..20 {
+            match domain.random_value(DataType::Int32, &mut random) {
+                Some(FieldValue::Int32(value)) => assert!((18..=60).contains(&value)),
+                value => panic!("unexpected value {:?}", value),
+            }
+            match domain.random_value(DataType::String, &mut random) {
+                Some(FieldValue::String(value)) => assert_eq!(value.len(), 6),
+                value => panic!("unexpected value {:?}", value),
+            }
+        }
+        assert!(domain.random_value(DataType::Bool, &mut random).is_none());
+
+        let domain = serde_json::from_str::<ValueDomain>(r#"{"values": ["a", "b"]}"#).unwrap();
+        for _ in 0..20 {
+            let value = domain.random_value(DataType::String, &mut random).unwrap();
+            assert!(["'a'", "'b'"].contains(&value.to_string().as_str()));
+        }
+    }
+}
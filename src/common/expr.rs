@@ -1,12 +1,35 @@
 use std::fmt::Display;
 
-use super::{DataKind, Property, Variable};
+use serde::{Deserialize, Serialize};
+
+use super::{DataKind, FieldValue, Property, Variable};
 use crate::{
     ast::{CypherNode, TransformVisitor},
+    config::Dialect,
     meta::Label,
 };
 
-#[derive(Debug, Default, Clone)]
+/// An enum with `VARIANTS`, the names of all its variants.
+macro_rules! enum_variants {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $( $(#[$variant_meta:meta])* $variant:ident $(( $($field:ty),* ))?, )*
+        }
+    ) => {
+        $(#[$meta])*
+        pub enum $name {
+            $( $(#[$variant_meta])* $variant $(( $($field),* ))?, )*
+        }
+
+        impl $name {
+            /// Names of all the variants.
+            pub const VARIANTS: &'static [&'static str] = &[$(stringify!($variant),)*];
+        }
+    };
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct NameSpace {
     name_space: String,
 }
@@ -18,39 +41,47 @@ impl NameSpace {
         }
     }
 
+    pub fn with_name(name_space: String) -> Self {
+        NameSpace { name_space }
+    }
+
     pub fn get_name(&self) -> String {
         self.name_space.clone()
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum ExprKind {
-    /// A binary operator expression (e.g., `a+2`).
-    BinOp(BinOpKind, Box<Expr>, Box<Expr>),
-    /// A unary operator expression (e.g., `-x`).
-    UnOp(UnOpKind, Box<Expr>),
-    /// A comparison chain (e.g. `a+b>1+c=d`).
-    Cmp(Box<Expr>, Vec<(CmpKind, Box<Expr>)>),
-    /// A literal.
-    Lit(Literal),
-    /// A Variable,
-    Variable(Variable),
-    /// A predicate variable,
-    PredicateVariable(Variable),
-    /// A case expression (e.g. ...),
-    Case(Option<Box<Expr>>, Vec<CaseAlternative>, Option<Box<Expr>>),
-    /// A property access (e.g. `a.age`),
-    Property(Box<Expr>, Property),
-    /// Vertex Label.
-    Label(Box<Expr>, Label),
-    /// A function invocation (e.g. `sin(a)`),
-    Invocation(Box<Expr>, bool, Vec<Expr>),
-    /// FilterExpression: (e.g. a in [1,2] where a>1).
-    FilterExpression(Variable, Box<Expr>, Option<Box<Expr>>),
-    /// A predicate function,
-    PredicateFunction(PredicateFunctionKind, Box<Expr>),
-    /// A Subquery expression,
-    SubQuery(SubQueryKind, Box<CypherNode>, Option<Box<Expr>>),
+enum_variants! {
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub enum ExprKind {
+        /// A binary operator expression (e.g., `a+2`).
+        BinOp(BinOpKind, Box<Expr>, Box<Expr>),
+        /// A unary operator expression (e.g., `-x`).
+        UnOp(UnOpKind, Box<Expr>),
+        /// A comparison chain (e.g. `a+b>1+c=d`).
+        Cmp(Box<Expr>, Vec<(CmpKind, Box<Expr>)>),
+        /// A literal.
+        Lit(Literal),
+        /// A Variable,
+        Variable(Variable),
+        /// A predicate variable,
+        PredicateVariable(Variable),
+        /// A case expression (e.g. ...),
+        Case(Option<Box<Expr>>, Vec<CaseAlternative>, Option<Box<Expr>>),
+        /// A property access (e.g. `a.age`),
+        Property(Box<Expr>, Property),
+        /// Vertex Label.
+        Label(Box<Expr>, Label),
+        /// A function invocation (e.g. `sin(a)`),
+        Invocation(Box<Expr>, bool, Vec<Expr>),
+        /// FilterExpression: (e.g. a in [1,2] where a>1).
+        FilterExpression(Variable, Box<Expr>, Option<Box<Expr>>),
+        /// A predicate function,
+        PredicateFunction(PredicateFunctionKind, Box<Expr>),
+        /// A Subquery expression,
+        SubQuery(SubQueryKind, Box<CypherNode>, Option<Box<Expr>>),
+        /// A type predicate (e.g. `a IS NOT :: INTEGER`),
+        TypePredicate(Box<Expr>, bool, ValueType),
+    }
 }
 
 impl ExprKind {
@@ -92,78 +123,86 @@ impl ExprKind {
             ExprKind::Label(_, _) => DataKind::Vertex,
             ExprKind::Invocation(_, _, _) => DataKind::Function,
             ExprKind::PredicateFunction(_, _) => DataKind::Boolean,
+            ExprKind::SubQuery(SubQueryKind::Count, _, _) => DataKind::Numerical,
             ExprKind::SubQuery(_, _, _) => DataKind::Query,
+            ExprKind::TypePredicate(_, _, _) => DataKind::Boolean,
             ExprKind::FilterExpression(_, _, _) => DataKind::Boolean,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum BinOpKind {
-    /// The `OR` operator (logical or).
-    Or,
-    /// The `XOR` operator (logical xor).
-    Xor,
-    /// The `AND` operator (logical and).
-    And,
-    /// The `+` operator (addition).
-    Add,
-    /// The `-` operator (subtraction).
-    Sub,
-    /// The `*` operator (multiplication).
-    Mul,
-    /// The `/` operator (division).
-    Div,
-    /// The `%` operator (modulo).
-    Mod,
-    /// The `^` operator (power).
-    Pow,
-    /// The `[_]` operator (index).
-    Index,
-    /// The `IN` operator.
-    In,
-    /// The `CONTAINS` operator
-    Contains,
-    /// The `STARTS WITH` operator
-    StartsWith,
-    /// The `ENDS WITH` operator
-    EndsWith,
-    /// The `|` Operator
-    Pipe,
-    /// The '...' Operator
-    Range,
-}
-
-#[derive(Debug, Clone, Copy)]
-pub enum UnOpKind {
-    /// The `+` operator (positive).
-    Pos,
-    /// The `-` operator (negative).
-    Neg,
-    /// The `NOT` operator (logical not).
-    Not,
-    /// The `IS NULL` operator.
-    Null,
-    /// The `IS NOT NULL` operator.
-    NotNull,
-    //// The `()` operator.
-    Parentheses,
-}
-
-#[derive(Debug, Copy, Clone)]
-pub enum CmpKind {
-    /// The `<>` operator.
-    Ne,
-    /// The `=` operator.
-    Eq,
-    /// The `<` operator.
-    Lt,
-    /// The `>` operator.
-    Gt,
-    /// The `<=` operator.
-    Le,
-    /// The `>=` operator.
-    Ge,
+enum_variants! {
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub enum BinOpKind {
+        /// The `OR` operator (logical or).
+        Or,
+        /// The `XOR` operator (logical xor).
+        Xor,
+        /// The `AND` operator (logical and).
+        And,
+        /// The `+` operator (addition).
+        Add,
+        /// The `-` operator (subtraction).
+        Sub,
+        /// The `*` operator (multiplication).
+        Mul,
+        /// The `/` operator (division).
+        Div,
+        /// The `%` operator (modulo).
+        Mod,
+        /// The `^` operator (power).
+        Pow,
+        /// The `[_]` operator (index).
+        Index,
+        /// The `IN` operator.
+        In,
+        /// The `CONTAINS` operator
+        Contains,
+        /// The `STARTS WITH` operator
+        StartsWith,
+        /// The `ENDS WITH` operator
+        EndsWith,
+        /// The `|` Operator
+        Pipe,
+        /// The '...' Operator
+        Range,
+    }
+}
+
+enum_variants! {
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub enum UnOpKind {
+        /// The `+` operator (positive).
+        Pos,
+        /// The `-` operator (negative).
+        Neg,
+        /// The `NOT` operator (logical not).
+        Not,
+        /// The `IS NULL` operator.
+        Null,
+        /// The `IS NOT NULL` operator.
+        NotNull,
+        //// The `()` operator.
+        Parentheses,
+    }
+}
+
+enum_variants! {
+    #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+    pub enum CmpKind {
+        /// The `<>` operator.
+        Ne,
+        /// The `=` operator.
+        Eq,
+        /// The `<` operator.
+        Lt,
+        /// The `>` operator.
+        Gt,
+        /// The `<=` operator.
+        Le,
+        /// The `>=` operator.
+        Ge,
+    }
 }
 
 impl Display for CmpKind {
@@ -179,16 +218,18 @@ impl Display for CmpKind {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum PredicateFunctionKind {
-    /// The `ALL` function.
-    All,
-    /// The `ANY` function.
-    Any,
-    /// The `NONE` function.
-    None,
-    /// The `SINGLE` function.
-    Single,
+enum_variants! {
+    #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+    pub enum PredicateFunctionKind {
+        /// The `ALL` function.
+        All,
+        /// The `ANY` function.
+        Any,
+        /// The `NONE` function.
+        None,
+        /// The `SINGLE` function.
+        Single,
+    }
 }
 
 impl Display for PredicateFunctionKind {
@@ -202,27 +243,79 @@ impl Display for PredicateFunctionKind {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum SubQueryKind {
     /// The `Exists { Query }` function
     Exists,
+    /// The `COUNT { Query }` function
+    Count,
     /// RelationShipsPattern:
     RelationShipsPattern,
     /// PredicatePattern: (Variable=)? RelationShipsPattern Where?
     PredicatePattern,
 }
 
+/// Value types of a type predicate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValueType {
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Date,
+    List,
+    Map,
+    Node,
+    Relationship,
+    Path,
+    Any,
+}
+
+impl ValueType {
+    pub const ALL: [ValueType; 11] = [
+        ValueType::Boolean,
+        ValueType::Integer,
+        ValueType::Float,
+        ValueType::String,
+        ValueType::Date,
+        ValueType::List,
+        ValueType::Map,
+        ValueType::Node,
+        ValueType::Relationship,
+        ValueType::Path,
+        ValueType::Any,
+    ];
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ValueType::Boolean => f.write_str("BOOLEAN"),
+            ValueType::Integer => f.write_str("INTEGER"),
+            ValueType::Float => f.write_str("FLOAT"),
+            ValueType::String => f.write_str("STRING"),
+            ValueType::Date => f.write_str("DATE"),
+            ValueType::List => f.write_str("LIST<ANY>"),
+            ValueType::Map => f.write_str("MAP"),
+            ValueType::Node => f.write_str("NODE"),
+            ValueType::Relationship => f.write_str("RELATIONSHIP"),
+            ValueType::Path => f.write_str("PATH"),
+            ValueType::Any => f.write_str("ANY"),
+        }
+    }
+}
+
 /// Case Alternative.
 ///
 /// # Synopsis
 /// > **WHEN** *Expression* **THEN** *Expression*
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CaseAlternative {
     pub condition: Box<Expr>,
     pub value: Box<Expr>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RelationshipDirection {
     // <- [] -
     Left,
@@ -254,6 +347,90 @@ impl RelationshipDirection {
     }
 }
 
+/// GQL path modes, restricting the repeated elements of a path.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathMode {
+    /// `WALK`: no restriction.
+    Walk,
+    /// `TRAIL`: no repeated edges.
+    Trail,
+    /// `ACYCLIC`: no repeated nodes.
+    Acyclic,
+    /// `SIMPLE`: no repeated nodes, except the first and the last node.
+    Simple,
+}
+
+impl PathMode {
+    pub const ALL: [PathMode; 4] = [
+        PathMode::Walk,
+        PathMode::Trail,
+        PathMode::Acyclic,
+        PathMode::Simple,
+    ];
+}
+
+impl Display for PathMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            PathMode::Walk => f.write_str("WALK"),
+            PathMode::Trail => f.write_str("TRAIL"),
+            PathMode::Acyclic => f.write_str("ACYCLIC"),
+            PathMode::Simple => f.write_str("SIMPLE"),
+        }
+    }
+}
+
+/// Path selectors, choosing the paths matched by a path pattern.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathSelector {
+    /// `ALL`
+    All,
+    /// `ANY` k?
+    Any(Option<u32>),
+    /// `ALL SHORTEST`
+    AllShortest,
+    /// `ANY SHORTEST`
+    AnyShortest,
+    /// `SHORTEST` k
+    Shortest(u32),
+    /// `SHORTEST` k `GROUPS`
+    ShortestGroups(u32),
+}
+
+impl Display for PathSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            PathSelector::All => f.write_str("ALL"),
+            PathSelector::Any(None) => f.write_str("ANY"),
+            PathSelector::Any(Some(k)) => f.write_fmt(format_args!("ANY {}", k)),
+            PathSelector::AllShortest => f.write_str("ALL SHORTEST"),
+            PathSelector::AnyShortest => f.write_str("ANY SHORTEST"),
+            PathSelector::Shortest(k) => f.write_fmt(format_args!("SHORTEST {}", k)),
+            PathSelector::ShortestGroups(k) => f.write_fmt(format_args!("SHORTEST {} GROUPS", k)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConstraintKind {
+    /// `IS UNIQUE`
+    Unique,
+    /// `IS NOT NULL`
+    NotNull,
+    /// `IS NODE KEY`
+    NodeKey,
+}
+
+impl Display for ConstraintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ConstraintKind::Unique => f.write_str("IS UNIQUE"),
+            ConstraintKind::NotNull => f.write_str("IS NOT NULL"),
+            ConstraintKind::NodeKey => f.write_str("IS NODE KEY"),
+        }
+    }
+}
+
 /// Literals
 ///
 /// # Synopsis
@@ -262,7 +439,7 @@ impl RelationshipDirection {
 /// > - *StringLiteral*
 /// > - *BooleanLiteral* := **TRUE** | **FALSE**
 /// > - *NullLiteral* := **NULL**
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Literal {
     Double(f64),
     Integer(u64),
@@ -274,24 +451,47 @@ pub enum Literal {
     Null,
 }
 
+impl Literal {
+    /// Render the literal in the syntax of a dialect, lists and maps hold expressions.
+    pub fn display(&self, dialect: Dialect) -> LiteralDisplay<'_> {
+        LiteralDisplay {
+            literal: self,
+            dialect,
+        }
+    }
+}
+
 impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Literal::Double(value) => f.write_fmt(format_args!("{}", value)),
+        self.display(Dialect::default()).fmt(f)
+    }
+}
+
+/// A literal rendered in the syntax of a dialect, see `Literal::display`.
+pub struct LiteralDisplay<'a> {
+    literal: &'a Literal,
+    dialect: Dialect,
+}
+
+impl Display for LiteralDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.literal {
+            // `{:?}` keeps the fraction of integral values, `3.0` and not `3`.
+            Literal::Double(value) => f.write_fmt(format_args!("{:?}", value)),
             Literal::Integer(value) => f.write_fmt(format_args!("{}", value)),
             Literal::String(value) => f.write_fmt(format_args!("{}", value)),
             Literal::Boolean(value) => f.write_str(if *value { "TRUE" } else { "FALSE" }),
             Literal::List(list) => {
                 let items = list
                     .iter()
-                    .map(|item| format!("{}", item))
+                    .map(|item| format!("{}", item.display(self.dialect)))
                     .collect::<Vec<String>>();
                 f.write_fmt(format_args!("[{}]", &items.join(", ")))
             }
             Literal::Map(entries) => {
                 let items = entries
                     .iter()
-                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .map(|(k, v)| format!("{}: {}", k, v.display(self.dialect)))
                     .collect::<Vec<String>>();
                 f.write_fmt(format_args!("{{{}}}", items.join(", ")))
             }
@@ -301,7 +501,7 @@ impl Display for Literal {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Expr {
     pub kind: ExprKind,
 }
@@ -312,50 +512,125 @@ impl From<ExprKind> for Expr {
     }
 }
 
+impl From<FieldValue> for Expr {
+    /// Literal of a property value, negative numbers are negated literals.
+    fn from(value: FieldValue) -> Self {
+        let (negative, literal) = match value {
+            FieldValue::Null => (false, Literal::Null),
+            FieldValue::Boolean(b) => (false, Literal::Boolean(b)),
+            FieldValue::Int32(i) => (i < 0, Literal::Integer(i.unsigned_abs() as _)),
+            FieldValue::Int64(i) => (i < 0, Literal::Integer(i.unsigned_abs())),
+            FieldValue::Date(_) | FieldValue::Datetime(_) => {
+                // `date('1970-01-01')`
+                let (function, text) = value.temporal().unwrap_or_default();
+                return Expr::from(ExprKind::Invocation(
+                    Box::new(Expr::from(ExprKind::Variable(Variable::new(
+                        function.to_string(),
+                    )))),
+                    false,
+                    vec![Expr::from(ExprKind::Lit(Literal::String(format!(
+                        "'{}'",
+                        text
+                    ))))],
+                ));
+            }
+            FieldValue::Float(f) => (f < 0.0, Literal::Double(f.abs() as _)),
+            FieldValue::Double(d) => (d < 0.0, Literal::Double(d.abs())),
+            // Literal::String holds the token text.
+            string => (false, Literal::String(string.to_string())),
+        };
+        let expr = Expr::from(ExprKind::Lit(literal));
+        if negative {
+            Expr::from(ExprKind::UnOp(UnOpKind::Neg, Box::new(expr)))
+        } else {
+            expr
+        }
+    }
+}
+
+impl Expr {
+    /// Render the expression in the syntax of a dialect.
+    pub fn display(&self, dialect: Dialect) -> ExprDisplay<'_> {
+        ExprDisplay {
+            expr: self,
+            dialect,
+        }
+    }
+}
+
 impl Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.kind {
-            ExprKind::BinOp(kind, lhs, rhs) => match kind {
-                BinOpKind::Or => f.write_fmt(format_args!("{} OR {}", lhs, rhs)),
-                BinOpKind::Xor => f.write_fmt(format_args!("{} XOR {}", lhs, rhs)),
-                BinOpKind::And => f.write_fmt(format_args!("{} AND {}", lhs, rhs)),
-                BinOpKind::Add => f.write_fmt(format_args!("{} + {}", lhs, rhs)),
-                BinOpKind::Sub => f.write_fmt(format_args!("{} - {}", lhs, rhs)),
-                BinOpKind::Mul => f.write_fmt(format_args!("{} * {}", lhs, rhs)),
-                BinOpKind::Div => f.write_fmt(format_args!("{} / {}", lhs, rhs)),
-                BinOpKind::Mod => f.write_fmt(format_args!("{} % {}", lhs, rhs)),
-                BinOpKind::Pow => f.write_fmt(format_args!("{} ^ {}", lhs, rhs)),
-                BinOpKind::Index => f.write_fmt(format_args!("{}[{}]", lhs, rhs)),
-                BinOpKind::In => f.write_fmt(format_args!("{} IN {}", lhs, rhs)),
-                BinOpKind::Contains => f.write_fmt(format_args!("{} CONTAINS {}", lhs, rhs)),
-                BinOpKind::StartsWith => f.write_fmt(format_args!("{} STARTS WITH {}", lhs, rhs)),
-                BinOpKind::EndsWith => f.write_fmt(format_args!("{} ENDS WITH {}", lhs, rhs)),
-                BinOpKind::Pipe => f.write_fmt(format_args!("{} | {}", lhs, rhs)),
-                BinOpKind::Range => f.write_fmt(format_args!("{}..{}", lhs, rhs)),
-            },
-            ExprKind::UnOp(kind, expr) => match kind {
-                UnOpKind::Pos => f.write_fmt(format_args!("+{}", expr)),
-                UnOpKind::Neg => f.write_fmt(format_args!("-{}", expr)),
-                UnOpKind::Not => f.write_fmt(format_args!("NOT {}", expr)),
-                UnOpKind::Null => f.write_fmt(format_args!("{} IS NULL", expr)),
-                UnOpKind::NotNull => f.write_fmt(format_args!("{} IS NOT NULL", expr)),
-                UnOpKind::Parentheses => f.write_fmt(format_args!("({})", expr)),
-            },
+        self.display(Dialect::default()).fmt(f)
+    }
+}
+
+/// An expression rendered in the syntax of a dialect, see `Expr::display`.
+pub struct ExprDisplay<'a> {
+    expr: &'a Expr,
+    dialect: Dialect,
+}
+
+impl Display for ExprDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dialect = self.dialect;
+        let d = |expr: &Expr| expr.display(dialect).to_string();
+        match &self.expr.kind {
+            ExprKind::BinOp(kind, lhs, rhs) => {
+                let (lhs, rhs) = (d(lhs), d(rhs));
+                match kind {
+                    BinOpKind::Or => f.write_fmt(format_args!("{} OR {}", lhs, rhs)),
+                    BinOpKind::Xor => f.write_fmt(format_args!("{} XOR {}", lhs, rhs)),
+                    BinOpKind::And => f.write_fmt(format_args!("{} AND {}", lhs, rhs)),
+                    BinOpKind::Add => f.write_fmt(format_args!("{} + {}", lhs, rhs)),
+                    BinOpKind::Sub => f.write_fmt(format_args!("{} - {}", lhs, rhs)),
+                    BinOpKind::Mul => f.write_fmt(format_args!("{} * {}", lhs, rhs)),
+                    BinOpKind::Div => f.write_fmt(format_args!("{} / {}", lhs, rhs)),
+                    // GQL has numeric functions instead of `%` and `^`.
+                    BinOpKind::Mod if dialect == Dialect::Gql => {
+                        f.write_fmt(format_args!("MOD({}, {})", lhs, rhs))
+                    }
+                    BinOpKind::Pow if dialect == Dialect::Gql => {
+                        f.write_fmt(format_args!("POWER({}, {})", lhs, rhs))
+                    }
+                    BinOpKind::Mod => f.write_fmt(format_args!("{} % {}", lhs, rhs)),
+                    BinOpKind::Pow => f.write_fmt(format_args!("{} ^ {}", lhs, rhs)),
+                    BinOpKind::Index => f.write_fmt(format_args!("{}[{}]", lhs, rhs)),
+                    BinOpKind::In => f.write_fmt(format_args!("{} IN {}", lhs, rhs)),
+                    BinOpKind::Contains => f.write_fmt(format_args!("{} CONTAINS {}", lhs, rhs)),
+                    BinOpKind::StartsWith => {
+                        f.write_fmt(format_args!("{} STARTS WITH {}", lhs, rhs))
+                    }
+                    BinOpKind::EndsWith => f.write_fmt(format_args!("{} ENDS WITH {}", lhs, rhs)),
+                    BinOpKind::Pipe => f.write_fmt(format_args!("{} | {}", lhs, rhs)),
+                    BinOpKind::Range => f.write_fmt(format_args!("{}..{}", lhs, rhs)),
+                }
+            }
+            ExprKind::UnOp(kind, expr) => {
+                let expr = d(expr);
+                match kind {
+                    UnOpKind::Pos => f.write_fmt(format_args!("+{}", expr)),
+                    UnOpKind::Neg => f.write_fmt(format_args!("-{}", expr)),
+                    UnOpKind::Not => f.write_fmt(format_args!("NOT {}", expr)),
+                    UnOpKind::Null => f.write_fmt(format_args!("{} IS NULL", expr)),
+                    UnOpKind::NotNull => f.write_fmt(format_args!("{} IS NOT NULL", expr)),
+                    UnOpKind::Parentheses => f.write_fmt(format_args!("({})", expr)),
+                }
+            }
             ExprKind::Cmp(cmp_expr, tails) => {
                 let tail_str: String = tails
                     .iter()
-                    .map(|(kind, expr)| format!("{} {}", kind, expr))
+                    .map(|(kind, expr)| format!("{} {}", kind, d(expr)))
                     .collect::<Vec<String>>()
                     .join(" ");
-                f.write_fmt(format_args!("{} {}", cmp_expr, &tail_str))
+                f.write_fmt(format_args!("{} {}", d(cmp_expr), &tail_str))
             }
-            ExprKind::Lit(lit) => f.write_fmt(format_args!("{}", lit)),
+            ExprKind::Lit(lit) => f.write_fmt(format_args!("{}", lit.display(dialect))),
             ExprKind::Variable(name) | ExprKind::PredicateVariable(name) => {
                 f.write_str(&name.get_name())
             }
             ExprKind::Case(case_expression, case_alternatives, else_expression) => {
                 let head_str = if let Some(expr) = case_expression {
-                    format!("CASE {}", expr)
+                    format!("CASE {}", d(expr))
                 } else {
                     "CASE".to_string()
                 };
@@ -364,13 +639,14 @@ impl Display for Expr {
                     .map(|case_alternative| {
                         format!(
                             " WHEN {} THEN {}",
-                            case_alternative.condition, case_alternative.value
+                            d(&case_alternative.condition),
+                            d(&case_alternative.value)
                         )
                     })
                     .collect::<Vec<String>>()
                     .join("");
                 let tail_str = if let Some(expr) = else_expression {
-                    format!(" ELSE {}", expr)
+                    format!(" ELSE {}", d(expr))
                 } else {
                     String::new()
                 };
@@ -379,20 +655,21 @@ impl Display for Expr {
                     &head_str, &middle_str, &tail_str
                 ))
             }
-            ExprKind::Property(expr, prop) => f.write_fmt(format_args!("{}.{}", expr, prop)),
+            ExprKind::Property(expr, prop) => f.write_fmt(format_args!("{}.{}", d(expr), prop)),
             ExprKind::Invocation(expr, _, params) => {
-                let params_str: String = params
-                    .iter()
-                    .map(|param| format!("{}", param))
-                    .collect::<Vec<String>>()
-                    .join(", ");
-                f.write_fmt(format_args!("{}({})", expr, &params_str))
+                let params_str: String = params.iter().map(d).collect::<Vec<String>>().join(", ");
+                let name = d(expr);
+                f.write_fmt(format_args!(
+                    "{}({})",
+                    dialect.function_name(&name),
+                    &params_str
+                ))
             }
             ExprKind::PredicateFunction(kind, expr) => {
-                f.write_fmt(format_args!("{}({})", kind, expr))
+                f.write_fmt(format_args!("{}({})", kind, d(expr)))
             }
             ExprKind::SubQuery(kind, expr, where_clause) => {
-                let mut transformer = TransformVisitor::new();
+                let mut transformer = TransformVisitor::with_dialect(dialect);
                 let result = transformer.exec(expr.clone());
 
                 match kind {
@@ -400,27 +677,50 @@ impl Display for Expr {
                         // `Exists {Query}`
                         f.write_fmt(format_args!("EXISTS {{{}}}", result))
                     }
+                    SubQueryKind::Count => f.write_fmt(format_args!("COUNT {{{}}}", result)),
                     SubQueryKind::RelationShipsPattern => {
-                        // RelationShipsPattern
-                        f.write_fmt(format_args!("{}", result))
+                        // RelationShipsPattern used as a predicate.
+                        f.write_str(&dialect.pattern_predicate(&result))
                     }
                     SubQueryKind::PredicatePattern => {
                         if let Some(where_clause) = where_clause {
-                            f.write_fmt(format_args!("{} WHERE {}", result, where_clause))
+                            f.write_fmt(format_args!("{} WHERE {}", result, d(where_clause)))
                         } else {
                             f.write_fmt(format_args!("{}", result))
                         }
                     }
                 }
             }
-            ExprKind::Label(expr, label) => f.write_fmt(format_args!("{}:{}", expr, label)),
+            ExprKind::Label(_, _) => {
+                // `n:A:B` is parsed as nested labels of `n`.
+                let mut labels = vec![];
+                let mut expr = self.expr;
+                while let ExprKind::Label(inner, label) = &expr.kind {
+                    labels.push(label.get_name());
+                    expr = inner;
+                }
+                labels.reverse();
+                f.write_str(&dialect.label_predicate(&d(expr), &labels))
+            }
             ExprKind::FilterExpression(var, in_expr, where_expr) => {
                 if let Some(where_expr) = where_expr {
-                    f.write_fmt(format_args!("{} IN {} WHERE {}", var, in_expr, where_expr))
+                    f.write_fmt(format_args!(
+                        "{} IN {} WHERE {}",
+                        var,
+                        d(in_expr),
+                        d(where_expr)
+                    ))
                 } else {
-                    f.write_fmt(format_args!("{} IN {}", var, in_expr))
+                    f.write_fmt(format_args!("{} IN {}", var, d(in_expr)))
                 }
             }
+            ExprKind::TypePredicate(expr, is_not, value_type) => f.write_fmt(format_args!(
+                "{} IS {}{} {}",
+                d(expr),
+                if *is_not { "NOT " } else { "" },
+                dialect.type_predicate_keyword(),
+                dialect.type_name(*value_type)
+            )),
         }
     }
 }
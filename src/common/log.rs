@@ -1,6 +1,9 @@
 use std::cmp;
 
-use super::{Expr, FieldValue, NameSpace, Property, RelationshipDirection, Variable};
+use super::{
+    ConstraintKind, Expr, FieldValue, NameSpace, PathMode, PathSelector, Property,
+    RelationshipDirection, Variable,
+};
 use crate::{
     ast::{CypherNode, LogVisitor},
     meta::Label,
@@ -274,6 +277,28 @@ impl LogVisitor for Log {
         (call_nodes + 1, call_height)
     }
 
+    fn visit_call_subquery(
+        &mut self,
+        import_variables: Vec<Variable>,
+        query: Box<CypherNode>,
+        _in_transactions: (bool, Option<i32>),
+    ) -> Self::Output {
+        let (mut call_nodes, call_height) = self.visit(query);
+        call_nodes += import_variables.len() as u32;
+
+        (call_nodes + 1, call_height + 1)
+    }
+
+    fn visit_load_csv(
+        &mut self,
+        _with_headers: bool,
+        _file: String,
+        _variable: Variable,
+        _field_terminator: Option<String>,
+    ) -> Self::Output {
+        (1, 1)
+    }
+
     fn visit_create(&mut self, pattern: Box<CypherNode>) -> Self::Output {
         self.visit(pattern)
     }
@@ -291,6 +316,30 @@ impl LogVisitor for Log {
         (merge_nodes, merge_height)
     }
 
+    fn visit_foreach(
+        &mut self,
+        _variable: Variable,
+        _expression: Expr,
+        updating_clauses: Vec<Box<CypherNode>>,
+    ) -> Self::Output {
+        let (mut foreach_nodes, mut foreach_height) = (1, 0);
+        self.get_info(updating_clauses, &mut foreach_nodes, &mut foreach_height);
+
+        (foreach_nodes + 1, foreach_height + 1)
+    }
+
+    fn visit_conditional_update(
+        &mut self,
+        _variable: Variable,
+        _condition: Expr,
+        updating_clauses: Vec<Box<CypherNode>>,
+    ) -> Self::Output {
+        let (mut update_nodes, mut update_height) = (1, 0);
+        self.get_info(updating_clauses, &mut update_nodes, &mut update_height);
+
+        (update_nodes + 1, update_height + 1)
+    }
+
     fn visit_delete(&mut self, _is_detach: bool, expressions: Vec<Expr>) -> Self::Output {
         (expressions.len() as u32, 1)
     }
@@ -345,6 +394,46 @@ impl LogVisitor for Log {
         (sum_nodes as u32, 1)
     }
 
+    fn visit_schema_command(&mut self, command: Box<CypherNode>) -> Self::Output {
+        let (command_nodes, command_height) = self.visit(command);
+        (command_nodes + 1, command_height + 1)
+    }
+
+    fn visit_create_index(
+        &mut self,
+        _name: String,
+        _if_not_exists: bool,
+        _variable: Variable,
+        _label: Label,
+        properties: Vec<Property>,
+    ) -> Self::Output {
+        (properties.len() as u32, 1)
+    }
+
+    fn visit_drop_index(&mut self, _name: String, _if_exists: bool) -> Self::Output {
+        (1, 1)
+    }
+
+    fn visit_create_constraint(
+        &mut self,
+        _name: String,
+        _if_not_exists: bool,
+        _variable: Variable,
+        _label: Label,
+        properties: Vec<Property>,
+        _kind: ConstraintKind,
+    ) -> Self::Output {
+        (properties.len() as u32, 1)
+    }
+
+    fn visit_drop_constraint(&mut self, _name: String, _if_exists: bool) -> Self::Output {
+        (1, 1)
+    }
+
+    fn visit_show_schema(&mut self, _show_constraints: bool) -> Self::Output {
+        (1, 1)
+    }
+
     fn visit_pattern(&mut self, pattern_parts: Vec<Box<CypherNode>>) -> Self::Output {
         let (mut pattern_parts_nodes, mut pattern_parts_height) = (0, 0);
         self.get_info(
@@ -358,6 +447,8 @@ impl LogVisitor for Log {
     fn visit_pattern_part(
         &mut self,
         _var: Option<Variable>,
+        _selector: Option<PathSelector>,
+        _path_mode: Option<PathMode>,
         pattern_element: Box<CypherNode>,
     ) -> Self::Output {
         self.visit(pattern_element)
@@ -400,4 +491,17 @@ impl LogVisitor for Log {
     ) -> Self::Output {
         (1, 1)
     }
+
+    fn visit_quantified_path_pattern(
+        &mut self,
+        pattern_element: Box<CypherNode>,
+        where_clause: Option<Expr>,
+        _quantifier: Option<(Option<i32>, Option<i32>)>,
+    ) -> Self::Output {
+        let (mut nodes, height) = self.visit(pattern_element);
+        if where_clause.is_some() {
+            nodes += 1;
+        }
+        (nodes + 1, height + 1)
+    }
 }
@@ -1,4 +1,5 @@
 mod diagnostic;
+mod domain;
 mod expr;
 mod log;
 mod rand;
@@ -12,6 +13,7 @@ pub use self::rand::RandomGenerator;
 //     RelationshipDirection, SchemaName, Variable, VariableGenerator,
 // };
 pub use diagnostic::*;
+pub use domain::ValueDomain;
 pub use expr::*;
 pub use log::Log;
 pub use typedef::*;
@@ -22,12 +24,18 @@ pub mod constants {
     pub const DEFAULT_LOOP_LIMIT: i32 = 3;
     pub const DEFAULT_EXPRESSION_LIMIT: i32 = 5;
     pub const DEFAULT_QUERY_LIMIT: i32 = 15;
-    // pub const DEFAULT_RETRY_LIMIT: i32 = 20;
+    pub const DEFAULT_CSV_ROWS: i32 = 20;
+    pub const DEFAULT_INTROSPECT_SAMPLES: i32 = 100;
+    pub const DEFAULT_RETRY_LIMIT: i32 = 100;
+    pub const DEFAULT_ADAPT_INTERVAL: u32 = 100;
+    pub const DEFAULT_ADAPT_SAMPLES: u32 = 20;
+    pub const DEFAULT_REFERENCE_ROWS: usize = 10000;
+    pub const DEFAULT_MUTATION_ATTEMPTS: usize = 8;
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Log, VariableGenerator};
+    use super::{escape_name, DataKind, Log, VariableGenerator};
     use crate::ast::{CypherNode, LogVisitor};
 
     #[test]
@@ -35,6 +43,8 @@ mod tests {
         let mut test_logger = Log::new();
         let pattern_parts = vec![Box::new(CypherNode::PatternPart {
             var: None,
+            selector: None,
+            path_mode: None,
             pattern_element: Box::new(CypherNode::PatternElement {
                 parenthesis: false,
                 pattern_element: (
@@ -87,6 +97,59 @@ mod tests {
         println!("{:?}", var);
     }
 
+    #[test]
+    fn test_variable_sub_scope() {
+        let mut outer = VariableGenerator::new();
+        let imported = outer.new_variable();
+        let _hidden = outer.new_variable();
+
+        let mut inner = outer.sub_scope(std::slice::from_ref(&imported));
+        // only the imported variable is visible before the subquery defines its own.
+        for _ in 0..10 {
+            let var = inner.get_old_variable().unwrap();
+            assert_eq!(var.get_name(), imported.get_name());
+        }
+
+        // numbering continues, so inner names never shadow the outer ones.
+        let inner_var = inner.new_variable();
+        assert_eq!(inner_var.get_name(), "v2");
+
+        let exported = inner.new_variable();
+        assert_eq!(exported.get_name(), "v3");
+
+        // only the returned variable of the subquery becomes visible outside.
+        outer.merge_scope(&inner, std::slice::from_ref(&exported));
+        assert_eq!(outer.new_variable().get_name(), "v4");
+        for _ in 0..50 {
+            let var = outer.get_old_variable().unwrap();
+            assert_ne!(var.get_name(), inner_var.get_name());
+        }
+    }
+
+    #[test]
+    fn test_escape_name() {
+        assert_eq!(escape_name("Person"), "`Person`");
+        assert_eq!(escape_name("a`b"), "`a``b`");
+    }
+
+    #[test]
+    fn test_variable_nested_scope() {
+        let mut outer = VariableGenerator::new();
+        let visible = outer.new_kind_variable(DataKind::Vertex);
+
+        let mut inner = outer.nested_scope();
+        let loop_variable = inner.new_kind_variable(DataKind::Vertex);
+        assert_eq!(loop_variable.get_name(), "v1");
+
+        outer.merge_scope(&inner, &[]);
+        assert_eq!(outer.new_variable().get_name(), "v2");
+        for _ in 0..50 {
+            assert_ne!(outer.get_old_variable().unwrap().get_name(), "v1");
+            let var = outer.get_target_variable(DataKind::Vertex).unwrap();
+            assert_eq!(var.get_name(), visible.get_name());
+        }
+    }
+
     // #[test]
     // fn test_schema_name() {
     //     let mut random_gen = RandomGenerator::new();
@@ -1,8 +1,23 @@
-use rand::{distributions::Uniform, rngs::ThreadRng, thread_rng, Rng};
+use std::cell::RefCell;
+
+use rand::{
+    distributions::{Distribution, Uniform},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+
+thread_local! {
+    // shared by all the generators of a thread, a seed reproduces what follows it.
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Run `f` with the random number generator of the thread.
+fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
 
 #[derive(Debug, Clone)]
 pub struct RandomGenerator {
-    rng: ThreadRng,
     uniform_d2: Uniform<i32>,
     uniform_d6: Uniform<i32>,
     uniform_d9: Uniform<i32>,
@@ -14,7 +29,6 @@ pub struct RandomGenerator {
 impl RandomGenerator {
     pub fn new() -> RandomGenerator {
         RandomGenerator {
-            rng: thread_rng(),
             uniform_d2: Uniform::new(0, 2),
             uniform_d6: Uniform::new(0, 6),
             uniform_d9: Uniform::new(0, 9),
@@ -24,28 +38,38 @@ impl RandomGenerator {
         }
     }
 
+    /// Reseed the generators of the thread.
+    pub fn seed(seed: u64) {
+        with_rng(|rng| *rng = StdRng::seed_from_u64(seed));
+    }
+
+    /// A seed for `RandomGenerator::seed`.
+    pub fn next_seed(&mut self) -> u64 {
+        with_rng(|rng| rng.gen())
+    }
+
     pub fn d2(&mut self) -> i32 {
-        self.rng.sample(self.uniform_d2)
+        with_rng(|rng| rng.sample(self.uniform_d2))
     }
 
     pub fn d6(&mut self) -> i32 {
-        self.rng.sample(self.uniform_d6)
+        with_rng(|rng| rng.sample(self.uniform_d6))
     }
 
     pub fn d9(&mut self) -> i32 {
-        self.rng.sample(self.uniform_d9)
+        with_rng(|rng| rng.sample(self.uniform_d9))
     }
 
     pub fn d12(&mut self) -> i32 {
-        self.rng.sample(self.uniform_d12)
+        with_rng(|rng| rng.sample(self.uniform_d12))
     }
 
     pub fn d20(&mut self) -> i32 {
-        self.rng.sample(self.uniform_d20)
+        with_rng(|rng| rng.sample(self.uniform_d20))
     }
 
     pub fn d100(&mut self) -> i32 {
-        self.rng.sample(self.uniform_d100)
+        with_rng(|rng| rng.sample(self.uniform_d100))
     }
 
     /// generate i32 in range [0, number).
@@ -54,7 +78,7 @@ impl RandomGenerator {
             return 0;
         }
         let uniform = Uniform::new(0, number);
-        self.rng.sample(uniform)
+        with_rng(|rng| rng.sample(uniform))
     }
 
     /// generate i32 in range [lo, hi).
@@ -63,10 +87,40 @@ impl RandomGenerator {
             return lo;
         }
         if lo > hi {
-            self.rng.sample(Uniform::new(hi, lo))
+            with_rng(|rng| rng.sample(Uniform::new(hi, lo)))
         } else {
-            self.rng.sample(Uniform::new(lo, hi))
+            with_rng(|rng| rng.sample(Uniform::new(lo, hi)))
+        }
+    }
+
+    /// generate f64 in range [0, 1).
+    pub fn probability(&mut self) -> f64 {
+        with_rng(|rng| rng.gen::<f64>())
+    }
+
+    pub fn sample<T, D: Distribution<T>>(&mut self, distribution: D) -> T {
+        with_rng(|rng| rng.sample(distribution))
+    }
+
+    /// index picked in proportion to the weights, `None` if all of them are 0.
+    pub fn weighted(&mut self, weights: &[u32]) -> Option<usize> {
+        let total = weights.iter().map(|weight| *weight as u64).sum::<u64>();
+        if total == 0 {
+            return None;
         }
+        let mut pick = with_rng(|rng| rng.gen_range(0..total));
+        for (idx, weight) in weights.iter().enumerate() {
+            if pick < *weight as u64 {
+                return Some(idx);
+            }
+            pick -= *weight as u64;
+        }
+        None
+    }
+
+    /// true with a chance of `percent` in 100.
+    pub fn chance(&mut self, percent: u32) -> bool {
+        (self.d100() as u32) < percent
     }
 
     pub fn bool(&mut self) -> bool {
@@ -109,4 +163,27 @@ mod test {
             eprintln!("random number {}: {}", i, gen_random.d100());
         }
     }
+
+    #[test]
+    fn test_seed() {
+        let mut gen_random = RandomGenerator::new();
+        RandomGenerator::seed(42);
+        let first = (0..10).map(|_| gen_random.d100()).collect::<Vec<_>>();
+        RandomGenerator::seed(42);
+        let second = (0..10)
+            .map(|_| RandomGenerator::new().d100())
+            .collect::<Vec<_>>();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut gen_random = RandomGenerator::new();
+        assert_eq!(gen_random.weighted(&[0, 0]), None);
+        for _ in 0..100 {
+            assert_eq!(gen_random.weighted(&[0, 3, 0]), Some(1));
+            assert!(!gen_random.chance(0));
+            assert!(gen_random.chance(100));
+        }
+    }
 }
@@ -1,5 +1,6 @@
-use super::RandomGenerator;
+use super::{RandomGenerator, ValueDomain};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fmt::{self, Display};
 
 pub type LabelId = u16;
@@ -26,7 +27,9 @@ pub enum FieldValue {
     Int64(i64),
     Float(f32),
     Double(f64),
+    // days since 1970-01-01.
     Date(i64),
+    // seconds since 1970-01-01T00:00:00.
     Datetime(i64),
     String(String),
 }
@@ -47,6 +50,52 @@ impl FieldValue {
             DataType::String => Self::String(random.d6().to_string()),
         }
     }
+
+    /// Convert a JSON value of the schema file, `Null` if it does not fit the type.
+    pub fn from_json(value: &Value, d_type: DataType) -> FieldValue {
+        match (d_type, value) {
+            (DataType::Bool, Value::Bool(b)) => Self::Boolean(*b),
+            (DataType::Int32, Value::Number(n)) => {
+                n.as_i64().map_or(Self::Null, |i| Self::Int32(i as _))
+            }
+            (DataType::Int64, Value::Number(n)) => n.as_i64().map_or(Self::Null, Self::Int64),
+            (DataType::Float, Value::Number(n)) => {
+                n.as_f64().map_or(Self::Null, |f| Self::Float(f as _))
+            }
+            (DataType::Double, Value::Number(n)) => n.as_f64().map_or(Self::Null, Self::Double),
+            (DataType::Date, Value::Number(n)) => n.as_i64().map_or(Self::Null, Self::Date),
+            (DataType::Datetime, Value::Number(n)) => n.as_i64().map_or(Self::Null, Self::Datetime),
+            (DataType::String, Value::String(s)) => Self::String(s.clone()),
+            _ => Self::Null,
+        }
+    }
+
+    /// The temporal function and its ISO 8601 argument, e.g. `("date", "1970-01-02")`.
+    pub fn temporal(&self) -> Option<(&'static str, String)> {
+        match self {
+            Self::Date(days) => {
+                let (year, month, day) = civil_date(*days);
+                Some(("date", format!("{:04}-{:02}-{:02}", year, month, day)))
+            }
+            Self::Datetime(seconds) => {
+                let (year, month, day) = civil_date(seconds.div_euclid(86400));
+                let seconds = seconds.rem_euclid(86400);
+                Some((
+                    "datetime",
+                    format!(
+                        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                        year,
+                        month,
+                        day,
+                        seconds / 3600,
+                        seconds / 60 % 60,
+                        seconds % 60
+                    ),
+                ))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Default for FieldValue {
@@ -55,6 +104,25 @@ impl Default for FieldValue {
     }
 }
 
+/// Year, month and day of the days since 1970-01-01 in the proleptic Gregorian calendar.
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
 impl Display for FieldValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(
@@ -71,9 +139,13 @@ impl Display for FieldValue {
                 Self::Int64(i) => i.to_string(),
                 Self::Float(f) => f.to_string(),
                 Self::Double(d) => d.to_string(),
-                Self::Date(d) => d.to_string(), // should add duration from 1970 and to string
-                Self::Datetime(d) => d.to_string(),
-                Self::String(s) => "'".to_string() + s + "'",
+                Self::Date(_) | Self::Datetime(_) => {
+                    let (function, text) = self.temporal().unwrap_or_default();
+                    format!("{}('{}')", function, text)
+                }
+                Self::String(s) => {
+                    "'".to_string() + &s.replace('\\', "\\\\").replace('\'', "\\'") + "'"
+                }
             }
             .as_ref(),
         )
@@ -88,6 +160,8 @@ pub struct Property {
     pub is_pk: bool,
     pub nullable: bool,
     pub is_delete: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<ValueDomain>,
 }
 
 impl Property {
@@ -98,6 +172,26 @@ impl Property {
     pub fn default_value(&self) -> FieldValue {
         FieldValue::get_default_value(self.prop_type)
     }
+
+    /// Random value within the domain of the property, never null.
+    pub fn random_value(&self, random: &mut RandomGenerator) -> FieldValue {
+        self.domain
+            .as_ref()
+            .and_then(|domain| domain.random_value(self.prop_type, random))
+            .unwrap_or_else(|| self.default_value())
+    }
+
+    /// Random value of a stored property, null with the null ratio of the domain.
+    pub fn random_stored_value(&self, random: &mut RandomGenerator) -> FieldValue {
+        let null_ratio = self.domain.as_ref().map_or_else(
+            || ValueDomain::default().null_ratio(),
+            |domain| domain.null_ratio(),
+        );
+        if self.nullable && random.probability() < null_ratio {
+            return FieldValue::Null;
+        }
+        self.random_value(random)
+    }
 }
 
 impl Display for Property {
@@ -108,7 +202,7 @@ impl Display for Property {
 
 #[cfg(test)]
 mod tests {
-    use super::{DataType, Property};
+    use super::{DataType, FieldValue, Property};
 
     #[test]
     fn test_property() {
@@ -119,8 +213,20 @@ mod tests {
             is_pk: false,
             nullable: true,
             is_delete: false,
+            domain: None,
         };
 
         println!("{}", pro)
     }
+
+    #[test]
+    fn test_temporal_literal() {
+        assert_eq!(FieldValue::Date(0).to_string(), "date('1970-01-01')");
+        assert_eq!(FieldValue::Date(19782).to_string(), "date('2024-02-29')");
+        assert_eq!(FieldValue::Date(-1).to_string(), "date('1969-12-31')");
+        assert_eq!(
+            FieldValue::Datetime(951_827_696).to_string(),
+            "datetime('2000-02-29T12:34:56')"
+        );
+    }
 }
@@ -1,26 +1,11 @@
 use csv::Writer;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 struct Cypher {
     cypher: String,
 }
 
-#[derive(Serialize, Deserialize)]
-struct Error {
-    detail: String,
-    level: String,
-    message: String,
-}
-
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all(deserialize = "camelCase"))]
-struct Errors {
-    cypher: String,
-    errors: String,
-}
-
 pub struct OutputWriter {
     pub file: Writer<std::fs::File>,
 }
@@ -31,13 +16,8 @@ impl OutputWriter {
         Self { file }
     }
 
-    // TODO: Record ALL errors.
-    pub fn write_errors(&mut self, cypher: String, errors: Vec<Value>) {
-        let err = serde_json::from_value::<Error>(errors[0].clone()).unwrap();
-        let errors = serde_json::to_string(&err).unwrap();
-
-        let record = Errors { cypher, errors };
-
+    /// One row per record, e.g. an error bucket.
+    pub fn write_record(&mut self, record: impl Serialize) {
         // the header row written automatic
         self.file.serialize(record).unwrap();
     }
@@ -52,7 +32,11 @@ impl OutputWriter {
     }
 }
 
-#[allow(dead_code)]
+/// Symbolic name in backticks, with the backticks inside doubled, e.g. `` `a``b` ``.
+pub fn escape_name(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
 pub const RESERVED_WORD: &[&str] = &[
     "All",
     "And",
@@ -101,3 +85,29 @@ pub const RESERVED_WORD: &[&str] = &[
     "With",
     "Xor",
 ];
+
+/// Procedures that modify the graph or the schema, `namespace.name` in lower case.
+pub const WRITE_PROCEDURES: &[&str] = &[
+    "db.createlabel",
+    "db.createproperty",
+    "db.createrelationshiptype",
+    "db.clearquerycaches",
+    "db.index.fulltext.createnodeindex",
+    "db.index.fulltext.createrelationshipindex",
+    "db.index.fulltext.drop",
+];
+
+/// Write procedures, by their name in `WRITE_PROCEDURES`.
+pub fn is_write_procedure(name_space: &str, name: &str) -> bool {
+    let full_name = format!("{}.{}", name_space, name).to_lowercase();
+    WRITE_PROCEDURES.contains(&full_name.as_str())
+}
+
+/// Parse a serialized AST, nested deeper than the default recursion limit of serde_json.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> serde_json::Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    deserializer.disable_recursion_limit();
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
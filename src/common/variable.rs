@@ -1,8 +1,10 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{cmp, collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
 
 use super::{DataType, Diagnostic, Literal, RandomGenerator};
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum DataKind {
     Vertex,
     Edge,
@@ -55,7 +57,7 @@ impl From<Literal> for DataKind {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Variable {
     name: String,
     kind: DataKind,
@@ -90,7 +92,7 @@ impl Display for Variable {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct VariableManager {
     types: HashMap<DataKind, Vec<String>>,
     random: RandomGenerator,
@@ -122,6 +124,12 @@ pub struct VariableGenerator {
     name: String,
     number: u32,
     t_number: u32,
+    // variables numbered below scope_start belong to an outer query.
+    scope_start: u32,
+    // outer variables imported into the current scope.
+    imported: Vec<Variable>,
+    // numbers of subquery variables which were not returned.
+    hidden: Vec<u32>,
     manager: VariableManager,
 }
 
@@ -131,10 +139,64 @@ impl VariableGenerator {
             name: "v".to_string(),
             number: 0u32,
             t_number: 0u32,
+            scope_start: 0u32,
+            imported: vec![],
+            hidden: vec![],
             manager: VariableManager::default(),
         }
     }
 
+    /// Scope of a `CALL { }` subquery.
+    ///
+    /// Numbering continues from the outer query, only the imported variables stay visible.
+    pub fn sub_scope(&self, imports: &[Variable]) -> Self {
+        let mut manager = VariableManager::default();
+        for var in imports {
+            manager.add_variable(var.get_name(), var.get_kind());
+        }
+        VariableGenerator {
+            name: self.name.clone(),
+            number: self.number,
+            t_number: self.t_number,
+            scope_start: self.number,
+            imported: imports.to_vec(),
+            hidden: vec![],
+            manager,
+        }
+    }
+
+    /// Scope of the clauses nested in a FOREACH.
+    ///
+    /// The enclosing variables stay visible, the ones defined inside are dropped by
+    /// `merge_scope` afterwards.
+    pub fn nested_scope(&self) -> Self {
+        VariableGenerator {
+            name: self.name.clone(),
+            number: self.number,
+            t_number: self.t_number,
+            scope_start: self.scope_start,
+            imported: self.imported.clone(),
+            hidden: self.hidden.clone(),
+            manager: self.manager.clone(),
+        }
+    }
+
+    /// Leave a subquery or nested scope: skip the names used inside and expose the returned
+    /// variables.
+    pub fn merge_scope(&mut self, inner: &VariableGenerator, exports: &[Variable]) {
+        for number in self.number..inner.number {
+            let name = self.name.clone() + &number.to_string();
+            if exports.iter().all(|var| var.get_name() != name) {
+                self.hidden.push(number);
+            }
+        }
+        self.number = cmp::max(self.number, inner.number);
+        self.t_number = cmp::max(self.t_number, inner.t_number);
+        for var in exports {
+            self.manager.add_variable(var.get_name(), var.get_kind());
+        }
+    }
+
     /// default variable.
     pub fn new_variable(&mut self) -> Variable {
         let var = Variable::new(self.name.clone() + &self.number.to_string());
@@ -159,11 +221,20 @@ impl VariableGenerator {
     }
 
     pub fn get_old_variable(&mut self) -> Result<Variable, Diagnostic> {
-        if self.number == 0 {
+        let imported = self.imported.len() as i32;
+        let numbers = (self.scope_start..self.number)
+            .filter(|number| !self.hidden.contains(number))
+            .collect::<Vec<_>>();
+        let visible = numbers.len() as i32 + imported;
+        if visible == 0 {
             return Err(Diagnostic::error("variable out of range.", None));
         }
         let mut random = RandomGenerator::new();
-        let old_number = random.d100() % (self.number as i32);
+        let old_number = random.d100() % visible;
+        if old_number < imported {
+            return Ok(self.imported[old_number as usize].clone());
+        }
+        let old_number = numbers[(old_number - imported) as usize];
         Ok(Variable::new(self.name.clone() + &old_number.to_string()))
     }
 
@@ -172,16 +243,6 @@ impl VariableGenerator {
         self.manager.random_target_variable(kind)
     }
 
-    /// procedure method.
-    pub fn get_procedure_method(&mut self) -> Variable {
-        Variable::new("shortestPath".to_string())
-    }
-
-    /// procedure result.
-    pub fn get_procedure_result(&mut self) -> Variable {
-        Variable::new("procedure_result(WIP)".to_string())
-    }
-
     // pub fn get_symbolic_or_integer(&mut self) -> Variable {
     //     Variable::new("symbolic_or_integer(WIP)".to_string())
     // }
@@ -0,0 +1,373 @@
+use std::{path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::Weights;
+use crate::common::ValueType;
+
+/// Syntax profile of the target engine.
+///
+/// A dialect decides which constructs may be generated and how the constructs with
+/// several spellings are printed, so that one AST can be rendered for each target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Dialect {
+    #[default]
+    Atlas,
+    Neo4j4,
+    Neo4j5,
+    Memgraph,
+    Gql,
+}
+
+impl Dialect {
+    pub const ALL: [Dialect; 5] = [
+        Dialect::Atlas,
+        Dialect::Neo4j4,
+        Dialect::Neo4j5,
+        Dialect::Memgraph,
+        Dialect::Gql,
+    ];
+
+    /// `EXISTS {` RegularQuery `}`, Neo4j 4 only allows a pattern with a WHERE.
+    pub fn exists_subquery(&self) -> bool {
+        matches!(self, Dialect::Atlas | Dialect::Neo4j5 | Dialect::Gql)
+    }
+
+    /// `COUNT {` RegularQuery `}`
+    pub fn count_subquery(&self) -> bool {
+        matches!(self, Dialect::Neo4j5)
+    }
+
+    /// `x IS :: INTEGER` or `x IS TYPED INTEGER`
+    pub fn type_predicate(&self) -> bool {
+        matches!(self, Dialect::Neo4j5 | Dialect::Gql)
+    }
+
+    /// Named indexes and constraints, `SHOW INDEXES` and `SHOW CONSTRAINTS`.
+    pub fn schema_commands(&self) -> bool {
+        matches!(self, Dialect::Atlas | Dialect::Neo4j4 | Dialect::Neo4j5)
+    }
+
+    pub fn load_csv(&self) -> bool {
+        !matches!(self, Dialect::Gql)
+    }
+
+    /// `CALL {` .. `} IN TRANSACTIONS`
+    pub fn in_transactions(&self) -> bool {
+        !matches!(self, Dialect::Memgraph | Dialect::Gql)
+    }
+
+    /// `WALK`, `TRAIL`, `ACYCLIC` and `SIMPLE` path modes of a MATCH pattern.
+    pub fn path_modes(&self) -> bool {
+        matches!(self, Dialect::Gql)
+    }
+
+    /// `((a)-[]->(b) WHERE ..){m,n}` quantified and grouped path patterns.
+    pub fn quantified_paths(&self) -> bool {
+        matches!(self, Dialect::Neo4j5 | Dialect::Gql)
+    }
+
+    /// `ALL`, `ANY k`, `ALL SHORTEST`, `ANY SHORTEST`, `SHORTEST k (GROUPS)?` path selectors.
+    pub fn path_selectors(&self) -> bool {
+        matches!(self, Dialect::Neo4j5 | Dialect::Gql)
+    }
+
+    /// Cypher clauses and expressions GQL can not express: MERGE, FOREACH, list and
+    /// pattern comprehensions and the ALL, ANY, NONE and SINGLE predicate functions.
+    pub fn cypher_only(&self) -> bool {
+        !matches!(self, Dialect::Gql)
+    }
+
+    /// `EXPLAIN` and `PROFILE` prefixes returning the plan of the query.
+    pub fn query_plans(&self) -> bool {
+        !matches!(self, Dialect::Gql)
+    }
+
+    /// `PROFILE` returns the rows of the query with its plan, Memgraph returns the
+    /// profiled operators instead of the rows.
+    pub fn profile_rows(&self) -> bool {
+        !matches!(self, Dialect::Memgraph)
+    }
+
+    /// Known procedures of the target: namespace, name and result fields, e.g.
+    /// `CALL db.labels() YIELD label`; none for GQL, which has no catalog procedures.
+    pub fn procedures(&self) -> Vec<Procedure> {
+        PROCEDURES
+            .iter()
+            .filter(|(dialects, ..)| dialects.contains(self))
+            .map(|(_, name_space, name, fields)| (*name_space, *name, *fields))
+            .collect()
+    }
+
+    /// Name of a Cypher function, e.g. `toUpper` is `UPPER` in GQL.
+    pub fn function_name<'a>(&self, name: &'a str) -> &'a str {
+        match self {
+            Dialect::Gql => GQL_FUNCTIONS
+                .iter()
+                .find(|(cypher, _)| cypher.eq_ignore_ascii_case(name))
+                .map_or(name, |(_, gql)| gql),
+            _ => name,
+        }
+    }
+
+    /// The weights without the productions the dialect does not support.
+    pub fn restrict(&self, mut weights: Weights) -> Weights {
+        if !self.exists_subquery() {
+            weights.exists_subquery = 0;
+        }
+        if !self.count_subquery() {
+            weights.count_subquery = 0;
+        }
+        if !self.type_predicate() {
+            weights.type_predicate = 0;
+        }
+        if !self.load_csv() {
+            weights.load_csv = 0;
+        }
+        if !self.quantified_paths() {
+            weights.quantified_path = 0;
+        }
+        if !self.path_selectors() {
+            weights.path_selector = 0;
+        }
+        if !self.cypher_only() {
+            weights.merge = 0;
+            weights.foreach = 0;
+            weights.conditional_update = 0;
+            weights.list_comprehension = 0;
+            weights.pattern_comprehension = 0;
+            weights.predicate_function = 0;
+        }
+        weights
+    }
+
+    /// Location of a csv fixture in `LOAD CSV`: a `file:///` url relative to the import
+    /// directory of the server, or the path of the file for Memgraph.
+    pub fn csv_location(&self, dir: &str, file: &str) -> String {
+        match self {
+            Dialect::Memgraph => Path::new(dir).join(file).display().to_string(),
+            _ => format!("file:///{}", file),
+        }
+    }
+
+    /// A relationships pattern used as a predicate.
+    pub fn pattern_predicate(&self, pattern: &str) -> String {
+        match self {
+            Dialect::Atlas => pattern.to_string(),
+            Dialect::Neo4j4 | Dialect::Memgraph => format!("exists({})", pattern),
+            Dialect::Neo4j5 | Dialect::Gql => format!("EXISTS {{{}}}", pattern),
+        }
+    }
+
+    /// Label predicate of all the labels: `n:A:B`, `n:A&B` or `n IS LABELED A&B`.
+    pub fn label_predicate(&self, expr: &str, labels: &[String]) -> String {
+        match self {
+            Dialect::Atlas | Dialect::Neo4j4 | Dialect::Memgraph => {
+                format!("{}:{}", expr, labels.join(":"))
+            }
+            Dialect::Neo4j5 => format!("{}:{}", expr, labels.join("&")),
+            Dialect::Gql => format!("{} IS LABELED {}", expr, labels.join("&")),
+        }
+    }
+
+    /// Separator of the alternative types of a relationship pattern.
+    pub fn relationship_types_separator(&self) -> &'static str {
+        match self {
+            Dialect::Neo4j5 | Dialect::Gql => "|",
+            _ => "|:",
+        }
+    }
+
+    /// `x IS [NOT] :: TYPE` or `x IS [NOT] TYPED TYPE`.
+    pub fn type_predicate_keyword(&self) -> &'static str {
+        match self {
+            Dialect::Gql => "TYPED",
+            _ => "::",
+        }
+    }
+
+    /// Name of a value type in type predicates.
+    pub fn type_name(&self, value_type: ValueType) -> String {
+        match (self, value_type) {
+            (Dialect::Gql, ValueType::Boolean) => "BOOL".to_string(),
+            (Dialect::Gql, ValueType::Integer) => "INT".to_string(),
+            (Dialect::Gql, ValueType::Map) => "RECORD".to_string(),
+            (Dialect::Gql, ValueType::Relationship) => "EDGE".to_string(),
+            (Dialect::Gql, ValueType::List) => "LIST<ANY VALUE>".to_string(),
+            (Dialect::Gql, ValueType::Any) => "ANY VALUE".to_string(),
+            (_, value_type) => value_type.to_string(),
+        }
+    }
+
+    /// `FOR` pattern `REQUIRE` of Neo4j 5, or `ON` pattern `ASSERT` of Neo4j 4.
+    pub fn constraint_keywords(&self) -> (&'static str, &'static str) {
+        match self {
+            Dialect::Neo4j4 => ("ON", "ASSERT"),
+            _ => ("FOR", "REQUIRE"),
+        }
+    }
+}
+
+/// Namespace, name and result fields of a procedure.
+pub type Procedure = (&'static str, &'static str, &'static [&'static str]);
+
+const NEO4J: &[Dialect] = &[Dialect::Neo4j4, Dialect::Neo4j5];
+
+/// Procedures and the dialects of the targets which have them.
+const PROCEDURES: &[(&[Dialect], &str, &str, &[&str])] = &[
+    (&[Dialect::Atlas], "atlas", "shortestPath", &["path"]),
+    (
+        &[Dialect::Atlas, Dialect::Neo4j4, Dialect::Neo4j5],
+        "db",
+        "labels",
+        &["label"],
+    ),
+    (
+        &[Dialect::Atlas, Dialect::Neo4j4, Dialect::Neo4j5],
+        "db",
+        "relationshipTypes",
+        &["relationshipType"],
+    ),
+    (NEO4J, "db", "propertyKeys", &["propertyKey"]),
+    (NEO4J, "db", "info", &["id", "name", "creationDate"]),
+    (NEO4J, "db", "ping", &["success"]),
+    (
+        NEO4J,
+        "db",
+        "schema.visualization",
+        &["nodes", "relationships"],
+    ),
+    (
+        NEO4J,
+        "dbms",
+        "components",
+        &["name", "versions", "edition"],
+    ),
+    (NEO4J, "db", "createLabel", &[]),
+    (NEO4J, "db", "createProperty", &[]),
+    (NEO4J, "db", "createRelationshipType", &[]),
+    (NEO4J, "db", "clearQueryCaches", &["value"]),
+    (NEO4J, "db", "index.fulltext.drop", &[]),
+    (
+        &[Dialect::Memgraph],
+        "mg",
+        "procedures",
+        &["name", "signature"],
+    ),
+    (
+        &[Dialect::Memgraph],
+        "mg",
+        "functions",
+        &["name", "signature"],
+    ),
+];
+
+/// Cypher functions and their GQL names.
+const GQL_FUNCTIONS: &[(&str, &str)] = &[
+    ("toUpper", "UPPER"),
+    ("toLower", "LOWER"),
+    ("trim", "TRIM"),
+    ("ltrim", "LTRIM"),
+    ("rtrim", "RTRIM"),
+    ("left", "LEFT"),
+    ("right", "RIGHT"),
+    ("abs", "ABS"),
+    ("ceil", "CEIL"),
+    ("floor", "FLOOR"),
+    ("sqrt", "SQRT"),
+    ("exp", "EXP"),
+    ("log", "LN"),
+    ("log10", "LOG10"),
+    ("coalesce", "COALESCE"),
+    ("count", "COUNT"),
+    ("sum", "SUM"),
+    ("avg", "AVG"),
+    ("min", "MIN"),
+    ("max", "MAX"),
+    ("collect", "COLLECT_LIST"),
+    ("stDev", "STDDEV_SAMP"),
+    ("stDevP", "STDDEV_POP"),
+    ("date", "DATE"),
+    ("datetime", "ZONED_DATETIME"),
+    ("localdatetime", "LOCAL_DATETIME"),
+    ("time", "ZONED_TIME"),
+    ("localtime", "LOCAL_TIME"),
+    ("duration", "DURATION"),
+];
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Dialect::ALL
+            .into_iter()
+            .find(|dialect| format!("{:?}", dialect).eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown dialect `{}`", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dialect;
+    use crate::{
+        common::{is_write_procedure, ValueType},
+        config::Weights,
+    };
+
+    #[test]
+    fn test_dialect() {
+        assert_eq!("neo4j5".parse::<Dialect>(), Ok(Dialect::Neo4j5));
+        assert!("neo4j6".parse::<Dialect>().is_err());
+        assert_eq!(
+            serde_json::from_str::<Dialect>(r#""Memgraph""#).unwrap(),
+            Dialect::Memgraph
+        );
+
+        let weights = Dialect::Memgraph.restrict(Weights::default());
+        assert_eq!(weights.exists_subquery, 0);
+        assert_eq!(weights.count_subquery, 0);
+        assert_eq!(weights.load_csv, Weights::default().load_csv);
+        let weights = Dialect::Neo4j5.restrict(Weights::default());
+        assert_eq!(weights.count_subquery, Weights::default().count_subquery);
+
+        let labels = ["A".to_string(), "B".to_string()];
+        assert_eq!(Dialect::Atlas.label_predicate("n", &labels), "n:A:B");
+        assert_eq!(Dialect::Neo4j5.label_predicate("n", &labels), "n:A&B");
+        assert_eq!(
+            Dialect::Gql.label_predicate("n", &labels),
+            "n IS LABELED A&B"
+        );
+        assert_eq!(
+            Dialect::Neo4j4.pattern_predicate("(a)-->()"),
+            "exists((a)-->())"
+        );
+
+        assert_eq!(Dialect::Gql.type_name(ValueType::Integer), "INT");
+        assert_eq!(Dialect::Neo4j5.type_name(ValueType::Integer), "INTEGER");
+        let weights = Dialect::Gql.restrict(Weights::default());
+        assert_eq!(
+            (weights.merge, weights.foreach, weights.load_csv),
+            (0, 0, 0)
+        );
+
+        assert_eq!(Dialect::Gql.function_name("toUpper"), "UPPER");
+        assert_eq!(Dialect::Gql.function_name("datetime"), "ZONED_DATETIME");
+        assert_eq!(Dialect::Neo4j5.function_name("toUpper"), "toUpper");
+        assert!(Dialect::Gql.procedures().is_empty());
+        // a target only calls its own procedures.
+        let names = |dialect: Dialect| {
+            dialect
+                .procedures()
+                .into_iter()
+                .map(|(name_space, name, _)| format!("{}.{}", name_space, name))
+                .collect::<Vec<_>>()
+        };
+        assert!(names(Dialect::Atlas).contains(&"atlas.shortestPath".to_string()));
+        assert!(!names(Dialect::Atlas).contains(&"db.info".to_string()));
+        assert!(!names(Dialect::Neo4j5).contains(&"atlas.shortestPath".to_string()));
+        assert!(names(Dialect::Memgraph)
+            .iter()
+            .all(|name| name.starts_with("mg.")));
+        assert!(is_write_procedure("db", "index.fulltext.drop"));
+    }
+}
@@ -1,7 +1,14 @@
+mod dialect;
+mod weights;
+
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::{ast::Layout, common::constants};
+pub use dialect::Dialect;
+pub use weights::Weights;
+
 /// GraphSchema Config.
 #[derive(Parser)]
 #[cfg_attr(debug_assertions, derive(Debug))]
@@ -16,6 +23,15 @@ $ cypher-smith --schema schema.json --config config.json
 ## import schema and atlas config.
 $ cypher-smith --schema schema.json --config config.json --atlas atlas.json
 
+## write the schema of the database into schema.json.
+$ cypher-smith --atlas atlas.json --introspect schema.json
+
+## print the query of a serialized AST or a findings record.
+$ cypher-smith --render ast.json
+
+## print it one clause per line.
+$ cypher-smith --render ast.json --layout pretty
+
 "#
 )]
 pub struct ArgsConfig {
@@ -25,6 +41,30 @@ pub struct ArgsConfig {
     pub config: Option<PathBuf>,
     #[clap(short, long, value_name = "PATH", help = "basic config information")]
     pub atlas: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "write the schema of the database instead of fuzzing"
+    )]
+    pub introspect: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "print the query of a serialized AST instead of fuzzing"
+    )]
+    pub render: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "DIALECT",
+        help = "dialect of the rendered query: Atlas, Neo4j4, Neo4j5, Memgraph or Gql"
+    )]
+    pub dialect: Option<Dialect>,
+    #[clap(
+        long,
+        value_name = "LAYOUT",
+        help = "layout of the rendered query: Canonical, Pretty or Fuzzed"
+    )]
+    pub layout: Option<Layout>,
 }
 
 impl Default for ArgsConfig {
@@ -37,41 +77,269 @@ impl Default for ArgsConfig {
 pub struct CypherConfig {
     // StandaloneCall
     pub call_query: bool,
+    // index and constraint DDL interleaved with the queries.
+    #[serde(default)]
+    pub ddl_query: bool,
     pub max_queries: u32,
     pub dry_run: bool,
     pub dry_run_path: Option<String>,
     pub verbose: Option<String>,
     pub dump_all_graphs: bool,
+    // directory of the csv fixtures used by LOAD CSV, the database must import from it.
+    pub csv_fixture_path: Option<String>,
+    // populate the database from the schema before fuzzing.
+    pub populate: Option<PopulateConfig>,
+    // total expression complexity of a query.
+    #[serde(default = "default_query_limit")]
+    pub query_limit: i32,
+    #[serde(default)]
+    pub weights: Weights,
+    // never generate updating clauses, write procedures or DDL.
+    #[serde(default)]
+    pub read_only: bool,
+    // how the writes of a query are isolated from the next queries.
+    #[serde(default)]
+    pub transaction: TransactionMode,
+    // JSON Lines report of the queries which did not succeed.
+    pub findings: Option<String>,
+    // seed of the first query, random if not given.
+    pub seed: Option<u64>,
+    // JSON file of the grammar coverage.
+    pub coverage: Option<String>,
+    // adapt the weights to the results of the queries.
+    #[serde(default)]
+    pub adaptive: bool,
+    // syntax of the target engine.
+    #[serde(default)]
+    pub dialect: Dialect,
+    // layout of the generated queries, `Fuzzed` for the lexer of the target.
+    #[serde(default)]
+    pub layout: Layout,
+    // chance in percent to mutate a query into an invalid one, which must be
+    // rejected with a syntax error.
+    #[serde(default)]
+    pub syntax_mutation: u32,
+    // huge queries to exhaust the stack and the memory of the engine.
+    pub stress: Option<StressConfig>,
+    // chance in percent to run a semantics preserving rewrite of a read only query,
+    // which must return the same rows.
+    #[serde(default)]
+    pub metamorphic: u32,
+    // chance in percent to generate `RETURN` of a constant expression, whose value
+    // must be the one of the evaluator.
+    #[serde(default)]
+    pub constant_folding: u32,
+    // check the rows of read only queries against the in memory reference engine
+    // over the populated dataset.
+    #[serde(default)]
+    pub reference: bool,
+    // capture the plan of every query, measure the plan coverage and attach the plan
+    // to the findings; unseen operators bias the generation with `adaptive` only.
+    #[serde(default)]
+    pub plan: Option<PlanMode>,
+}
+
+fn default_query_limit() -> i32 {
+    constants::DEFAULT_QUERY_LIMIT
 }
 
 impl Default for CypherConfig {
     fn default() -> Self {
         CypherConfig {
             call_query: false,
+            ddl_query: false,
             max_queries: 100,
             dry_run: true,
             dry_run_path: None,
             verbose: None,
             dump_all_graphs: false,
+            csv_fixture_path: None,
+            populate: None,
+            query_limit: constants::DEFAULT_QUERY_LIMIT,
+            weights: Weights::default(),
+            read_only: false,
+            transaction: TransactionMode::AutoCommit,
+            findings: None,
+            seed: None,
+            coverage: None,
+            adaptive: false,
+            dialect: Dialect::Atlas,
+            layout: Layout::Canonical,
+            syntax_mutation: 0,
+            stress: None,
+            metamorphic: 0,
+            constant_folding: 0,
+            reference: false,
+            plan: None,
+        }
+    }
+}
+
+/// Number of edges generated per source vertex and relation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DegreeDistribution {
+    Fixed(u32),
+    Uniform { min: u32, max: u32 },
+    // heavy-tailed, most vertices get few edges and some get up to max.
+    PowerLaw { max: u32, exponent: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PopulateConfig {
+    pub vertices_per_label: u32,
+    // rows per UNWIND statement.
+    pub batch_size: u32,
+    pub degree: DegreeDistribution,
+    // delete all the existing data first.
+    pub clear: bool,
+}
+
+impl Default for PopulateConfig {
+    fn default() -> Self {
+        PopulateConfig {
+            vertices_per_label: 100,
+            batch_size: 100,
+            degree: DegreeDistribution::Uniform { min: 0, max: 3 },
+            clear: false,
+        }
+    }
+}
+
+/// Stress profile: deep expressions, many UNION arms, long patterns, huge lists and
+/// large variable length ranges, each applied with `chance` percent at each opportunity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StressConfig {
+    pub chance: u32,
+    // nesting depth of an expression.
+    pub expression_depth: u32,
+    pub union_arms: u32,
+    // relationships of a pattern.
+    pub pattern_length: u32,
+    // elements of a list literal.
+    pub list_length: u32,
+    // upper bound of a variable length relationship.
+    pub max_hops: u32,
+    // a query without a result in time is a finding.
+    pub timeout_ms: u64,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        StressConfig {
+            chance: 10,
+            expression_depth: 1000,
+            union_arms: 1000,
+            pattern_length: 100,
+            list_length: 10000,
+            max_hops: 1000,
+            timeout_ms: 30000,
         }
     }
 }
 
+/// Isolation of the queries that write, other queries are always auto-committed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionMode {
+    // every statement is committed on its own.
+    #[default]
+    AutoCommit,
+    // run in an explicit transaction which is rolled back.
+    Rollback,
+    // run in an explicit transaction which is committed.
+    Commit,
+    // auto-commit, then restore the populated dataset.
+    Snapshot,
+}
+
+impl TransactionMode {
+    /// The queries that write run in an explicit transaction opened by `Begin`.
+    pub fn explicit(&self) -> bool {
+        matches!(self, TransactionMode::Rollback | TransactionMode::Commit)
+    }
+}
+
+/// How the plan of a query is captured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlanMode {
+    // `EXPLAIN` the query before executing it.
+    Explain,
+    // execute the query prefixed with `PROFILE`, the rows are kept but by Memgraph,
+    // see `Dialect::profile_rows`.
+    Profile,
+}
+
 #[cfg(test)]
 mod tests {
-    use super::CypherConfig;
+    use super::{
+        CypherConfig, Dialect, Layout, PlanMode, PopulateConfig, StressConfig, TransactionMode,
+        Weights,
+    };
 
     #[test]
     fn test_deserialize() {
         let cypher_config = CypherConfig {
             call_query: false,
+            ddl_query: false,
             max_queries: 100,
             dry_run: true,
             dry_run_path: Some("test".to_string()),
             verbose: Some("test".to_string()),
             dump_all_graphs: false,
+            csv_fixture_path: Some("test".to_string()),
+            populate: Some(PopulateConfig::default()),
+            query_limit: 15,
+            weights: Weights::default(),
+            read_only: false,
+            transaction: TransactionMode::AutoCommit,
+            findings: None,
+            seed: None,
+            coverage: None,
+            adaptive: false,
+            dialect: Dialect::Atlas,
+            layout: Layout::Canonical,
+            syntax_mutation: 0,
+            stress: None,
+            metamorphic: 0,
+            constant_folding: 0,
+            reference: false,
+            plan: None,
         };
 
         println!("{:?}", cypher_config);
     }
+
+    #[test]
+    fn test_populate_deserialize() {
+        let populate_config = serde_json::from_str::<PopulateConfig>(
+            r#"{"vertices_per_label": 10, "degree": {"PowerLaw": {"max": 20, "exponent": 2.5}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(populate_config.vertices_per_label, 10);
+        assert_eq!(populate_config.batch_size, 100);
+        assert!(!populate_config.clear);
+
+        let stress_config =
+            serde_json::from_str::<StressConfig>(r#"{"union_arms": 5000, "timeout_ms": 1000}"#)
+                .unwrap();
+        assert_eq!(stress_config.union_arms, 5000);
+        assert_eq!(stress_config.expression_depth, 1000);
+    }
+
+    #[test]
+    fn test_transaction_deserialize() {
+        let cypher_config = serde_json::from_str::<CypherConfig>(
+            r#"{"call_query": false, "max_queries": 10, "dry_run": true, "dump_all_graphs": false, "transaction": "Rollback", "plan": "Profile"}"#,
+        )
+        .unwrap();
+        assert_eq!(cypher_config.transaction, TransactionMode::Rollback);
+        assert_eq!(cypher_config.plan, Some(PlanMode::Profile));
+        assert_eq!(cypher_config.stress, None);
+        assert_eq!(
+            CypherConfig::default().transaction,
+            TransactionMode::AutoCommit
+        );
+    }
 }
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+macro_rules! weights_impl {
+    ($(
+        $(#[doc = $doc:expr])*
+        $name:ident: $default:expr,
+    )*) => {
+        /// Weights of the grammar productions, 0 disables a production entirely.
+        ///
+        /// Clauses and atoms are picked in proportion to the weights of their alternatives,
+        /// operator weights are the chance in percent to apply the operator at each opportunity.
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(default)]
+        pub struct Weights {
+            $(
+                $(#[doc = $doc])*
+                pub $name: u32,
+            )*
+        }
+
+        impl Default for Weights {
+            fn default() -> Self {
+                Weights {
+                    $($name: $default,)*
+                }
+            }
+        }
+
+        impl Weights {
+            /// (production, weight) pairs, in declaration order.
+            pub fn productions(&self) -> Vec<(&'static str, u32)> {
+                vec![$((stringify!($name), self.$name),)*]
+            }
+
+            /// Set the weight of a production, false if there is no such production.
+            pub fn set(&mut self, production: &str, weight: u32) -> bool {
+                match production {
+                    $(stringify!($name) => self.$name = weight,)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
+weights_impl! {
+    // reading clauses
+    match_clause: 3,
+    optional_match: 3,
+    unwind: 2,
+    call_subquery: 2,
+    load_csv: 2,
+
+    // updating clauses
+    create: 3,
+    merge: 1,
+    delete: 1,
+    set: 1,
+    remove: 1,
+    foreach: 1,
+    conditional_update: 1,
+
+    // atoms
+    literal: 38,
+    case: 3,
+    count_star: 4,
+    list_comprehension: 3,
+    pattern_comprehension: 6,
+    predicate_function: 6,
+    relationships_pattern: 5,
+    parenthesized: 3,
+    exists_subquery: 2,
+    variable: 30,
+    count_subquery: 2,
+
+    // operator families, in percent.
+    or: 5,
+    xor: 5,
+    and: 5,
+    not: 8,
+    comparison: 5,
+    add_subtract: 5,
+    multiply_divide_modulo: 5,
+    power: 5,
+    unary: 11,
+    string_operator: 1,
+    list_operator: 1,
+    null_operator: 1,
+    type_predicate: 1,
+    property_lookup: 8,
+    node_labels: 8,
+
+    // match patterns, in percent.
+    quantified_path: 10,
+    path_selector: 10,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Weights;
+
+    #[test]
+    fn test_weights_deserialize() {
+        let weights =
+            serde_json::from_str::<Weights>(r#"{"create": 0, "merge": 0, "variable": 50}"#)
+                .unwrap();
+        assert_eq!(weights.create, 0);
+        assert_eq!(weights.variable, 50);
+        assert_eq!(weights.match_clause, Weights::default().match_clause);
+        assert!(weights.productions().contains(&("merge", 0)));
+
+        let mut weights = Weights::default();
+        assert!(weights.set("unwind", 7));
+        assert!(!weights.set("unknown", 7));
+        assert_eq!(weights.unwind, 7);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::common::{FieldValue, LabelId, Property};
+
+#[derive(Debug, Clone)]
+pub struct VertexData {
+    pub label_id: LabelId,
+    pub properties: Vec<(Property, FieldValue)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgeData {
+    pub label_id: LabelId,
+    /// index of the src vertex in `GraphData::vertices`.
+    pub src: usize,
+    /// index of the dst vertex in `GraphData::vertices`.
+    pub dst: usize,
+    pub properties: Vec<(Property, FieldValue)>,
+}
+
+/// The dataset written into the database before fuzzing.
+#[derive(Debug, Clone, Default)]
+pub struct GraphData {
+    pub vertices: Vec<VertexData>,
+    pub edges: Vec<EdgeData>,
+}
+
+impl GraphData {
+    /// indexes of the vertices with the given label.
+    pub fn vertices_of(&self, label_id: LabelId) -> Vec<usize> {
+        self.vertices
+            .iter()
+            .enumerate()
+            .filter(|(_, vertex)| vertex.label_id == label_id)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// value of the property, `None` if the vertex does not have it.
+    pub fn vertex_value(&self, vertex: usize, name: &str) -> Option<&FieldValue> {
+        self.vertices[vertex]
+            .properties
+            .iter()
+            .find(|(property, _)| property.name == name)
+            .map(|(_, value)| value)
+    }
+}
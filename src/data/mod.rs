@@ -0,0 +1,7 @@
+mod graph;
+mod populate;
+mod reference;
+
+pub use graph::{EdgeData, GraphData, VertexData};
+pub use populate::DataGenerator;
+pub use reference::ReferenceEngine;
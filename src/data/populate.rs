@@ -0,0 +1,313 @@
+use super::{EdgeData, GraphData, VertexData};
+use crate::{
+    common::{DataType, Diagnostic, FieldValue, LabelId, Property, RandomGenerator},
+    config::{DegreeDistribution, PopulateConfig},
+    meta::{GraphSchema, Label, LabelKind},
+};
+
+/// Generate the dataset of a schema and the statements writing it into the database.
+pub struct DataGenerator {
+    random: RandomGenerator,
+    graph_schema: GraphSchema,
+    config: PopulateConfig,
+}
+
+impl DataGenerator {
+    pub fn new(graph_schema: &GraphSchema, config: PopulateConfig) -> Self {
+        DataGenerator {
+            random: RandomGenerator::new(),
+            graph_schema: graph_schema.clone(),
+            config,
+        }
+    }
+
+    /// `vertices_per_label` vertices per vertex label, edges follow the relations of the edge labels.
+    pub fn generate(&mut self) -> GraphData {
+        let mut graph_data = GraphData::default();
+
+        for label in self.graph_schema.vertex_labels().to_vec() {
+            for seq in 0..self.config.vertices_per_label as usize {
+                let properties = self.gen_properties(&label, seq);
+                graph_data.vertices.push(VertexData {
+                    label_id: label.label_id,
+                    properties,
+                });
+            }
+        }
+
+        for label in self.graph_schema.edge_labels().to_vec() {
+            let relations = match &label.kind {
+                LabelKind::Edge { relations, .. } => relations.clone(),
+                LabelKind::Vertex => continue,
+            };
+            let mut seq = 0;
+            for (src_id, dst_id) in relations {
+                let dsts = graph_data.vertices_of(dst_id);
+                if dsts.is_empty() {
+                    continue;
+                }
+                for src in graph_data.vertices_of(src_id) {
+                    for _ in 0..self.gen_degree() {
+                        let dst = dsts[self.random.under(dsts.len() as _) as usize];
+                        let properties = self.gen_properties(&label, seq);
+                        seq += 1;
+                        graph_data.edges.push(EdgeData {
+                            label_id: label.label_id,
+                            src,
+                            dst,
+                            properties,
+                        });
+                    }
+                }
+            }
+        }
+
+        graph_data
+    }
+
+    /// Batched `UNWIND [...] AS row CREATE ...` statements, vertices first.
+    pub fn statements(&self, graph_data: &GraphData) -> Vec<String> {
+        let mut statements = vec![];
+        if self.config.clear {
+            statements.push("MATCH (n) DETACH DELETE n".to_string());
+        }
+
+        let batch_size = self.config.batch_size.max(1) as usize;
+        for label in self.graph_schema.vertex_labels() {
+            let rows = graph_data
+                .vertices
+                .iter()
+                .filter(|vertex| vertex.label_id == label.label_id)
+                .map(|vertex| property_map(&vertex.properties))
+                .collect::<Vec<_>>();
+            let create = format!(
+                "CREATE (:{} {})",
+                label.get_name(),
+                row_properties(label, "row")
+            );
+            statements.extend(unwind_statements(&rows, batch_size, &create));
+        }
+
+        for label in self.graph_schema.edge_labels() {
+            let relations = match &label.kind {
+                LabelKind::Edge { relations, .. } => relations,
+                LabelKind::Vertex => continue,
+            };
+            for (src_id, dst_id) in relations {
+                let (src_label, dst_label) =
+                    match (self.vertex_label(*src_id), self.vertex_label(*dst_id)) {
+                        (Some(src_label), Some(dst_label)) => (src_label, dst_label),
+                        _ => continue,
+                    };
+                // endpoints are matched by primary key.
+                let (src_pk, dst_pk) = match (primary_key(src_label), primary_key(dst_label)) {
+                    (Some(src_pk), Some(dst_pk)) => (src_pk, dst_pk),
+                    _ => {
+                        eprintln!(
+                            "{}",
+                            Diagnostic::warn(
+                                "Populate edges without primary key",
+                                format!("{} from {} to {}", label, src_label, dst_label),
+                            )
+                        );
+                        continue;
+                    }
+                };
+                let rows = graph_data
+                    .edges
+                    .iter()
+                    .filter(|edge| {
+                        edge.label_id == label.label_id
+                            && graph_data.vertices[edge.src].label_id == *src_id
+                            && graph_data.vertices[edge.dst].label_id == *dst_id
+                    })
+                    .map(|edge| {
+                        let src = graph_data.vertex_value(edge.src, &src_pk.name);
+                        let dst = graph_data.vertex_value(edge.dst, &dst_pk.name);
+                        format!(
+                            "{{src: {}, dst: {}, properties: {}}}",
+                            literal(src.unwrap_or(&FieldValue::Null)),
+                            literal(dst.unwrap_or(&FieldValue::Null)),
+                            property_map(&edge.properties)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let create = format!(
+                    "MATCH (a:{} {{{}: row.src}}), (b:{} {{{}: row.dst}}) CREATE (a)-[:{} {}]->(b)",
+                    src_label,
+                    src_pk,
+                    dst_label,
+                    dst_pk,
+                    label,
+                    row_properties(label, "row.properties")
+                );
+                statements.extend(unwind_statements(&rows, batch_size, &create));
+            }
+        }
+
+        statements
+    }
+}
+
+impl DataGenerator {
+    fn vertex_label(&self, label_id: LabelId) -> Option<&Label> {
+        self.graph_schema
+            .vertex_labels()
+            .iter()
+            .find(|label| label.label_id == label_id)
+    }
+
+    fn gen_properties(&mut self, label: &Label, seq: usize) -> Vec<(Property, FieldValue)> {
+        label
+            .properties
+            .iter()
+            .filter(|property| !property.is_delete)
+            .map(|property| (property.clone(), self.gen_value(property, seq)))
+            .collect()
+    }
+
+    /// primary keys are derived from `seq` and unique per label, the others follow the property domain.
+    fn gen_value(&mut self, property: &Property, seq: usize) -> FieldValue {
+        if property.is_pk {
+            match property.prop_type {
+                DataType::Int32 => return FieldValue::Int32(seq as _),
+                DataType::Int64 => return FieldValue::Int64(seq as _),
+                DataType::Float => return FieldValue::Float(seq as _),
+                DataType::Double => return FieldValue::Double(seq as _),
+                DataType::Date => return FieldValue::Date(seq as _),
+                DataType::Datetime => return FieldValue::Datetime(seq as _),
+                DataType::String => return FieldValue::String(seq.to_string()),
+                DataType::Null | DataType::Bool => {}
+            }
+        }
+        property.random_stored_value(&mut self.random)
+    }
+
+    fn gen_degree(&mut self) -> u32 {
+        match self.config.degree {
+            DegreeDistribution::Fixed(degree) => degree,
+            DegreeDistribution::Uniform { min, max } => {
+                self.random.range(min as _, max as i32 + 1) as _
+            }
+            DegreeDistribution::PowerLaw { max, exponent } => {
+                // inverse transform sampling of a pareto distribution starting at 1.
+                let exponent = exponent.max(1.1);
+                let sample = (1.0 - self.random.probability()).powf(-1.0 / (exponent - 1.0));
+                ((sample - 1.0).floor() as u32).min(max)
+            }
+        }
+    }
+}
+
+fn primary_key(label: &Label) -> Option<&Property> {
+    label
+        .properties
+        .iter()
+        .find(|property| property.is_pk && !property.is_delete)
+}
+
+/// `{a: row.a, b: row.b}`
+fn row_properties(label: &Label, row: &str) -> String {
+    let properties = label
+        .properties
+        .iter()
+        .filter(|property| !property.is_delete)
+        .map(|property| format!("{}: {}.{}", property, row, property))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", properties.join(", "))
+}
+
+/// `{a: 1, b: 'x'}`
+fn property_map(properties: &[(Property, FieldValue)]) -> String {
+    let properties = properties
+        .iter()
+        .map(|(property, value)| format!("{}: {}", property, literal(value)))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", properties.join(", "))
+}
+
+fn literal(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Float(value) if value.fract() == 0.0 => format!("{:.1}", value),
+        FieldValue::Double(value) if value.fract() == 0.0 => format!("{:.1}", value),
+        value => value.to_string(),
+    }
+}
+
+fn unwind_statements(rows: &[String], batch_size: usize, clause: &str) -> Vec<String> {
+    rows.chunks(batch_size)
+        .map(|chunk| format!("UNWIND [{}] AS row {}", chunk.join(", "), clause))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::DataGenerator;
+    use crate::common::{DataType, FieldValue, Property};
+    use crate::config::{DegreeDistribution, PopulateConfig};
+    use crate::meta::testing::{pk_property, property};
+    use crate::meta::{GraphSchema, Label, LabelKind};
+
+    fn test_schema() -> GraphSchema {
+        let properties = vec![
+            pk_property("id", 0, DataType::Int32),
+            Property {
+                nullable: false,
+                ..property("name", 1, DataType::String)
+            },
+        ];
+        let labels = vec![
+            Label {
+                label_name: "Person".to_string(),
+                label_id: 1,
+                kind: LabelKind::Vertex,
+                properties: properties.clone(),
+            },
+            Label {
+                label_name: "Knows".to_string(),
+                label_id: 2,
+                kind: LabelKind::Edge {
+                    relations: vec![(1, 1)],
+                    is_directed: true,
+                },
+                properties: vec![],
+            },
+        ];
+        GraphSchema::new("test".to_string(), labels)
+    }
+
+    #[test]
+    fn test_populate() {
+        let config = PopulateConfig {
+            vertices_per_label: 10,
+            batch_size: 4,
+            degree: DegreeDistribution::Fixed(2),
+            clear: true,
+        };
+        let mut generator = DataGenerator::new(&test_schema(), config);
+        let graph_data = generator.generate();
+
+        assert_eq!(graph_data.vertices.len(), 10);
+        assert_eq!(graph_data.edges.len(), 20);
+        let ids = (0..graph_data.vertices.len())
+            .map(|vertex| graph_data.vertex_value(vertex, "id").unwrap().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), 10);
+        assert!(graph_data
+            .vertices
+            .iter()
+            .all(|vertex| !matches!(vertex.properties[1].1, FieldValue::Null)));
+
+        let statements = generator.statements(&graph_data);
+        // clear, 3 vertex batches, 5 edge batches.
+        assert_eq!(statements.len(), 9);
+        assert_eq!(statements[0], "MATCH (n) DETACH DELETE n");
+        assert!(statements[1].starts_with("UNWIND [{id: 0, name: '"));
+        assert!(statements[1].ends_with("] AS row CREATE (:Person {id: row.id, name: row.name})"));
+        assert!(statements[8].ends_with(
+            "] AS row MATCH (a:Person {id: row.src}), (b:Person {id: row.dst}) CREATE (a)-[:Knows {}]->(b)"
+        ));
+    }
+}
@@ -0,0 +1,1243 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use serde_json::Value;
+
+use super::GraphData;
+use crate::{
+    ast::{
+        aggregates, aggregation, equals, evaluate, evaluate_in, Constant, CypherNode, Eval,
+        EvalError, Scope,
+    },
+    common::{constants, Expr, FieldValue, Property, RelationshipDirection},
+    meta::Label,
+};
+
+/// The variables of a row.
+type Row = BTreeMap<String, Constant>;
+
+/// Expected rows of a query, in this order if `ordered`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expected {
+    pub rows: Vec<Vec<Constant>>,
+    pub ordered: bool,
+}
+
+impl Expected {
+    /// How the rows of the target differ from the expected ones, `None` if they agree.
+    pub fn mismatch(&self, rows: &[Vec<Value>]) -> Option<String> {
+        let same_row = |expected: &Vec<Constant>, row: &Vec<Value>| {
+            expected.len() == row.len()
+                && expected
+                    .iter()
+                    .zip(row)
+                    .all(|(expected, value)| expected.matches(value))
+        };
+        let same = self.rows.len() == rows.len()
+            && if self.ordered {
+                self.rows
+                    .iter()
+                    .zip(rows)
+                    .all(|(expected, row)| same_row(expected, row))
+            } else {
+                // every expected row matches a row of its own.
+                let mut matched = vec![false; rows.len()];
+                self.rows.iter().all(|expected| {
+                    let idx = (0..rows.len())
+                        .find(|idx| !matched[*idx] && same_row(expected, &rows[*idx]));
+                    idx.map(|idx| matched[idx] = true).is_some()
+                })
+            };
+        if same {
+            return None;
+        }
+        let expected = self
+            .rows
+            .iter()
+            .map(|row| Value::Array(row.iter().map(Constant::to_json).collect()))
+            .collect::<Vec<_>>();
+        Some(format!(
+            "expected {} rows{}: {}, got {} rows: {}",
+            self.rows.len(),
+            if self.ordered { " in order" } else { "" },
+            Value::Array(expected),
+            rows.len(),
+            Value::from(rows.to_vec())
+        ))
+    }
+}
+
+/// In memory engine over the populated dataset, for the subset of read queries made of
+/// MATCH, OPTIONAL MATCH, UNWIND, WITH and RETURN with WHERE, DISTINCT, ORDER BY, SKIP,
+/// LIMIT and aggregations.
+///
+/// A query, or a part of it, beyond the subset is not executed: variable length
+/// relationships, subqueries, functions other than the aggregations, errors and results
+/// which depend on the evaluation order of the engine.
+pub struct ReferenceEngine {
+    graph_data: GraphData,
+    // edges by their src and by their dst vertex.
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+impl ReferenceEngine {
+    pub fn new(graph_data: GraphData) -> Self {
+        let mut outgoing = vec![vec![]; graph_data.vertices.len()];
+        let mut incoming = vec![vec![]; graph_data.vertices.len()];
+        for (idx, edge) in graph_data.edges.iter().enumerate() {
+            outgoing[edge.src].push(idx);
+            incoming[edge.dst].push(idx);
+        }
+        ReferenceEngine {
+            graph_data,
+            outgoing,
+            incoming,
+        }
+    }
+
+    /// The expected rows of a query, `None` if the query is beyond the engine.
+    pub fn execute(&self, query: &CypherNode) -> Option<Expected> {
+        let CypherNode::RegularQuery {
+            single_query,
+            union_all,
+        } = query
+        else {
+            return None;
+        };
+        let mut expected = self.single_query(single_query)?;
+        if union_all.is_empty() {
+            return Some(expected);
+        }
+
+        let mut distinct = false;
+        for union in union_all {
+            let CypherNode::Union {
+                union_all: Some((all, single_query)),
+            } = union.as_ref()
+            else {
+                return None;
+            };
+            distinct |= !all;
+            let arm = self.single_query(single_query)?;
+            if arm.rows.first().map(Vec::len) != expected.rows.first().map(Vec::len) {
+                return None;
+            }
+            expected.rows.extend(arm.rows);
+        }
+        if distinct {
+            expected.rows = distinct_rows(expected.rows, |row| row.clone());
+        }
+        expected.ordered = false;
+        Some(expected)
+    }
+
+    fn single_query(&self, single_query: &CypherNode) -> Option<Expected> {
+        let CypherNode::SingleQuery { part_query } = single_query else {
+            return None;
+        };
+        let mut rows = vec![Row::new()];
+        let single_part = match part_query.as_ref() {
+            CypherNode::MultiPartQuery {
+                multi_part,
+                single_part,
+            } => {
+                for (reading_clauses, updating_clauses, with) in multi_part {
+                    if !updating_clauses.is_empty() {
+                        return None;
+                    }
+                    rows = self.reading_clauses(rows, reading_clauses)?;
+                    let CypherNode::With {
+                        projection_body,
+                        where_clause,
+                    } = with.as_ref()
+                    else {
+                        return None;
+                    };
+                    rows = self.projection(rows, projection_body, false)?.rows;
+                    if let Some(where_clause) = where_clause {
+                        rows = self.filter(rows, where_clause)?;
+                    }
+                }
+                single_part.as_ref()
+            }
+            single_part => single_part,
+        };
+
+        let CypherNode::SinglePartQuery {
+            reading_clauses,
+            updating_clauses,
+            return_clause: Some(return_clause),
+        } = single_part
+        else {
+            return None;
+        };
+        if !updating_clauses.is_empty() {
+            return None;
+        }
+        rows = self.reading_clauses(rows, reading_clauses)?;
+        let CypherNode::Return { projection_body } = return_clause.as_ref() else {
+            return None;
+        };
+        let projected = self.projection(rows, projection_body, true)?;
+        let rows = projected
+            .rows
+            .iter()
+            .map(|row| {
+                projected
+                    .columns
+                    .iter()
+                    .map(|column| row.get(column).cloned().unwrap_or(Constant::Null))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // nodes and relationships are returned in the format of the engine.
+        if rows.iter().flatten().any(Constant::has_entity) {
+            return None;
+        }
+        Some(Expected {
+            rows,
+            ordered: projected.ordered,
+        })
+    }
+
+    fn reading_clauses(&self, mut rows: Vec<Row>, clauses: &[Box<CypherNode>]) -> Option<Vec<Row>> {
+        for clause in clauses {
+            let CypherNode::ReadingClause { reading_clause } = clause.as_ref() else {
+                return None;
+            };
+            rows = match reading_clause.as_ref() {
+                CypherNode::Match {
+                    is_optional,
+                    pattern,
+                    where_clause,
+                } => self.match_clause(rows, *is_optional, pattern, where_clause.as_ref())?,
+                CypherNode::Unwind {
+                    expression,
+                    variable,
+                } => {
+                    let mut unwound = vec![];
+                    for row in rows {
+                        let values = match self.evaluate(expression, &row)? {
+                            Constant::Null => vec![],
+                            Constant::List(list) => list,
+                            value => vec![value],
+                        };
+                        for value in values {
+                            let mut row = row.clone();
+                            row.insert(variable.get_name(), value);
+                            unwound.push(row);
+                        }
+                    }
+                    unwound
+                }
+                _ => return None,
+            };
+            if rows.len() > constants::DEFAULT_REFERENCE_ROWS {
+                return None;
+            }
+        }
+        Some(rows)
+    }
+
+    fn match_clause(
+        &self,
+        rows: Vec<Row>,
+        is_optional: bool,
+        pattern: &CypherNode,
+        where_clause: Option<&Expr>,
+    ) -> Option<Vec<Row>> {
+        let CypherNode::Pattern { pattern_parts } = pattern else {
+            return None;
+        };
+        let mut matched = vec![];
+        for row in rows {
+            let mut matches = self.match_pattern(&row, pattern_parts)?;
+            if let Some(where_clause) = where_clause {
+                matches = self.filter(matches, where_clause)?;
+            }
+            // the variables of the pattern are null without a match.
+            if matches.is_empty() && is_optional {
+                let mut row = row.clone();
+                for variable in pattern_variables(pattern_parts) {
+                    row.entry(variable).or_insert(Constant::Null);
+                }
+                matches.push(row);
+            }
+            matched.extend(matches);
+            if matched.len() > constants::DEFAULT_REFERENCE_ROWS {
+                return None;
+            }
+        }
+        Some(matched)
+    }
+
+    /// The rows of the pattern parts, a relationship is matched once per pattern.
+    fn match_pattern(&self, row: &Row, pattern_parts: &[Box<CypherNode>]) -> Option<Vec<Row>> {
+        let mut rows = vec![(row.clone(), vec![])];
+        for part in pattern_parts {
+            let CypherNode::PatternPart {
+                var: None,
+                selector: None,
+                path_mode: None,
+                pattern_element,
+            } = part.as_ref()
+            else {
+                return None;
+            };
+            let CypherNode::PatternElement {
+                pattern_element: (first, chain),
+                ..
+            } = pattern_element.as_ref()
+            else {
+                return None;
+            };
+            let mut next = vec![];
+            for (row, used) in rows {
+                for vertex in 0..self.graph_data.vertices.len() {
+                    if let Some(row) = self.bind_node(&row, first, vertex)? {
+                        self.extend(row, used.clone(), vertex, chain, &mut next)?;
+                    }
+                }
+            }
+            rows = next;
+        }
+        Some(rows.into_iter().map(|(row, _)| row).collect())
+    }
+
+    /// Match the chain of relationships from `vertex`, `used` are the matched edges.
+    fn extend(
+        &self,
+        row: Row,
+        used: Vec<usize>,
+        vertex: usize,
+        chain: &[(Box<CypherNode>, Box<CypherNode>)],
+        rows: &mut Vec<(Row, Vec<usize>)>,
+    ) -> Option<()> {
+        let Some((relationship, node)) = chain.first() else {
+            rows.push((row, used));
+            return (rows.len() <= constants::DEFAULT_REFERENCE_ROWS).then_some(());
+        };
+        let CypherNode::RelationshipPattern {
+            direction,
+            var,
+            edge_labels,
+            is_range: false,
+            properties,
+            ..
+        } = relationship.as_ref()
+        else {
+            return None;
+        };
+
+        let (outgoing, incoming) = match direction {
+            RelationshipDirection::Right => (true, false),
+            RelationshipDirection::Left => (false, true),
+            RelationshipDirection::Both | RelationshipDirection::None => (true, true),
+        };
+        let mut steps = vec![];
+        if outgoing {
+            steps.extend(
+                self.outgoing[vertex]
+                    .iter()
+                    .map(|edge| (*edge, self.graph_data.edges[*edge].dst)),
+            );
+        }
+        if incoming {
+            for edge in &self.incoming[vertex] {
+                // an undirected self loop matches once or twice, depending on the engine.
+                if outgoing && self.graph_data.edges[*edge].src == vertex {
+                    return None;
+                }
+                steps.push((*edge, self.graph_data.edges[*edge].src));
+            }
+        }
+
+        for (edge, next) in steps {
+            let data = &self.graph_data.edges[edge];
+            if used.contains(&edge)
+                || !edge_labels.is_empty()
+                    && !edge_labels
+                        .iter()
+                        .any(|label| label.label_id == data.label_id)
+                || !property_matches(&data.properties, properties)
+            {
+                continue;
+            }
+            let mut row = row.clone();
+            if let Some(var) = var {
+                match row.get(&var.get_name()) {
+                    Some(Constant::Relationship(bound)) if *bound == edge => {}
+                    Some(Constant::Relationship(_)) => continue,
+                    Some(_) => return None,
+                    None => {
+                        row.insert(var.get_name(), Constant::Relationship(edge));
+                    }
+                }
+            }
+            if let Some(row) = self.bind_node(&row, node, next)? {
+                let mut used = used.clone();
+                used.push(edge);
+                self.extend(row, used, next, &chain[1..], rows)?;
+            }
+        }
+        Some(())
+    }
+
+    /// The row with the vertex bound to the node pattern, `Some(None)` if it does not match.
+    fn bind_node(&self, row: &Row, node: &CypherNode, vertex: usize) -> Option<Option<Row>> {
+        let CypherNode::NodePattern {
+            var,
+            vertex_labels,
+            properties,
+        } = node
+        else {
+            return None;
+        };
+        let data = &self.graph_data.vertices[vertex];
+        if vertex_labels
+            .iter()
+            .any(|label| label.label_id != data.label_id)
+            || !property_matches(&data.properties, properties)
+        {
+            return Some(None);
+        }
+        let mut row = row.clone();
+        if let Some(var) = var {
+            match row.get(&var.get_name()) {
+                Some(Constant::Node(bound)) if *bound == vertex => {}
+                Some(Constant::Node(_)) => return Some(None),
+                Some(_) => return None,
+                None => {
+                    row.insert(var.get_name(), Constant::Node(vertex));
+                }
+            }
+        }
+        Some(Some(row))
+    }
+
+    /// The rows for which the predicate is true.
+    fn filter(&self, rows: Vec<Row>, predicate: &Expr) -> Option<Vec<Row>> {
+        let mut filtered = vec![];
+        for row in rows {
+            match self.evaluate(predicate, &row)? {
+                Constant::Boolean(true) => filtered.push(row),
+                Constant::Boolean(false) | Constant::Null => {}
+                _ => return None,
+            }
+        }
+        Some(filtered)
+    }
+
+    /// The value of an expression of a row, `None` for an error, which the engine may
+    /// not raise if it evaluates the expressions in another order.
+    fn evaluate(&self, expr: &Expr, row: &Row) -> Option<Constant> {
+        evaluate_in(expr, &RowScope { engine: self, row }).ok()
+    }
+
+    /// WITH or RETURN: the projected rows, in order if sorted without ties.
+    fn projection(
+        &self,
+        rows: Vec<Row>,
+        projection_body: &CypherNode,
+        is_return: bool,
+    ) -> Option<Projected> {
+        let CypherNode::ProjectionBody {
+            is_distinct,
+            projection_items,
+            order,
+            skip,
+            limit,
+        } = projection_body
+        else {
+            return None;
+        };
+        let CypherNode::ProjectionItems {
+            is_all,
+            expressions,
+        } = projection_items.as_ref()
+        else {
+            return None;
+        };
+        // the columns of `RETURN *` are ordered by the engine.
+        if *is_all && is_return {
+            return None;
+        }
+        let mut items = vec![];
+        for (expr, alias) in expressions {
+            let name = match (alias, &expr.kind) {
+                (Some(alias), _) => alias.get_name(),
+                (None, crate::common::ExprKind::Variable(variable)) => variable.get_name(),
+                (None, _) if is_return => expr.to_string(),
+                // WITH requires an alias.
+                (None, _) => return None,
+            };
+            items.push((expr, name));
+        }
+        let mut columns = items
+            .iter()
+            .map(|(_, name)| name.clone())
+            .collect::<Vec<_>>();
+        if *is_all {
+            let mut names = rows
+                .first()
+                .map(|row| row.keys().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            names.retain(|name| !columns.contains(name));
+            columns.extend(names);
+        }
+
+        // the projected rows, with the rows they come from when not aggregated.
+        let aggregating = items.iter().any(|(expr, _)| aggregates(expr));
+        let mut projected: Vec<(Row, Option<Row>)> = vec![];
+        if aggregating {
+            if *is_all {
+                return None;
+            }
+            let keys = items
+                .iter()
+                .filter(|(expr, _)| !aggregates(expr))
+                .collect::<Vec<_>>();
+            let mut groups: Vec<(Vec<Constant>, Vec<Row>)> = vec![];
+            for row in rows {
+                let key = keys
+                    .iter()
+                    .map(|(expr, _)| self.evaluate(expr, &row))
+                    .collect::<Option<Vec<_>>>()?;
+                match groups
+                    .iter_mut()
+                    .find(|(group, _)| same_values(group, &key))
+                {
+                    Some((_, group)) => group.push(row),
+                    None => groups.push((key, vec![row])),
+                }
+            }
+            // aggregations without grouping keys have a row for no rows.
+            if groups.is_empty() && keys.is_empty() {
+                groups.push((vec![], vec![]));
+            }
+            for (_, group) in groups {
+                let scope = GroupScope {
+                    engine: self,
+                    rows: &group,
+                };
+                let mut row = Row::new();
+                for (expr, name) in &items {
+                    let value = match aggregates(expr) {
+                        true => evaluate_in(expr, &scope).ok()?,
+                        false => self.evaluate(expr, group.first()?)?,
+                    };
+                    row.insert(name.clone(), value);
+                }
+                projected.push((row, None));
+            }
+        } else {
+            for row in rows {
+                let mut projected_row = if *is_all { row.clone() } else { Row::new() };
+                for (expr, name) in &items {
+                    projected_row.insert(name.clone(), self.evaluate(expr, &row)?);
+                }
+                projected.push((projected_row, Some(row)));
+            }
+        }
+
+        if *is_distinct {
+            projected = distinct_rows(projected, |(row, _)| {
+                columns
+                    .iter()
+                    .map(|column| row.get(column).cloned().unwrap_or(Constant::Null))
+                    .collect()
+            });
+        }
+
+        // the sort keys, empty without ORDER BY: all the rows tie.
+        let mut sort_items = vec![];
+        if let Some(order) = order {
+            let CypherNode::Order { sort_items: items } = order.as_ref() else {
+                return None;
+            };
+            sort_items = items
+                .iter()
+                .map(|(expr, rule)| {
+                    let descending = rule
+                        .as_ref()
+                        .is_some_and(|rule| rule.to_uppercase().starts_with("DESC"));
+                    (expr, descending)
+                })
+                .collect();
+        }
+        let mut keyed = vec![];
+        for (row, source) in projected {
+            let scope = OrderScope {
+                engine: self,
+                row: &row,
+                // after an aggregation or DISTINCT only the projected columns are visible.
+                source: source.as_ref().filter(|_| !*is_distinct),
+                items: &items,
+            };
+            let key = sort_items
+                .iter()
+                .map(|(expr, _)| evaluate_in(expr, &scope).ok())
+                .collect::<Option<Vec<_>>>()?;
+            keyed.push((key, row));
+        }
+        let mut failed = false;
+        keyed.sort_by(|(lhs, _), (rhs, _)| {
+            for ((lhs, rhs), (_, descending)) in lhs.iter().zip(rhs).zip(&sort_items) {
+                match order_values(lhs, rhs) {
+                    Some(Ordering::Equal) => {}
+                    Some(ordering) if *descending => return ordering.reverse(),
+                    Some(ordering) => return ordering,
+                    None => failed = true,
+                }
+            }
+            Ordering::Equal
+        });
+        if failed {
+            return None;
+        }
+        let ties = |idx: usize| {
+            keyed[idx - 1]
+                .0
+                .iter()
+                .zip(&keyed[idx].0)
+                .all(|(lhs, rhs)| order_values(lhs, rhs) == Some(Ordering::Equal))
+        };
+
+        // SKIP and LIMIT must not cut between rows of the same sort key.
+        let bound = |expr: &Option<Expr>| match expr.as_ref().map(evaluate) {
+            None => Some(None),
+            Some(Ok(Constant::Integer(i))) if i >= 0 => Some(Some(i as usize)),
+            Some(_) => None,
+        };
+        let len = keyed.len();
+        let start = bound(skip)?.unwrap_or(0).min(len);
+        let end = match bound(limit)? {
+            Some(limit) => start.saturating_add(limit).min(len),
+            None => len,
+        };
+        if [start, end]
+            .iter()
+            .any(|cut| 0 < *cut && *cut < len && ties(*cut))
+        {
+            return None;
+        }
+        let keyed = keyed
+            .into_iter()
+            .skip(start)
+            .take(end - start)
+            .collect::<Vec<_>>();
+        let ordered = order.is_some()
+            && (1..keyed.len()).all(|idx| {
+                !keyed[idx - 1]
+                    .0
+                    .iter()
+                    .zip(&keyed[idx].0)
+                    .all(|(lhs, rhs)| order_values(lhs, rhs) == Some(Ordering::Equal))
+            });
+
+        Some(Projected {
+            rows: keyed.into_iter().map(|(_, row)| row).collect(),
+            columns,
+            ordered,
+        })
+    }
+
+    fn property(&self, entity: &Constant, name: &str) -> Eval {
+        let properties = match entity {
+            Constant::Node(vertex) => &self.graph_data.vertices[*vertex].properties,
+            Constant::Relationship(edge) => &self.graph_data.edges[*edge].properties,
+            _ => return Err(EvalError::Unsupported),
+        };
+        Ok(properties
+            .iter()
+            .find(|(property, _)| property.name == name)
+            .map_or(Constant::Null, |(_, value)| constant(value)))
+    }
+
+    fn has_label(&self, entity: &Constant, label: &Label) -> Eval {
+        match entity {
+            Constant::Node(vertex) => Ok(Constant::Boolean(
+                self.graph_data.vertices[*vertex].label_id == label.label_id,
+            )),
+            _ => Err(EvalError::Unsupported),
+        }
+    }
+}
+
+struct Projected {
+    rows: Vec<Row>,
+    columns: Vec<String>,
+    ordered: bool,
+}
+
+/// The variables of a row.
+struct RowScope<'a> {
+    engine: &'a ReferenceEngine,
+    row: &'a Row,
+}
+
+impl Scope for RowScope<'_> {
+    fn variable(&self, name: &str) -> Eval {
+        self.row.get(name).cloned().ok_or(EvalError::Unsupported)
+    }
+
+    fn property(&self, entity: &Constant, name: &str) -> Eval {
+        self.engine.property(entity, name)
+    }
+
+    fn has_label(&self, entity: &Constant, label: &Label) -> Eval {
+        self.engine.has_label(entity, label)
+    }
+}
+
+/// The aggregations of the rows of a group.
+struct GroupScope<'a> {
+    engine: &'a ReferenceEngine,
+    rows: &'a [Row],
+}
+
+impl Scope for GroupScope<'_> {
+    fn expression(&self, expr: &Expr) -> Option<Eval> {
+        let aggregation = aggregation(expr)?;
+        let Some(argument) = aggregation.argument else {
+            return Some(Ok(Constant::Integer(self.rows.len() as i64)));
+        };
+        let mut values = vec![];
+        for row in self.rows {
+            match evaluate_in(
+                argument,
+                &RowScope {
+                    engine: self.engine,
+                    row,
+                },
+            ) {
+                Ok(Constant::Null) => {}
+                Ok(value) => values.push(value),
+                Err(_) => return Some(Err(EvalError::Unsupported)),
+            }
+        }
+        if aggregation.distinct {
+            values = distinct_rows(values, |value| vec![value.clone()]);
+        }
+        Some(aggregate(&aggregation.function, values).ok_or(EvalError::Unsupported))
+    }
+}
+
+/// The projected columns and, unless aggregated or distinct, the variables of the row
+/// they come from.
+struct OrderScope<'a> {
+    engine: &'a ReferenceEngine,
+    row: &'a Row,
+    source: Option<&'a Row>,
+    items: &'a [(&'a Expr, String)],
+}
+
+impl Scope for OrderScope<'_> {
+    fn variable(&self, name: &str) -> Eval {
+        self.row
+            .get(name)
+            .or_else(|| self.source.and_then(|source| source.get(name)))
+            .cloned()
+            .ok_or(EvalError::Unsupported)
+    }
+
+    fn property(&self, entity: &Constant, name: &str) -> Eval {
+        self.engine.property(entity, name)
+    }
+
+    fn has_label(&self, entity: &Constant, label: &Label) -> Eval {
+        self.engine.has_label(entity, label)
+    }
+
+    /// A projected expression, e.g. `ORDER BY v0.name` after `RETURN v0.name`.
+    fn expression(&self, expr: &Expr) -> Option<Eval> {
+        let text = expr.to_string();
+        self.items
+            .iter()
+            .find(|(item, _)| item.to_string() == text)
+            .and_then(|(_, name)| self.row.get(name))
+            .map(|value| Ok(value.clone()))
+    }
+}
+
+/// The value of an aggregation of the non null values, `None` if it depends on the
+/// order of the rows or on the engine.
+fn aggregate(function: &str, values: Vec<Constant>) -> Option<Constant> {
+    match function {
+        "count" => Some(Constant::Integer(values.len() as i64)),
+        "sum" => {
+            let mut sum = Constant::Integer(0);
+            for value in values {
+                sum = match (sum, value) {
+                    (Constant::Integer(lhs), Constant::Integer(rhs)) => {
+                        Constant::Integer(lhs.checked_add(rhs)?)
+                    }
+                    (Constant::Integer(lhs), Constant::Float(rhs)) => {
+                        Constant::Float(lhs as f64 + rhs)
+                    }
+                    (Constant::Float(lhs), Constant::Integer(rhs)) => {
+                        Constant::Float(lhs + rhs as f64)
+                    }
+                    (Constant::Float(lhs), Constant::Float(rhs)) => Constant::Float(lhs + rhs),
+                    _ => return None,
+                };
+            }
+            Some(sum)
+        }
+        "avg" => {
+            if values.is_empty() {
+                return Some(Constant::Null);
+            }
+            let mut sum = 0.0;
+            for value in &values {
+                sum += match value {
+                    Constant::Integer(i) => *i as f64,
+                    Constant::Float(f) => *f,
+                    _ => return None,
+                };
+            }
+            Some(Constant::Float(sum / values.len() as f64))
+        }
+        "min" | "max" => {
+            let mut result: Option<Constant> = None;
+            for value in values {
+                // the order of values of different types is engine specific.
+                if !matches!(
+                    value,
+                    Constant::Integer(_)
+                        | Constant::Float(_)
+                        | Constant::String(_)
+                        | Constant::Boolean(_)
+                ) {
+                    return None;
+                }
+                result = match result {
+                    None => Some(value),
+                    Some(current) => {
+                        let ordering = order_values(&value, &current)?;
+                        if rank(&value) != rank(&current) {
+                            return None;
+                        }
+                        let better = match function {
+                            "min" => ordering == Ordering::Less,
+                            _ => ordering == Ordering::Greater,
+                        };
+                        Some(if better { value } else { current })
+                    }
+                };
+            }
+            Some(result.unwrap_or(Constant::Null))
+        }
+        // the order of the collected values is the order of the rows.
+        _ => None,
+    }
+}
+
+/// The rank of the type of a value in the order of ORDER BY, null last.
+fn rank(value: &Constant) -> u8 {
+    match value {
+        Constant::Map(_) => 0,
+        Constant::Node(_) => 1,
+        Constant::Relationship(_) => 2,
+        Constant::List(_) => 3,
+        Constant::String(_) => 4,
+        Constant::Boolean(_) => 5,
+        Constant::Integer(_) | Constant::Float(_) => 6,
+        Constant::Null => 7,
+    }
+}
+
+/// The order of ORDER BY, `None` where it is engine specific: maps, entities and NaN.
+fn order_values(lhs: &Constant, rhs: &Constant) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Constant::Integer(lhs), Constant::Integer(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::String(lhs), Constant::String(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Boolean(lhs), Constant::Boolean(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Null, Constant::Null) => Some(Ordering::Equal),
+        (Constant::List(lhs), Constant::List(rhs)) => {
+            for (lhs, rhs) in lhs.iter().zip(rhs) {
+                match order_values(lhs, rhs)? {
+                    Ordering::Equal => {}
+                    ordering => return Some(ordering),
+                }
+            }
+            Some(lhs.len().cmp(&rhs.len()))
+        }
+        (Constant::Map(_) | Constant::Node(_) | Constant::Relationship(_), _)
+        | (_, Constant::Map(_) | Constant::Node(_) | Constant::Relationship(_)) => None,
+        (Constant::Integer(_) | Constant::Float(_), Constant::Integer(_) | Constant::Float(_)) => {
+            let number = |value: &Constant| match value {
+                Constant::Integer(i) => *i as f64,
+                Constant::Float(f) => *f,
+                _ => f64::NAN,
+            };
+            number(lhs).partial_cmp(&number(rhs))
+        }
+        (lhs, rhs) => Some(rank(lhs).cmp(&rank(rhs))),
+    }
+}
+
+/// The same values, null equal to null as in grouping keys and DISTINCT.
+fn same_values(lhs: &[Constant], rhs: &[Constant]) -> bool {
+    lhs.len() == rhs.len()
+        && lhs.iter().zip(rhs).all(|(lhs, rhs)| match (lhs, rhs) {
+            (Constant::Null, Constant::Null) => true,
+            (Constant::List(lhs), Constant::List(rhs)) => same_values(lhs, rhs),
+            (lhs, rhs) => equals(lhs, rhs) == Some(true),
+        })
+}
+
+/// The first of the items with the same values.
+fn distinct_rows<T>(items: Vec<T>, values: impl Fn(&T) -> Vec<Constant>) -> Vec<T> {
+    let mut seen: Vec<Vec<Constant>> = vec![];
+    let mut distinct = vec![];
+    for item in items {
+        let key = values(&item);
+        if !seen.iter().any(|seen| same_values(seen, &key)) {
+            seen.push(key);
+            distinct.push(item);
+        }
+    }
+    distinct
+}
+
+/// The property of the pattern has the value, e.g. `(n {id: 1})`.
+fn property_matches(
+    properties: &[(Property, FieldValue)],
+    pattern: &Option<(Property, FieldValue)>,
+) -> bool {
+    let Some((property, value)) = pattern else {
+        return true;
+    };
+    properties
+        .iter()
+        .find(|(stored, _)| stored.name == property.name)
+        .is_some_and(|(_, stored)| equals(&constant(stored), &constant(value)) == Some(true))
+}
+
+/// The variables bound by the pattern.
+fn pattern_variables(pattern_parts: &[Box<CypherNode>]) -> Vec<String> {
+    let mut variables = vec![];
+    for part in pattern_parts {
+        let CypherNode::PatternPart {
+            pattern_element, ..
+        } = part.as_ref()
+        else {
+            continue;
+        };
+        let CypherNode::PatternElement {
+            pattern_element: (first, chain),
+            ..
+        } = pattern_element.as_ref()
+        else {
+            continue;
+        };
+        let nodes = std::iter::once(first).chain(
+            chain
+                .iter()
+                .flat_map(|(relationship, node)| [relationship, node]),
+        );
+        for node in nodes {
+            if let CypherNode::NodePattern { var: Some(var), .. }
+            | CypherNode::RelationshipPattern { var: Some(var), .. } = node.as_ref()
+            {
+                variables.push(var.get_name());
+            }
+        }
+    }
+    variables
+}
+
+/// The value of a populated property, as the target reads the literal written by
+/// `DataGenerator::statements`.
+fn constant(value: &FieldValue) -> Constant {
+    match value {
+        FieldValue::Null => Constant::Null,
+        FieldValue::Boolean(b) => Constant::Boolean(*b),
+        FieldValue::Int32(i) => Constant::Integer(*i as i64),
+        FieldValue::Int64(i) => Constant::Integer(*i),
+        // temporal values are returned as their ISO 8601 text.
+        FieldValue::Date(_) | FieldValue::Datetime(_) => {
+            Constant::String(value.temporal().unwrap_or_default().1)
+        }
+        // the shortest text of the f32, read as a double.
+        FieldValue::Float(f) => Constant::Float(f.to_string().parse().unwrap_or(*f as f64)),
+        FieldValue::Double(d) => Constant::Float(*d),
+        FieldValue::String(s) => Constant::String(s.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Expected, ReferenceEngine};
+    use crate::{
+        ast::{Constant, CypherNode},
+        common::{DataType, Expr, ExprKind, FieldValue, Literal, RelationshipDirection, Variable},
+        data::{EdgeData, GraphData, VertexData},
+        meta::{testing::property, Label, LabelKind},
+    };
+
+    fn person() -> Label {
+        Label {
+            label_name: "Person".to_string(),
+            label_id: 1,
+            kind: LabelKind::Vertex,
+            properties: vec![property("id", 0, DataType::Int32)],
+        }
+    }
+
+    /// Persons 0, 1 and 2, 0 knows 1 and 2, 1 knows 2.
+    fn engine() -> ReferenceEngine {
+        let vertices = (0..3)
+            .map(|id| VertexData {
+                label_id: 1,
+                properties: vec![(property("id", 0, DataType::Int32), FieldValue::Int32(id))],
+            })
+            .collect();
+        let edges = [(0, 1), (0, 2), (1, 2)]
+            .into_iter()
+            .map(|(src, dst)| EdgeData {
+                label_id: 2,
+                src,
+                dst,
+                properties: vec![],
+            })
+            .collect();
+        ReferenceEngine::new(GraphData { vertices, edges })
+    }
+
+    fn var(name: &str) -> Expr {
+        ExprKind::Variable(Variable::new(name.to_string())).into()
+    }
+
+    fn id(name: &str) -> Expr {
+        ExprKind::Property(Box::new(var(name)), property("id", 0, DataType::Int32)).into()
+    }
+
+    fn node(name: &str, id: Option<i32>) -> Box<CypherNode> {
+        Box::new(CypherNode::NodePattern {
+            var: Some(Variable::new(name.to_string())),
+            vertex_labels: vec![person()],
+            properties: id.map(|id| (property("id", 0, DataType::Int32), FieldValue::Int32(id))),
+        })
+    }
+
+    fn knows(direction: RelationshipDirection) -> Box<CypherNode> {
+        Box::new(CypherNode::RelationshipPattern {
+            direction,
+            var: None,
+            edge_labels: vec![],
+            is_range: false,
+            range: (None, None),
+            properties: None,
+        })
+    }
+
+    fn matching(is_optional: bool, pattern_element: CypherNode) -> Box<CypherNode> {
+        let pattern_part = CypherNode::PatternPart {
+            var: None,
+            selector: None,
+            path_mode: None,
+            pattern_element: Box::new(pattern_element),
+        };
+        Box::new(CypherNode::ReadingClause {
+            reading_clause: Box::new(CypherNode::Match {
+                is_optional,
+                pattern: Box::new(CypherNode::Pattern {
+                    pattern_parts: vec![Box::new(pattern_part)],
+                }),
+                where_clause: None,
+            }),
+        })
+    }
+
+    /// `MATCH (v0:Person)-[]->(v1:Person)`
+    fn knowing() -> Box<CypherNode> {
+        matching(
+            false,
+            CypherNode::PatternElement {
+                parenthesis: false,
+                pattern_element: (
+                    node("v0", None),
+                    vec![(knows(RelationshipDirection::Right), node("v1", None))],
+                ),
+            },
+        )
+    }
+
+    fn query(
+        reading_clauses: Vec<Box<CypherNode>>,
+        items: Vec<(Expr, &str)>,
+        order: Option<Expr>,
+        limit: Option<u64>,
+    ) -> CypherNode {
+        let projection_body = CypherNode::ProjectionBody {
+            is_distinct: false,
+            projection_items: Box::new(CypherNode::ProjectionItems {
+                is_all: false,
+                expressions: items
+                    .into_iter()
+                    .map(|(expr, alias)| (expr, Some(Variable::new(alias.to_string()))))
+                    .collect(),
+            }),
+            order: order.map(|expr| {
+                Box::new(CypherNode::Order {
+                    sort_items: vec![(expr, None)],
+                })
+            }),
+            skip: None,
+            limit: limit.map(|limit| ExprKind::Lit(Literal::Integer(limit)).into()),
+        };
+        CypherNode::RegularQuery {
+            single_query: Box::new(CypherNode::SingleQuery {
+                part_query: Box::new(CypherNode::SinglePartQuery {
+                    reading_clauses,
+                    updating_clauses: vec![],
+                    return_clause: Some(Box::new(CypherNode::Return {
+                        projection_body: Box::new(projection_body),
+                    })),
+                }),
+            }),
+            union_all: vec![],
+        }
+    }
+
+    fn rows(rows: &[&[i64]]) -> Vec<Vec<Constant>> {
+        rows.iter()
+            .map(|row| row.iter().map(|i| Constant::Integer(*i)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_execute() {
+        let engine = engine();
+        let count = || Expr::from(ExprKind::Lit(Literal::String("COUNT (*)".to_string())));
+
+        let pairs = query(
+            vec![knowing()],
+            vec![(id("v0"), "a"), (id("v1"), "b")],
+            None,
+            None,
+        );
+        assert_eq!(
+            engine.execute(&pairs),
+            Some(Expected {
+                rows: rows(&[&[0, 1], &[0, 2], &[1, 2]]),
+                ordered: false,
+            })
+        );
+
+        // grouped by `a`, sorted without ties.
+        let counts = query(
+            vec![knowing()],
+            vec![(id("v0"), "a"), (count(), "c")],
+            Some(var("a")),
+            None,
+        );
+        assert_eq!(
+            engine.execute(&counts),
+            Some(Expected {
+                rows: rows(&[&[0, 2], &[1, 1]]),
+                ordered: true,
+            })
+        );
+        // a count without rows.
+        let none = query(
+            vec![matching(
+                false,
+                CypherNode::PatternElement {
+                    parenthesis: false,
+                    pattern_element: (node("v0", Some(5)), vec![]),
+                },
+            )],
+            vec![(count(), "c")],
+            None,
+            None,
+        );
+        assert_eq!(engine.execute(&none).unwrap().rows, rows(&[&[0]]));
+
+        // the limit cuts between rows of different `v0.id`, not within a tie or
+        // without ORDER BY.
+        let sorted = query(
+            vec![knowing()],
+            vec![(id("v1"), "b")],
+            Some(id("v0")),
+            Some(1),
+        );
+        assert_eq!(engine.execute(&sorted), None);
+        let sorted = query(
+            vec![knowing()],
+            vec![(id("v0"), "a")],
+            Some(id("v0")),
+            Some(2),
+        );
+        assert_eq!(
+            engine.execute(&sorted),
+            Some(Expected {
+                rows: rows(&[&[0], &[0]]),
+                ordered: false,
+            })
+        );
+        let limited = query(vec![knowing()], vec![(id("v0"), "a")], None, Some(2));
+        assert_eq!(engine.execute(&limited), None);
+
+        // the variables of an unmatched optional pattern are null.
+        let optional = query(
+            vec![matching(
+                true,
+                CypherNode::PatternElement {
+                    parenthesis: false,
+                    pattern_element: (
+                        node("v0", Some(2)),
+                        vec![(knows(RelationshipDirection::Right), node("v1", None))],
+                    ),
+                },
+            )],
+            vec![(id("v0"), "a"), (var("v1"), "b")],
+            None,
+            None,
+        );
+        assert_eq!(
+            engine.execute(&optional).unwrap().rows,
+            vec![vec![Constant::Null, Constant::Null]]
+        );
+
+        // nodes are returned in the format of the engine.
+        let nodes = query(vec![knowing()], vec![(var("v0"), "a")], None, None);
+        assert_eq!(engine.execute(&nodes), None);
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let expected = Expected {
+            rows: rows(&[&[1, 2], &[3, 4]]),
+            ordered: false,
+        };
+        assert_eq!(
+            expected.mismatch(&[vec![json!(3), json!(4)], vec![json!(1), json!(2)]]),
+            None
+        );
+        assert!(expected.mismatch(&[vec![json!(1), json!(2)]]).is_some());
+        // an integer is not a float of the same value.
+        assert!(expected
+            .mismatch(&[vec![json!(3.0), json!(4)], vec![json!(1), json!(2)]])
+            .is_some());
+        assert!(expected
+            .mismatch(&[vec![json!(1), json!(2)], vec![json!(1), json!(2)]])
+            .is_some());
+
+        let ordered = Expected {
+            ordered: true,
+            ..expected
+        };
+        assert_eq!(
+            ordered.mismatch(&[vec![json!(1), json!(2)], vec![json!(3), json!(4)]]),
+            None
+        );
+        assert!(ordered
+            .mismatch(&[vec![json!(3), json!(4)], vec![json!(1), json!(2)]])
+            .is_some());
+    }
+}
@@ -1,6 +1,11 @@
-use rpc::atlas::atlas_graph_client::AtlasGraphClient;
+use rpc::atlas::{
+    atlas_graph_client::AtlasGraphClient, BeginRequest, CommitRequest, ExecRequest,
+    RollbackRequest, VersionRequest,
+};
 use serde::{Deserialize, Serialize};
-use tonic::transport::Channel;
+use tonic::{transport::Channel, Code, Request};
+
+use crate::common::Diagnostic;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AtlasConfig {
@@ -18,6 +23,8 @@ pub struct AtlasConnection {
     // pub client: RefCell<AtlasGraphClient<Channel>>,
     pub client: AtlasGraphClient<Channel>,
     pub session_id: Vec<u8>,
+    // the open transaction, statements are auto-committed without one.
+    pub transaction_id: Option<Vec<u8>>,
     pub config: AtlasConfig,
 }
 
@@ -39,9 +46,119 @@ impl AtlasConnection {
         Self {
             client,
             session_id,
+            transaction_id: None,
             config,
         }
     }
+
+    /// Name and address of the backend, e.g. `atlas://127.0.0.1:8080`.
+    pub fn backend(&self) -> String {
+        format!("atlas://{}", self.config.address)
+    }
+
+    pub async fn version(&mut self) -> Result<String, Diagnostic> {
+        let response = self
+            .client
+            .version(Request::new(VersionRequest {
+                session_id: self.session_id.clone(),
+            }))
+            .await
+            .map_err(|status| Diagnostic::error("Version RPC failed", status.to_string()))?;
+
+        Ok(response.into_inner().version)
+    }
+
+    /// Execute a statement, return the result in JSON format.
+    pub async fn exec(&mut self, statement: &str) -> Result<String, Diagnostic> {
+        let response = self
+            .client
+            .exec(Request::new(ExecRequest {
+                session_id: self.session_id.clone(),
+                statement: statement.to_string(),
+                transaction_id: self.transaction_id.clone().unwrap_or_default(),
+            }))
+            .await
+            .map_err(|status| Diagnostic::error("Exec RPC failed", status.to_string()))?;
+
+        Ok(response.into_inner().result)
+    }
+
+    /// Open a transaction, the following statements run in it until commit or rollback.
+    pub async fn begin(&mut self) -> Result<(), Diagnostic> {
+        if self.transaction_id.is_some() {
+            return Err(Diagnostic::bug("Nested transaction", None));
+        }
+        let response = self
+            .client
+            .begin(Request::new(BeginRequest {
+                session_id: self.session_id.clone(),
+            }))
+            .await
+            .map_err(|status| rpc_error("Begin", status))?;
+
+        self.transaction_id = Some(response.into_inner().transaction_id);
+        Ok(())
+    }
+
+    /// Whether the backend implements `Begin`, `Commit` and `Rollback`, probed by two empty
+    /// transactions.
+    pub async fn has_transactions(&mut self) -> Result<bool, Diagnostic> {
+        let unimplemented = |err: &Diagnostic| err.message == "Unimplemented RPC";
+        for commit in [true, false] {
+            let probe = match self.begin().await {
+                Ok(()) if commit => self.commit().await,
+                Ok(()) => self.rollback().await,
+                Err(err) => Err(err),
+            };
+            match probe {
+                Err(err) if unimplemented(&err) => {
+                    self.transaction_id = None;
+                    return Ok(false);
+                }
+                probe => probe?,
+            }
+        }
+        Ok(true)
+    }
+
+    pub async fn commit(&mut self) -> Result<(), Diagnostic> {
+        let transaction_id = self
+            .transaction_id
+            .take()
+            .ok_or_else(|| Diagnostic::bug("No open transaction", None))?;
+        self.client
+            .commit(Request::new(CommitRequest {
+                session_id: self.session_id.clone(),
+                transaction_id,
+            }))
+            .await
+            .map_err(|status| rpc_error("Commit", status))?;
+        Ok(())
+    }
+
+    pub async fn rollback(&mut self) -> Result<(), Diagnostic> {
+        let transaction_id = self
+            .transaction_id
+            .take()
+            .ok_or_else(|| Diagnostic::bug("No open transaction", None))?;
+        self.client
+            .rollback(Request::new(RollbackRequest {
+                session_id: self.session_id.clone(),
+                transaction_id,
+            }))
+            .await
+            .map_err(|status| rpc_error("Rollback", status))?;
+        Ok(())
+    }
+}
+
+/// The error of a failed RPC, `Unimplemented RPC` if the backend does not implement it.
+fn rpc_error(rpc: &str, status: tonic::Status) -> Diagnostic {
+    if status.code() == Code::Unimplemented {
+        Diagnostic::error("Unimplemented RPC", format!("{}: {}", rpc, status))
+    } else {
+        Diagnostic::error(format!("{} RPC failed", rpc), status.to_string())
+    }
 }
 
 #[cfg(test)]
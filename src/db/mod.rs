@@ -1,3 +1,7 @@
 mod atlas;
+mod plan;
+mod result;
 
 pub use atlas::{AtlasConfig, AtlasConnection};
+pub use plan::Plan;
+pub use result::QueryResult;
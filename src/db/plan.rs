@@ -0,0 +1,141 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Physical operator tree of a query, returned for `EXPLAIN` and `PROFILE`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    // e.g. `NodeByLabelScan`, without the `@neo4j` suffix.
+    pub operator: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Plan>,
+}
+
+impl Plan {
+    /// The plan of a statement result: a tree of `{"operatorType": .., "children": [..]}`
+    /// under `plan` or `profile`, possibly wrapped in `{"root": ..}`.
+    pub fn parse(result: &Value) -> Option<Plan> {
+        let plan = result.get("plan").or_else(|| result.get("profile"))?;
+        Plan::from_json(plan.get("root").unwrap_or(plan))
+    }
+
+    fn from_json(value: &Value) -> Option<Plan> {
+        let operator = ["operatorType", "operator", "name"]
+            .iter()
+            .find_map(|key| value.get(key).and_then(|operator| operator.as_str()))?;
+        let children = value
+            .get("children")
+            .and_then(|children| children.as_array())
+            .map(|children| children.iter().filter_map(Plan::from_json).collect())
+            .unwrap_or_default();
+        Some(Plan {
+            operator: operator_name(operator)?,
+            children,
+        })
+    }
+
+    /// The plan of the rows of a `QUERY PLAN` column, one operator per line, e.g.
+    /// ` * ScanAllByLabel (n :Person)`. The branches drawn with `|\` are not kept, the
+    /// plan is read as a chain from the first line.
+    pub fn from_lines(lines: &[&Value]) -> Option<Plan> {
+        let operators = lines
+            .iter()
+            .filter_map(|line| line.as_str())
+            .filter_map(|line| {
+                let line =
+                    line.trim_start_matches(|c: char| c.is_whitespace() || "|\\/*".contains(c));
+                operator_name(
+                    line.split(|c: char| c.is_whitespace() || c == '{' || c == '(')
+                        .next()?,
+                )
+            })
+            .collect::<Vec<_>>();
+        operators.into_iter().rev().fold(None, |child, operator| {
+            Some(Plan {
+                operator,
+                children: child.into_iter().collect(),
+            })
+        })
+    }
+
+    /// The distinct operators of the tree.
+    pub fn operators(&self) -> BTreeSet<&str> {
+        let mut operators = BTreeSet::from([self.operator.as_str()]);
+        for child in &self.children {
+            operators.extend(child.operators());
+        }
+        operators
+    }
+}
+
+impl Display for Plan {
+    /// The shape of the tree, e.g. `ProduceResults(Filter(AllNodesScan))`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.operator)?;
+        if !self.children.is_empty() {
+            let children = self
+                .children
+                .iter()
+                .map(|child| child.to_string())
+                .collect::<Vec<_>>();
+            f.write_fmt(format_args!("({})", children.join(", ")))?;
+        }
+        Ok(())
+    }
+}
+
+/// The operator without the planner suffix, e.g. `Filter@neo4j`.
+fn operator_name(operator: &str) -> Option<String> {
+    let operator = operator.split('@').next()?.trim();
+    (!operator.is_empty()).then(|| operator.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::Plan;
+
+    #[test]
+    fn test_plan() {
+        let result = json!({
+            "columns": [],
+            "data": [],
+            "plan": {"root": {
+                "operatorType": "ProduceResults@neo4j",
+                "children": [{
+                    "operatorType": "CartesianProduct@neo4j",
+                    "children": [
+                        {"operatorType": "NodeByLabelScan@neo4j", "children": []},
+                        {"operatorType": "AllNodesScan@neo4j"},
+                    ],
+                }],
+            }},
+        });
+        let plan = Plan::parse(&result).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            "ProduceResults(CartesianProduct(NodeByLabelScan, AllNodesScan))"
+        );
+        assert_eq!(plan.operators().len(), 4);
+        assert_eq!(
+            Plan::parse(&json!({"profile": {"operator": "Produce"}})),
+            Some(Plan {
+                operator: "Produce".to_string(),
+                children: vec![],
+            })
+        );
+        assert_eq!(Plan::parse(&json!({"columns": ["a"], "data": []})), None);
+
+        let lines = [
+            json!(" * Produce {n}"),
+            json!(" * Filter"),
+            json!(" * ScanAllByLabel (n :Person)"),
+            json!(" * Once"),
+        ];
+        let plan = Plan::from_lines(&lines.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(plan.to_string(), "Produce(Filter(ScanAllByLabel(Once)))");
+        assert_eq!(Plan::from_lines(&[]), None);
+    }
+}
@@ -0,0 +1,193 @@
+use serde_json::Value;
+
+use super::Plan;
+use crate::common::Diagnostic;
+
+/// Result of a statement, in the JSON format returned by `ExecResponse`:
+/// `{"results": [{"columns": [...], "data": [{"row": [...]}]}], "errors": [...]}`.
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub errors: Vec<Value>,
+    // returned for `EXPLAIN` and `PROFILE`.
+    pub plan: Option<Plan>,
+}
+
+impl QueryResult {
+    pub fn parse(result: &str) -> Result<Self, Diagnostic> {
+        let value: Value = serde_json::from_str(result)
+            .map_err(|err| Diagnostic::bug("Invalid result JSON", err.to_string()))?;
+        let errors = value
+            .get("errors")
+            .and_then(|errors| errors.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        // only the first statement result.
+        let first = value
+            .get("results")
+            .and_then(|results| results.as_array())
+            .and_then(|results| results.first());
+        let columns = first
+            .and_then(|result| result.get("columns"))
+            .and_then(|columns| columns.as_array())
+            .map(|columns| {
+                columns
+                    .iter()
+                    .map(|column| column.as_str().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let rows = first
+            .and_then(|result| result.get("data"))
+            .and_then(|data| data.as_array())
+            .map(|data| {
+                data.iter()
+                    .map(|row| {
+                        // `{"row": [...]}` or a plain array.
+                        row.get("row")
+                            .unwrap_or(row)
+                            .as_array()
+                            .cloned()
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut result = QueryResult {
+            columns,
+            rows,
+            errors,
+            plan: None,
+        };
+        result.plan = first
+            .and_then(Plan::parse)
+            .or_else(|| Plan::from_lines(&result.column("QUERY PLAN")));
+        Ok(result)
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// How the rows of the other result differ from these ones in any order, `None` if
+    /// they agree up to the rounding of floats, the column names aside.
+    pub fn row_mismatch(&self, other: &QueryResult) -> Option<String> {
+        if self.columns.len() != other.columns.len() {
+            return Some(format!(
+                "{} columns, got {} columns",
+                self.columns.len(),
+                other.columns.len()
+            ));
+        }
+        let same_row = |lhs: &Vec<Value>, rhs: &Vec<Value>| {
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| same_value(lhs, rhs))
+        };
+        // every row matches a row of its own.
+        let mut matched = vec![false; other.rows.len()];
+        let same = self.rows.len() == other.rows.len()
+            && self.rows.iter().all(|row| {
+                let idx = (0..other.rows.len())
+                    .find(|idx| !matched[*idx] && same_row(row, &other.rows[*idx]));
+                idx.map(|idx| matched[idx] = true).is_some()
+            });
+        if same {
+            return None;
+        }
+        Some(format!(
+            "expected {} rows: {}, got {} rows: {}",
+            self.rows.len(),
+            Value::from(self.rows.clone()),
+            other.rows.len(),
+            Value::from(other.rows.clone())
+        ))
+    }
+
+    /// Values of a column, empty if the column does not exist.
+    pub fn column(&self, name: &str) -> Vec<&Value> {
+        match self.columns.iter().position(|column| column == name) {
+            Some(idx) => self.rows.iter().filter_map(|row| row.get(idx)).collect(),
+            None => vec![],
+        }
+    }
+}
+
+/// Equal values, floats up to their rounding.
+fn same_value(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Number(lhs), Value::Number(rhs)) if lhs.is_f64() || rhs.is_f64() => {
+            match (lhs.as_f64(), rhs.as_f64()) {
+                (Some(lhs), Some(rhs)) if lhs.is_finite() && rhs.is_finite() => {
+                    (lhs - rhs).abs() <= 1e-9 * lhs.abs().max(rhs.abs()).max(1.0)
+                }
+                (lhs, rhs) => lhs == rhs,
+            }
+        }
+        (Value::Array(lhs), Value::Array(rhs)) => {
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| same_value(lhs, rhs))
+        }
+        (Value::Object(lhs), Value::Object(rhs)) => {
+            lhs.len() == rhs.len()
+                && lhs
+                    .iter()
+                    .all(|(key, lhs)| rhs.get(key).is_some_and(|rhs| same_value(lhs, rhs)))
+        }
+        _ => lhs == rhs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryResult;
+
+    #[test]
+    fn test_parse() {
+        let result = QueryResult::parse(r#"{"results": [], "errors": []}"#).unwrap();
+        assert!(result.is_ok());
+
+        let result = QueryResult::parse(
+            r#"{"errors": [{"level": "Error", "message": "bad", "detail": ""}]}"#,
+        )
+        .unwrap();
+        assert_eq!(result.errors.len(), 1);
+
+        let result = QueryResult::parse(
+            r#"{"results": [{"columns": ["a", "b"], "data": [{"row": [1, "x"]}, [2, "y"]]}], "errors": []}"#,
+        )
+        .unwrap();
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.column("b"), vec!["x", "y"]);
+        assert!(result.column("c").is_empty());
+
+        let reordered = QueryResult::parse(
+            r#"{"results": [{"columns": ["b", "a"], "data": [[2, "y"], [1, "x"]]}], "errors": []}"#,
+        )
+        .unwrap();
+        assert_eq!(result.row_mismatch(&reordered), None);
+        let missing = QueryResult::parse(
+            r#"{"results": [{"columns": ["a", "b"], "data": [[1, "x"], [1, "x"]]}], "errors": []}"#,
+        )
+        .unwrap();
+        assert!(result.row_mismatch(&missing).is_some());
+        let sum = QueryResult::parse(
+            r#"{"results": [{"columns": ["a"], "data": [[0.30000000000000004]]}], "errors": []}"#,
+        )
+        .unwrap();
+        let rounded = QueryResult::parse(
+            r#"{"results": [{"columns": ["a"], "data": [[0.3]]}], "errors": []}"#,
+        )
+        .unwrap();
+        assert_eq!(sum.row_mismatch(&rounded), None);
+        assert_eq!(result.plan, None);
+
+        let explained = QueryResult::parse(
+            r#"{"results": [{"columns": ["QUERY PLAN"], "data": [[" * Produce {a}"], [" * Once"]]}], "errors": []}"#,
+        )
+        .unwrap();
+        assert_eq!(explained.plan.unwrap().to_string(), "Produce(Once)");
+
+        assert!(QueryResult::parse("not json").is_err());
+    }
+}
@@ -1,15 +1,26 @@
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
 
 use crate::{
-    ast::{CypherGenerator, CypherNode, TransformVisitor},
-    common::{constants, Diagnostic, Log, OutputWriter, RandomGenerator},
-    config::CypherConfig,
-    db::{AtlasConfig, AtlasConnection},
-    meta::GraphSchema,
+    ast::{
+        fold, is_deterministic, mutate, rewrite, Constant, CypherGenerator, CypherNode, Layout,
+        TransformVisitor,
+    },
+    common::{constants, escape_name, from_json, Diagnostic, Log, OutputWriter, RandomGenerator},
+    config::{
+        CypherConfig, Dialect, PlanMode, PopulateConfig, StressConfig, TransactionMode, Weights,
+    },
+    data::{DataGenerator, ReferenceEngine},
+    db::{AtlasConfig, AtlasConnection, Plan, QueryResult},
+    meta::{write_csv_fixtures, GraphSchema, SchemaInferrer},
+    report::{
+        is_syntax_error, Buckets, Coverage, Feedback, Finding, FindingWriter, Outcome, PlanCoverage,
+    },
 };
-use rpc::atlas::ExecRequest;
-use serde_json::Value;
-use tonic::Request;
 
 #[derive(Default)]
 pub struct Driver {
@@ -20,6 +31,17 @@ pub struct Driver {
     graph_schema: GraphSchema,
     cypher_config: CypherConfig,
     atlas_connection: Option<AtlasConnection>,
+    csv_files: Vec<String>,
+    // the last constructed query writes.
+    is_write: bool,
+    // statements restoring the populated dataset, see `TransactionMode::Snapshot`.
+    snapshot: Vec<String>,
+    // the populated dataset, when the rows are checked against it.
+    reference: Option<ReferenceEngine>,
+    // reported by `VersionRequest` when connected.
+    server_version: Option<String>,
+    // the configured weights, adapted to the results when `adaptive`.
+    weights: Weights,
 }
 
 impl Driver {
@@ -27,11 +49,17 @@ impl Driver {
         Driver {
             queries: 0,
             retries: 0,
-            retry_limit: constants::DEFAULT_QUERY_LIMIT,
+            retry_limit: constants::DEFAULT_RETRY_LIMIT,
             random: RandomGenerator::default(),
             graph_schema: GraphSchema::default(),
             cypher_config: CypherConfig::default(),
             atlas_connection: None,
+            csv_files: vec![],
+            is_write: false,
+            snapshot: vec![],
+            reference: None,
+            server_version: None,
+            weights: Weights::default(),
         }
     }
 
@@ -41,37 +69,89 @@ impl Driver {
     }
 
     pub fn load_config(&mut self, config: CypherConfig) -> CypherConfig {
+        self.weights = config.dialect.restrict(config.weights.clone());
         self.cypher_config = config;
         self.cypher_config.clone()
     }
 
     /// Connect to AtlasGraph.
     async fn load_atlas(&mut self, atlas: AtlasConfig) {
-        self.atlas_connection = Some(AtlasConnection::new(atlas).await);
+        let mut atlas_connection = AtlasConnection::new(atlas).await;
         println!("\nConnect Success!\n");
+        match atlas_connection.version().await {
+            Ok(version) => {
+                println!("Server version: {}", version);
+                self.server_version = Some(version);
+            }
+            Err(err) => eprint!("{}", err),
+        }
+        self.atlas_connection = Some(atlas_connection);
     }
 }
 
 impl Driver {
     /// ast tree construct
     pub fn construct(&mut self) -> Result<CypherNode, Diagnostic> {
-        let mut ast_generator = CypherGenerator::new_schema(&self.graph_schema);
+        let mut ast_generator = CypherGenerator::with_dialect(
+            &self.graph_schema,
+            self.cypher_config.dialect,
+            self.weights.clone(),
+        );
+        ast_generator.csv_files = self.csv_files.clone();
+        ast_generator.query_limit = self.cypher_config.query_limit;
+        ast_generator.read_only = self.cypher_config.read_only;
+        ast_generator.explicit_transaction = self.cypher_config.transaction.explicit();
+        ast_generator.stress = self.cypher_config.stress.clone();
+
+        self.retries = 0;
         while self.retries < self.retry_limit {
-            let query = if self.cypher_config.call_query && self.random.d9() > 7 {
+            self.retries += 1;
+            let ddl_query = self.cypher_config.ddl_query
+                && !self.cypher_config.read_only
+                && self.cypher_config.dialect.schema_commands();
+            let query = if self.random.chance(self.cypher_config.constant_folding) {
+                ast_generator.constant_query()
+            } else if ddl_query && self.random.d9() > 7 {
+                ast_generator.schema_command()
+            } else if self.cypher_config.call_query
+                && !self.cypher_config.dialect.procedures().is_empty()
+                && self.random.d9() > 7
+            {
                 ast_generator.call_query()
             } else {
                 ast_generator.visit()
             };
             if query.is_ok() {
+                self.is_write = ast_generator.writes > 0;
                 return query;
             }
         }
         Err(Diagnostic::error("Retry Limit", None))
     }
 
+    /// Render a serialized AST, or the `ast` of a finding, as a cypher string.
+    pub fn render(json: &str, dialect: Dialect, layout: Layout) -> Result<String, Diagnostic> {
+        let mut value: Value = from_json(json)
+            .map_err(|err| Diagnostic::error("Invalid AST JSON", err.to_string()))?;
+        if let Some(ast) = value.get_mut("ast") {
+            value = ast.take();
+        }
+        let cypher_ast = serde_json::from_value::<CypherNode>(value)
+            .map_err(|err| Diagnostic::error("Invalid AST", err.to_string()))?;
+        let mut transformer = TransformVisitor::with_dialect(dialect).layout(layout);
+        Ok(transformer.exec(Box::new(cypher_ast)))
+    }
+
     /// ast tree transfrom to cypher string.
     pub fn transfrom(&self, cypher_node: Box<CypherNode>) -> String {
-        let mut transformer = TransformVisitor::new();
+        let mut transformer = TransformVisitor::with_dialect(self.cypher_config.dialect)
+            .layout(self.cypher_config.layout)
+            .csv_dir(
+                self.cypher_config
+                    .csv_fixture_path
+                    .as_deref()
+                    .unwrap_or_default(),
+            );
         let mut ans = transformer.exec(cypher_node);
         ans += ";";
         ans
@@ -98,71 +178,744 @@ impl Driver {
         Ok(())
     }
 
+    /// Execute a statement, its errors are returned as an error.
+    async fn exec_checked(&mut self, statement: &str) -> Result<QueryResult, Diagnostic> {
+        let atlas_connection = self
+            .atlas_connection
+            .as_mut()
+            .ok_or_else(|| Diagnostic::error("Not connected", None))?;
+        let result = QueryResult::parse(&atlas_connection.exec(statement).await?)?;
+        if !result.is_ok() {
+            return Err(Diagnostic::error(
+                "Statement failed",
+                format!("{}\n{:?}", statement, result.errors),
+            ));
+        }
+        Ok(result)
+    }
+
+    /// Infer the schema of the connected database from its labels and sampled data,
+    /// write it into `path`.
+    pub async fn introspect(&mut self, path: PathBuf) -> Result<GraphSchema, Diagnostic> {
+        let samples = constants::DEFAULT_INTROSPECT_SAMPLES;
+        let mut inferrer = SchemaInferrer::default();
+
+        let labels = self
+            .exec_checked("CALL db.labels() YIELD label RETURN label")
+            .await?;
+        for label in labels.column("label").iter().filter_map(|x| x.as_str()) {
+            inferrer.add_vertex_label(label);
+            let statement = format!(
+                "MATCH (n:{}) RETURN properties(n) AS properties LIMIT {}",
+                escape_name(label),
+                samples
+            );
+            for properties in self.exec_checked(&statement).await?.column("properties") {
+                if let Some(properties) = properties.as_object() {
+                    inferrer.add_vertex_sample(label, properties);
+                }
+            }
+        }
+
+        let types = self
+            .exec_checked(
+                "CALL db.relationshipTypes() YIELD relationshipType RETURN relationshipType",
+            )
+            .await?;
+        for edge_type in types
+            .column("relationshipType")
+            .iter()
+            .filter_map(|x| x.as_str())
+        {
+            inferrer.add_edge_label(edge_type);
+            let statement = format!(
+                "MATCH (a)-[r:{}]->(b) RETURN head(labels(a)) AS src, head(labels(b)) AS dst, properties(r) AS properties LIMIT {}",
+                escape_name(edge_type),
+                samples
+            );
+            let result = self.exec_checked(&statement).await?;
+            let idx = |name: &str| result.columns.iter().position(|column| column == name);
+            if let (Some(src), Some(dst), Some(properties)) =
+                (idx("src"), idx("dst"), idx("properties"))
+            {
+                for row in result.rows.iter() {
+                    if let (
+                        Some(Value::String(src)),
+                        Some(Value::String(dst)),
+                        Some(Value::Object(properties)),
+                    ) = (row.get(src), row.get(dst), row.get(properties))
+                    {
+                        inferrer.add_edge_sample(edge_type, src, dst, properties);
+                    }
+                }
+            }
+        }
+
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("introspected");
+        let schema = inferrer.build(name);
+        let json = serde_json::to_string_pretty(&schema)
+            .map_err(|err| Diagnostic::bug("Serialize schema", err.to_string()))?;
+        std::fs::write(&path, json)
+            .map_err(|err| Diagnostic::error("Write schema", format!("{:?}: {}", path, err)))?;
+
+        Ok(schema)
+    }
+
+    /// Write the generated dataset into the database.
+    async fn populate(&mut self, mut config: PopulateConfig) -> Result<(), Diagnostic> {
+        // the snapshot and the reference engine start from an empty database.
+        let snapshot = self.cypher_config.transaction == TransactionMode::Snapshot;
+        config.clear |= snapshot || self.cypher_config.reference;
+
+        let mut data_generator = DataGenerator::new(&self.graph_schema, config);
+        let graph_data = data_generator.generate();
+        println!(
+            "Populate {} vertices and {} edges.",
+            graph_data.vertices.len(),
+            graph_data.edges.len()
+        );
+
+        let statements = data_generator.statements(&graph_data);
+        if snapshot {
+            self.snapshot = statements.clone();
+        }
+        if self.cypher_config.reference {
+            self.reference = Some(ReferenceEngine::new(graph_data));
+        }
+        for statement in statements {
+            if self.cypher_config.dry_run {
+                println!("Populate:\n{}", statement);
+            }
+            if self.atlas_connection.is_some() {
+                self.exec_checked(&statement).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Execute a generated query, isolating its writes according to the transaction mode.
+    async fn exec_query(&mut self, query: &str) -> Result<String, Diagnostic> {
+        let mode = if self.is_write {
+            self.cypher_config.transaction
+        } else {
+            TransactionMode::AutoCommit
+        };
+        let atlas_connection = self
+            .atlas_connection
+            .as_mut()
+            .ok_or_else(|| Diagnostic::error("Not connected", None))?;
+
+        match mode {
+            TransactionMode::AutoCommit => atlas_connection.exec(query).await,
+            TransactionMode::Rollback | TransactionMode::Commit => {
+                atlas_connection.begin().await?;
+                let res = atlas_connection.exec(query).await;
+                // a failed statement is always rolled back.
+                let succeeded = res
+                    .as_ref()
+                    .is_ok_and(|res| QueryResult::parse(res).is_ok_and(|result| result.is_ok()));
+                if mode == TransactionMode::Commit && succeeded {
+                    atlas_connection.commit().await?;
+                } else {
+                    atlas_connection.rollback().await?;
+                }
+                res
+            }
+            TransactionMode::Snapshot => {
+                let res = atlas_connection.exec(query).await?;
+                for statement in self.snapshot.clone() {
+                    self.exec_checked(&statement).await?;
+                }
+                Ok(res)
+            }
+        }
+    }
+
+    /// Execute a query of the stress profile. A query without a result in time is a
+    /// `Timeout`, a failed RPC is a `Crash` if the server does not answer anymore.
+    async fn exec_stress(
+        &mut self,
+        query: &str,
+        stress: &StressConfig,
+    ) -> (Result<String, Diagnostic>, Option<Outcome>) {
+        let timeout = Duration::from_millis(stress.timeout_ms);
+        let err = match tokio::time::timeout(timeout, self.exec_query(query)).await {
+            Ok(Ok(res)) => return (Ok(res), None),
+            Ok(Err(err)) => err,
+            Err(_) => {
+                let err = Diagnostic::error("Timeout", format!("{}ms", stress.timeout_ms));
+                // the interrupted query may have left a transaction or writes behind.
+                let restore =
+                    self.is_write && self.cypher_config.transaction == TransactionMode::Snapshot;
+                if let Some(atlas_connection) = self.atlas_connection.as_mut() {
+                    if atlas_connection.transaction_id.is_some() {
+                        let _ = tokio::time::timeout(timeout, atlas_connection.rollback()).await;
+                    }
+                }
+                if restore {
+                    for statement in self.snapshot.clone() {
+                        let _ = tokio::time::timeout(timeout, self.exec_checked(&statement)).await;
+                    }
+                }
+                // a server which stopped answering crashed on the query.
+                let outcome = if self.is_alive(timeout).await {
+                    Outcome::Timeout
+                } else {
+                    Outcome::Crash
+                };
+                return (Err(err), Some(outcome));
+            }
+        };
+
+        let outcome = if self.is_alive(timeout).await {
+            Outcome::Failure
+        } else {
+            Outcome::Crash
+        };
+        (Err(err), Some(outcome))
+    }
+
+    /// The server answers a `VersionRequest` within the timeout.
+    async fn is_alive(&mut self, timeout: Duration) -> bool {
+        match self.atlas_connection.as_mut() {
+            Some(atlas_connection) => {
+                matches!(
+                    tokio::time::timeout(timeout, atlas_connection.version()).await,
+                    Ok(Ok(_))
+                )
+            }
+            None => false,
+        }
+    }
+
+    /// Execute a query, with the timeout of the stress profile if any.
+    async fn run(&mut self, query: &str) -> (Result<String, Diagnostic>, Option<Outcome>) {
+        match self.cypher_config.stress.clone() {
+            Some(stress) => self.exec_stress(query, &stress).await,
+            None => (self.exec_query(query).await, None),
+        }
+    }
+
+    /// The plan of a query by `EXPLAIN`, which does not execute it, `None` if the target
+    /// returns no plan.
+    async fn explain(&mut self, query: &str) -> Option<Plan> {
+        let statement = format!("EXPLAIN {}", query);
+        let timeout = self
+            .cypher_config
+            .stress
+            .as_ref()
+            .map(|stress| stress.timeout_ms);
+        let atlas_connection = self.atlas_connection.as_mut()?;
+        let res = match timeout {
+            Some(timeout_ms) => tokio::time::timeout(
+                Duration::from_millis(timeout_ms),
+                atlas_connection.exec(&statement),
+            )
+            .await
+            .ok()?,
+            None => atlas_connection.exec(&statement).await,
+        };
+        QueryResult::parse(&res.ok()?).ok()?.plan
+    }
+
+    /// A finding of the query, neither mutated nor rewritten.
+    fn finding(
+        &self,
+        query: &str,
+        seed: u64,
+        outcome: Outcome,
+        errors: &[Value],
+        latency: Duration,
+        ast: &CypherNode,
+    ) -> Result<Finding, Diagnostic> {
+        let ast = serde_json::to_value(ast)
+            .map_err(|err| Diagnostic::bug("Serialize AST", err.to_string()))?;
+        Ok(Finding {
+            query: query.to_string(),
+            seed,
+            outcome,
+            errors: errors.to_vec(),
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            backend: self
+                .atlas_connection
+                .as_ref()
+                .map(|atlas_connection| atlas_connection.backend())
+                .unwrap_or_default(),
+            server_version: self.server_version.clone(),
+            ast,
+            mutation: None,
+            rewrite: None,
+            plan: None,
+            plan_mode: None,
+            weights: self.cypher_config.adaptive.then(|| self.weights.clone()),
+        })
+    }
+
+    /// The backend has no transactions: a committed statement is an auto-committed one,
+    /// the writes of the Rollback mode can not be undone.
+    fn fallback_transaction(&mut self) -> Result<(), Diagnostic> {
+        match self.cypher_config.transaction {
+            TransactionMode::Commit => {
+                eprintln!(
+                    "{}",
+                    Diagnostic::warn(
+                        "No transactions",
+                        "the backend has no Begin, Commit and Rollback RPCs, the Commit \
+                         transaction mode falls back to AutoCommit"
+                            .to_string(),
+                    )
+                );
+                self.cypher_config.transaction = TransactionMode::AutoCommit;
+                Ok(())
+            }
+            mode => Err(Diagnostic::error(
+                "Invalid config",
+                format!(
+                    "the {:?} transaction mode requires the Begin, Commit and Rollback RPCs, \
+                     which the backend does not implement, use Snapshot instead",
+                    mode
+                ),
+            )),
+        }
+    }
+
+    /// Use the adapted weights for the next queries, print the changed ones.
+    fn adapt_weights(&mut self, weights: Weights) {
+        let changes = self
+            .weights
+            .productions()
+            .into_iter()
+            .zip(weights.productions())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((name, old), (_, new))| format!("{}: {} -> {}", name, old, new))
+            .collect::<Vec<_>>();
+        if !changes.is_empty() {
+            println!("Adapted weights: {}", changes.join(", "));
+        }
+        self.weights = weights;
+    }
+
     /// databse execution
     pub async fn execute(&mut self) -> Result<(), Diagnostic> {
         // log_record recording intermediate information
         let mut log_record = Log::new();
 
         let mut cypher = Vec::new();
-        let mut results = Vec::new();
+        let mut buckets = Buckets::default();
+        let mut coverage = Coverage::default();
+        let mut feedback = Feedback::new(self.weights.clone());
+        let mut plans = PlanCoverage::default();
+        // the backend can not be reached anymore.
+        let mut failure = None;
+
+        let disabled = self
+            .weights
+            .productions()
+            .into_iter()
+            .filter(|(_, weight)| *weight == 0)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        if !disabled.is_empty() {
+            println!("Disabled productions: {}", disabled.join(", "));
+        }
+
+        let mut findings = match &self.cypher_config.findings {
+            Some(path) => Some(FindingWriter::new(path)?),
+            None => None,
+        };
+
+        // csv fixtures for LOAD CSV.
+        if let Some(path) = self.cypher_config.csv_fixture_path.clone() {
+            self.csv_files = write_csv_fixtures(&self.graph_schema, &path)?;
+            // Memgraph reads the fixtures by their absolute path.
+            if let Ok(dir) = std::fs::canonicalize(&path) {
+                self.cypher_config.csv_fixture_path = Some(dir.display().to_string());
+            }
+        }
+
+        if self.cypher_config.transaction == TransactionMode::Snapshot
+            && self.cypher_config.populate.is_none()
+        {
+            return Err(Diagnostic::error(
+                "Invalid config",
+                "the Snapshot transaction mode restores the populated dataset and requires populate"
+                    .to_string(),
+            ));
+        }
+
+        // the snapshot restores the data, not the indexes and constraints.
+        if self.cypher_config.transaction == TransactionMode::Snapshot
+            && self.cypher_config.ddl_query
+        {
+            return Err(Diagnostic::error(
+                "Invalid config",
+                "the Snapshot transaction mode does not restore the DDL of ddl_query".to_string(),
+            ));
+        }
+
+        // the rows of the reference engine are those of the populated dataset, which
+        // the queries that write must leave as it is.
+        if self.cypher_config.reference
+            && (self.cypher_config.populate.is_none()
+                || !(self.cypher_config.read_only
+                    || matches!(
+                        self.cypher_config.transaction,
+                        TransactionMode::Rollback | TransactionMode::Snapshot
+                    )))
+        {
+            return Err(Diagnostic::error(
+                "Invalid config",
+                "the reference engine requires populate and read_only or the Rollback or \
+                 Snapshot transaction mode"
+                    .to_string(),
+            ));
+        }
+
+        if self.cypher_config.plan.is_some() && !self.cypher_config.dialect.query_plans() {
+            return Err(Diagnostic::error(
+                "Invalid config",
+                format!(
+                    "the {:?} dialect has no EXPLAIN and PROFILE to capture plans",
+                    self.cypher_config.dialect
+                ),
+            ));
+        }
+
+        // the oracles check the rows of the executed query, which a PROFILE replaces.
+        let row_oracles = self.cypher_config.constant_folding > 0
+            || self.cypher_config.metamorphic > 0
+            || self.cypher_config.reference;
+        if self.cypher_config.plan == Some(PlanMode::Profile)
+            && !self.cypher_config.dialect.profile_rows()
+            && row_oracles
+        {
+            return Err(Diagnostic::error(
+                "Invalid config",
+                format!(
+                    "the PROFILE of the {:?} dialect returns no rows to check by \
+                     constant_folding, metamorphic or reference, use the Explain plan mode",
+                    self.cypher_config.dialect
+                ),
+            ));
+        }
+
+        // Rollback and Commit need the transaction RPCs of the backend.
+        if self.cypher_config.transaction.explicit() {
+            if let Some(atlas_connection) = self.atlas_connection.as_mut() {
+                if !atlas_connection.has_transactions().await? {
+                    self.fallback_transaction()?;
+                }
+            }
+        }
+
+        // populate the database before fuzzing, read_only or not.
+        if let Some(populate) = self.cypher_config.populate.clone() {
+            self.populate(populate).await?;
+        }
+
+        // every query is generated from its own seed, derived from the previous one.
+        let mut seed = match self.cypher_config.seed {
+            Some(seed) => seed,
+            None => self.random.next_seed(),
+        };
+        println!("Seed: {}", seed);
 
         // while current queries < max_queries.
         while self.queries < self.cypher_config.max_queries {
+            let query_seed = seed;
+            RandomGenerator::seed(query_seed);
+
             // generator the ast tree and string.
             let cypher_ast = self.construct()?;
+            seed = self.random.next_seed();
 
             // transform ast tree to string.
-            let cypher_string = self.transfrom(Box::new(cypher_ast.clone()));
+            let mut cypher_string = self.transfrom(Box::new(cypher_ast.clone()));
+
+            // an invalid query, derived from the valid one.
+            let mut mutation = None;
+            if self.random.chance(self.cypher_config.syntax_mutation) {
+                if let Some((mutated, kind)) = mutate(&cypher_string, &mut self.random) {
+                    cypher_string = mutated;
+                    mutation = Some(kind);
+                }
+            }
+
+            // a semantics preserving rewrite of a read only query, which must return
+            // the same rows.
+            let mut rewritten = None;
+            if mutation.is_none()
+                && !self.is_write
+                && self.random.chance(self.cypher_config.metamorphic)
+                && is_deterministic(&cypher_ast)
+            {
+                if let Some((ast, kind)) = rewrite(&cypher_ast, &mut self.random) {
+                    rewritten = Some((kind, self.transfrom(Box::new(ast))));
+                }
+            }
+
+            // the values of a `RETURN` of constant expressions, computed by the evaluator.
+            let folded = if mutation.is_none() && self.cypher_config.constant_folding > 0 {
+                fold(&cypher_ast)
+            } else {
+                None
+            };
+
+            // the rows of a read only query, computed by the reference engine.
+            let expected_rows = match &self.reference {
+                Some(reference) if mutation.is_none() && !self.is_write && folded.is_none() => {
+                    reference.execute(&cypher_ast)
+                }
+                _ => None,
+            };
 
             // print queries instead of executing them
             if self.cypher_config.dry_run {
+                if let Some(mutation) = mutation {
+                    println!("Mutation: {:?}", mutation);
+                }
                 println!("CypherString:\n{}", cypher_string);
+                if let Some((kind, rewritten)) = &rewritten {
+                    println!("Rewrite: {:?}\n{}", kind, rewritten);
+                }
+                match &folded {
+                    Some(Ok(values)) => println!("Folded: {}", folded_row(values)),
+                    Some(Err(err)) => println!("Folded: error, {}", err),
+                    None => {}
+                }
+                if let Some(expected_rows) = &expected_rows {
+                    println!("Reference: {} rows", expected_rows.rows.len());
+                }
                 cypher.push(cypher_string.clone());
             }
 
-            // dump generated ASTs for debugging.
+            // dump generated ASTs for debugging, reloadable by `--render`.
             if self.cypher_config.dump_all_graphs {
-                println!("CypherAST:\n{:?}", cypher_ast);
+                let json = serde_json::to_string(&cypher_ast)
+                    .map_err(|err| Diagnostic::bug("Serialize AST", err.to_string()))?;
+                println!("CypherAST:\n{}", json);
             }
 
-            log_record.execute(Box::new(cypher_ast));
+            log_record.execute(Box::new(cypher_ast.clone()));
 
             // query number add 1
             self.queries += 1;
 
+            let mut features = Coverage::features(&cypher_ast);
+            let mut query_outcome = None;
+
             // if connect to AtlasGraph
             if self.atlas_connection.is_some() {
-                let mut atlas_client = self.atlas_connection.as_ref().unwrap().client.clone();
-                let session_id = self.atlas_connection.as_ref().unwrap().session_id.clone();
+                // the plan by `EXPLAIN` before the query, or by `PROFILE` with it.
+                let plan_mode = self.cypher_config.plan.filter(|_| mutation.is_none());
+                let mut plan = match plan_mode {
+                    Some(PlanMode::Explain) => self.explain(&cypher_string).await,
+                    _ => None,
+                };
+                let executed = match plan_mode {
+                    Some(PlanMode::Profile) => format!("PROFILE {}", cypher_string),
+                    _ => cypher_string.clone(),
+                };
+
+                let start = Instant::now();
+                let (res, stress_outcome) = self.run(&executed).await;
+                let latency = start.elapsed();
+
+                let result = res.and_then(|res| {
+                    println!("\n{}", res);
+                    QueryResult::parse(&res)
+                });
+                if plan_mode == Some(PlanMode::Profile) {
+                    plan = result.as_ref().ok().and_then(|result| result.plan.clone());
+                }
+                // the operators count as features of the query, a new one makes the
+                // query novel for the adaptive weights.
+                let mut new_operator = false;
+                if let Some(plan) = &plan {
+                    new_operator = plans.record(plan);
+                    features.extend(
+                        plan.operators()
+                            .into_iter()
+                            .map(|operator| format!("Operator::{}", operator)),
+                    );
+                }
+                let (outcome, errors) = match &result {
+                    Ok(result) => (Outcome::of(result), result.errors.clone()),
+                    Err(err) => (
+                        stress_outcome.unwrap_or(Outcome::Failure),
+                        vec![serde_json::to_value(err).unwrap_or_default()],
+                    ),
+                };
+                let (outcome, errors) = match (&folded, &result) {
+                    (Some(folded), Ok(result)) => match folding_mismatch(folded, outcome, result) {
+                        Some(detail) => (
+                            Outcome::LogicBug,
+                            vec![serde_json::json!({
+                                "level": "LogicBug",
+                                "message": "constant folding",
+                                "detail": detail,
+                            })],
+                        ),
+                        None => (outcome, errors),
+                    },
+                    _ => (outcome, errors),
+                };
+                let mismatch = match (&expected_rows, &result) {
+                    (Some(expected_rows), Ok(result)) if outcome == Outcome::Success => {
+                        expected_rows.mismatch(&result.rows)
+                    }
+                    _ => None,
+                };
+                let (outcome, errors) = match mismatch {
+                    Some(detail) => (
+                        Outcome::LogicBug,
+                        vec![serde_json::json!({
+                            "level": "LogicBug",
+                            "message": "reference engine",
+                            "detail": detail,
+                        })],
+                    ),
+                    None => (outcome, errors),
+                };
 
-                // if self.cypher_config
+                // a mutated query must be rejected by the parser, the crashes, the
+                // accepted queries and the other errors are findings; a constant
+                // expression the evaluator rejects must be rejected too.
+                let expected = match mutation {
+                    Some(_) => outcome == Outcome::Error && is_syntax_error(&errors),
+                    None if matches!(folded, Some(Err(_))) => outcome == Outcome::Error,
+                    None => outcome == Outcome::Success,
+                };
+                if mutation.is_none() {
+                    query_outcome = Some(outcome);
+                }
+                if !expected {
+                    if let Some(findings) = findings.as_mut() {
+                        findings.write(&Finding {
+                            mutation,
+                            plan: plan.clone(),
+                            plan_mode,
+                            ..self.finding(
+                                &cypher_string,
+                                query_seed,
+                                outcome,
+                                &errors,
+                                latency,
+                                &cypher_ast,
+                            )?
+                        })?;
+                    }
+                }
+                let new_bucket = !expected
+                    && buckets.add(
+                        self.queries,
+                        &cypher_ast,
+                        &cypher_string,
+                        query_seed,
+                        outcome,
+                        &errors,
+                    );
 
-                //let session_id = self
-                let res = atlas_client
-                    .exec(Request::new(ExecRequest {
-                        session_id: session_id.clone(),
-                        statement: cypher_string.clone(),
-                    }))
-                    .await
-                    .unwrap()
-                    .into_inner()
-                    .result;
+                if self.cypher_config.adaptive && mutation.is_none() {
+                    feedback.record(&features, outcome, new_bucket || new_operator);
+                    if let Some(weights) = feedback.next_weights() {
+                        self.adapt_weights(weights);
+                    }
+                }
 
-                println!("\n{}", res);
+                // the rows of the accepted query and of its rewrite, a rewrite rejected
+                // with an error is not a finding, the rewritten syntax may be unsupported.
+                if let (Some((kind, rewritten)), Ok(expected_rows)) = (&rewritten, &result) {
+                    if outcome == Outcome::Success {
+                        let start = Instant::now();
+                        let (res, stress_outcome) = self.run(rewritten).await;
+                        let latency = start.elapsed();
+                        let parsed = res.and_then(|res| QueryResult::parse(&res));
+                        let mismatch = match &parsed {
+                            Ok(rows) if rows.is_ok() => expected_rows.row_mismatch(rows),
+                            _ => None,
+                        };
+                        let (outcome, errors) = match parsed {
+                            Ok(_) if mismatch.is_some() => (
+                                Outcome::LogicBug,
+                                vec![serde_json::json!({
+                                    "level": "LogicBug",
+                                    "message": format!("{:?} changed the rows", kind),
+                                    "detail": mismatch,
+                                })],
+                            ),
+                            Ok(rows) => (Outcome::of(&rows), rows.errors),
+                            Err(err) => (
+                                stress_outcome.unwrap_or(Outcome::Failure),
+                                vec![serde_json::to_value(err).unwrap_or_default()],
+                            ),
+                        };
+                        if !matches!(
+                            outcome,
+                            Outcome::Success | Outcome::Warning | Outcome::Error
+                        ) {
+                            if let Some(findings) = findings.as_mut() {
+                                findings.write(&Finding {
+                                    rewrite: Some((*kind, rewritten.clone())),
+                                    plan: plan.clone(),
+                                    plan_mode,
+                                    ..self.finding(
+                                        &cypher_string,
+                                        query_seed,
+                                        outcome,
+                                        &errors,
+                                        latency,
+                                        &cypher_ast,
+                                    )?
+                                })?;
+                            }
+                            buckets.add(
+                                self.queries,
+                                &cypher_ast,
+                                &cypher_string,
+                                query_seed,
+                                outcome,
+                                &errors,
+                            );
+                        }
+                    }
+                }
 
-                let v: Value = serde_json::from_str(res.as_str()).unwrap();
-                let errors = v.get("errors").unwrap().as_array().unwrap().clone();
-                if !errors.is_empty() {
-                    results.push((cypher_string, errors));
+                // the stress profile goes on after a timeout or a failed query, as long
+                // as the server answers.
+                let fatal = match outcome {
+                    Outcome::Crash => true,
+                    Outcome::Failure => self.cypher_config.stress.is_none(),
+                    _ => false,
+                };
+                if let Err(err) = result {
+                    if fatal {
+                        failure = Some(err);
+                        break;
+                    }
                 }
             }
+            coverage.record(&features, query_outcome);
+        }
+
+        coverage.report();
+        if self.cypher_config.plan.is_some() {
+            plans.report();
+        }
+        if let Some(path) = &self.cypher_config.coverage {
+            coverage.export(path)?;
         }
 
-        // verbose
+        // one row per error bucket.
+        buckets.report();
         if let Some(path) = &self.cypher_config.verbose {
             let mut output = OutputWriter::new(path.to_string());
-            for (cypher, errors) in results {
-                output.write_errors(cypher, errors);
+            for bucket in buckets.buckets() {
+                output.write_record(bucket);
             }
         }
 
@@ -177,6 +930,296 @@ impl Driver {
         // print report.
         log_record.report();
 
-        Ok(())
+        match failure {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The values of a folded row in the JSON format of the results.
+fn folded_row(values: &[Constant]) -> Value {
+    Value::Array(values.iter().map(Constant::to_json).collect())
+}
+
+/// How the result of the target differs from the folded values: another row, an error
+/// instead of the values, or the values instead of an error.
+fn folding_mismatch(
+    folded: &Result<Vec<Constant>, String>,
+    outcome: Outcome,
+    result: &QueryResult,
+) -> Option<String> {
+    let rows = Value::from(result.rows.clone());
+    match (folded, outcome) {
+        (Ok(values), Outcome::Success) => {
+            let same = match result.rows.as_slice() {
+                [row] => {
+                    row.len() == values.len()
+                        && values
+                            .iter()
+                            .zip(row)
+                            .all(|(value, rhs)| value.matches(rhs))
+                }
+                _ => false,
+            };
+            (!same).then(|| format!("expected {}, got {}", folded_row(values), rows))
+        }
+        (Ok(values), Outcome::Error) => {
+            Some(format!("expected {}, got an error", folded_row(values)))
+        }
+        (Err(err), Outcome::Success) => Some(format!("expected an error, {}, got {}", err, rows)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rpc::atlas::{
+        atlas_graph_server::{AtlasGraph, AtlasGraphServer},
+        AuthenticateRequest, AuthenticateResponse, BeginRequest, BeginResponse, CommitRequest,
+        ExecRequest, ExecResponse, ExitRequest, PingRequest, RollbackRequest, VersionRequest,
+        VersionResponse,
+    };
+    use tonic::{Request, Response, Status};
+
+    use super::Driver;
+    use crate::config::TransactionMode;
+    use crate::db::{AtlasConfig, AtlasConnection};
+
+    /// A backend recording the RPCs, a statement containing `fail` returns an error.
+    #[derive(Clone, Default)]
+    struct MockBackend {
+        calls: Arc<Mutex<Vec<String>>>,
+        // `Begin`, `Commit` and `Rollback` are unimplemented without.
+        transactions: bool,
+    }
+
+    impl MockBackend {
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+
+        /// Record a transaction RPC, false if unimplemented.
+        fn transaction(&self, rpc: &str) -> bool {
+            if self.transactions {
+                self.record(rpc.to_string());
+            }
+            self.transactions
+        }
+    }
+
+    #[tonic::async_trait]
+    impl AtlasGraph for MockBackend {
+        async fn authenticate(
+            &self,
+            _: Request<AuthenticateRequest>,
+        ) -> Result<Response<AuthenticateResponse>, Status> {
+            Ok(Response::new(AuthenticateResponse {
+                session_id: vec![1],
+            }))
+        }
+
+        async fn ping(&self, _: Request<PingRequest>) -> Result<Response<()>, Status> {
+            Ok(Response::new(()))
+        }
+
+        async fn version(
+            &self,
+            _: Request<VersionRequest>,
+        ) -> Result<Response<VersionResponse>, Status> {
+            Ok(Response::new(VersionResponse {
+                version: "mock".to_string(),
+            }))
+        }
+
+        async fn exec(
+            &self,
+            request: Request<ExecRequest>,
+        ) -> Result<Response<ExecResponse>, Status> {
+            let request = request.into_inner();
+            let in_transaction = if request.transaction_id.is_empty() {
+                ""
+            } else {
+                " in transaction"
+            };
+            self.record(format!("exec {}{}", request.statement, in_transaction));
+            let result = if request.statement.contains("fail") {
+                r#"{"results": [], "errors": [{"code": "SyntaxError"}]}"#
+            } else {
+                r#"{"results": [{"columns": [], "data": []}], "errors": []}"#
+            };
+            Ok(Response::new(ExecResponse {
+                result: result.to_string(),
+            }))
+        }
+
+        async fn exit(&self, _: Request<ExitRequest>) -> Result<Response<()>, Status> {
+            Ok(Response::new(()))
+        }
+
+        async fn begin(&self, _: Request<BeginRequest>) -> Result<Response<BeginResponse>, Status> {
+            if !self.transaction("begin") {
+                return Err(Status::unimplemented("begin"));
+            }
+            Ok(Response::new(BeginResponse {
+                transaction_id: vec![2],
+            }))
+        }
+
+        async fn commit(&self, _: Request<CommitRequest>) -> Result<Response<()>, Status> {
+            if !self.transaction("commit") {
+                return Err(Status::unimplemented("commit"));
+            }
+            Ok(Response::new(()))
+        }
+
+        async fn rollback(&self, _: Request<RollbackRequest>) -> Result<Response<()>, Status> {
+            if !self.transaction("rollback") {
+                return Err(Status::unimplemented("rollback"));
+            }
+            Ok(Response::new(()))
+        }
+    }
+
+    /// A driver connected to the backend served on a free local port.
+    async fn connect(backend: MockBackend) -> Driver {
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap();
+        let server = tonic::transport::Server::builder()
+            .add_service(AtlasGraphServer::new(backend))
+            .serve(address);
+        tokio::spawn(server);
+        while tokio::net::TcpStream::connect(address).await.is_err() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let mut driver = Driver::new();
+        driver.atlas_connection = Some(
+            AtlasConnection::new(AtlasConfig {
+                address: address.to_string().parse().unwrap(),
+                username: "root".to_string(),
+                password: "root".to_string(),
+            })
+            .await,
+        );
+        driver
+    }
+
+    /// Execute a query of the mode, return the RPCs of the backend.
+    async fn exec(
+        driver: &mut Driver,
+        calls: &Mutex<Vec<String>>,
+        mode: TransactionMode,
+        is_write: bool,
+        query: &str,
+    ) -> Vec<String> {
+        driver.cypher_config.transaction = mode;
+        driver.is_write = is_write;
+        calls.lock().unwrap().clear();
+        driver.exec_query(query).await.unwrap();
+        calls.lock().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_exec_query() {
+        let backend = MockBackend {
+            transactions: true,
+            ..MockBackend::default()
+        };
+        let calls = backend.calls.clone();
+        let mut driver = connect(backend).await;
+
+        // reads are auto-committed in every mode.
+        assert_eq!(
+            exec(
+                &mut driver,
+                &calls,
+                TransactionMode::Rollback,
+                false,
+                "MATCH (n) RETURN n"
+            )
+            .await,
+            ["exec MATCH (n) RETURN n"]
+        );
+        assert_eq!(
+            exec(
+                &mut driver,
+                &calls,
+                TransactionMode::Rollback,
+                true,
+                "CREATE ()"
+            )
+            .await,
+            ["begin", "exec CREATE () in transaction", "rollback"]
+        );
+        assert_eq!(
+            exec(
+                &mut driver,
+                &calls,
+                TransactionMode::Commit,
+                true,
+                "CREATE ()"
+            )
+            .await,
+            ["begin", "exec CREATE () in transaction", "commit"]
+        );
+        // a failed statement is rolled back.
+        assert_eq!(
+            exec(
+                &mut driver,
+                &calls,
+                TransactionMode::Commit,
+                true,
+                "CREATE fail"
+            )
+            .await,
+            ["begin", "exec CREATE fail in transaction", "rollback"]
+        );
+
+        driver.snapshot = vec!["MATCH (n) DETACH DELETE n".to_string()];
+        assert_eq!(
+            exec(
+                &mut driver,
+                &calls,
+                TransactionMode::Snapshot,
+                true,
+                "CREATE ()"
+            )
+            .await,
+            ["exec CREATE ()", "exec MATCH (n) DETACH DELETE n"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_transactions() {
+        let mut driver = connect(MockBackend::default()).await;
+        let atlas_connection = driver.atlas_connection.as_mut().unwrap();
+        assert!(!atlas_connection.has_transactions().await.unwrap());
+        assert!(atlas_connection.transaction_id.is_none());
+
+        driver.cypher_config.transaction = TransactionMode::Commit;
+        driver.fallback_transaction().unwrap();
+        assert_eq!(
+            driver.cypher_config.transaction,
+            TransactionMode::AutoCommit
+        );
+        driver.cypher_config.transaction = TransactionMode::Rollback;
+        assert!(driver.fallback_transaction().is_err());
+
+        let backend = MockBackend {
+            transactions: true,
+            ..MockBackend::default()
+        };
+        let calls = backend.calls.clone();
+        let mut driver = connect(backend).await;
+        let atlas_connection = driver.atlas_connection.as_mut().unwrap();
+        assert!(atlas_connection.has_transactions().await.unwrap());
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["begin", "commit", "begin", "rollback"]
+        );
     }
 }
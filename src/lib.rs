@@ -1,12 +1,15 @@
 mod ast;
 mod common;
 mod config;
+mod data;
 mod db;
 mod driver;
 mod meta;
+mod report;
 
+pub use ast::Layout;
 pub use common::Log;
-pub use config::{ArgsConfig, CypherConfig};
+pub use config::{ArgsConfig, CypherConfig, Dialect, StressConfig};
 pub use db::AtlasConfig;
 pub use driver::Driver;
 pub use meta::GraphSchema;
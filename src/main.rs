@@ -1,9 +1,81 @@
-use cypher_smith::{ArgsConfig, CypherConfig, Driver, GraphSchema};
+use std::path::PathBuf;
+
+use cypher_smith::{ArgsConfig, CypherConfig, Dialect, Driver, GraphSchema, Layout};
+
+/// Write the schema of the database instead of fuzzing.
+fn introspect(config: &ArgsConfig, schema_path: PathBuf) {
+    let atlas_path = match config.atlas {
+        Some(ref atlas_path) => atlas_path.clone(),
+        None => {
+            eprintln!("[WARNING] Please provide the atlas config to introspect!\n\tuse `cypher_smith --help` to find out example usage");
+            return;
+        }
+    };
+
+    let mut driver = Driver::new();
+    tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(async {
+            if let Err(err) = driver.load(atlas_path).await {
+                eprintln!("{}", err);
+                return;
+            }
+            match driver.introspect(schema_path.clone()).await {
+                Ok(schema) => println!(
+                    "Write schema information into {:?}: \n{:?}",
+                    schema_path, schema
+                ),
+                Err(err) => eprintln!("{}", err),
+            }
+        });
+}
+
+/// Print the query of a serialized AST, e.g. a line of the findings report.
+fn render(ast_path: PathBuf, dialect: Dialect, layout: Layout) {
+    let rendered = std::fs::read_to_string(&ast_path)
+        .map_err(|err| format!("[Error]: {}: {}", ast_path.display(), err))
+        .and_then(|json| Driver::render(&json, dialect, layout).map_err(|err| err.to_string()));
+    match rendered {
+        Ok(query) => println!("{}", query),
+        Err(err) => eprintln!("{}", err),
+    }
+}
+
+/// Stack of the fuzzing thread, the ASTs of the stress profile are thousands of levels deep.
+const STACK_SIZE: usize = 1 << 30;
 
 fn main() {
+    let fuzzer = std::thread::Builder::new()
+        .name("cypher_smith".to_string())
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .unwrap();
+    if fuzzer.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+fn run() {
     // get user config.
     let config = <ArgsConfig as clap::Parser>::parse();
 
+    if let Some(ref schema_path) = config.introspect {
+        introspect(&config, schema_path.clone());
+        return;
+    }
+
+    if let Some(ref ast_path) = config.render {
+        render(
+            ast_path.clone(),
+            config.dialect.unwrap_or_default(),
+            config.layout.unwrap_or_default(),
+        );
+        return;
+    }
+
     if config.schema.is_none() || config.config.is_none() {
         eprintln!("[WARNING] Please provide schema and basic config information!\n\tuse `cypher_smith --help` to find out example usage");
         return;
@@ -14,18 +86,36 @@ fn main() {
 
     // schema information
     if let Some(ref schema_path) = config.schema {
-        let schema_path = schema_path.clone();
-        let json = std::fs::read_to_string(schema_path).unwrap();
-        let schema = serde_json::from_str::<GraphSchema>(&json).unwrap();
+        let schema = match GraphSchema::from_file(schema_path) {
+            Ok(schema) => schema,
+            Err(diagnostics) => {
+                for diagnostic in diagnostics {
+                    eprint!("{}", diagnostic);
+                }
+                return;
+            }
+        };
         println!("Input schema information: \n{:?}", schema);
         driver.load_schema(schema);
     }
 
     // basic config information
     if let Some(ref config_path) = config.config {
-        let config_path = config_path.clone();
-        let json = std::fs::read_to_string(config_path).unwrap();
-        let config = serde_json::from_str::<CypherConfig>(&json).unwrap();
+        let config = match std::fs::read_to_string(config_path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<CypherConfig>(&json).map_err(|err| err.to_string())
+            }) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!(
+                    "[Error]: Invalid config\n\t{}: {}",
+                    config_path.display(),
+                    err
+                );
+                return;
+            }
+        };
         println!("\nInput basic config information: \n{:?}", config);
         driver.load_config(config);
     }
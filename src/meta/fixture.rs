@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use super::{GraphSchema, Label};
+use crate::common::{constants, Diagnostic, FieldValue, RandomGenerator};
+
+/// Write one csv file per label into `dir`, read back by generated `LOAD CSV` clauses.
+///
+/// The header row holds the property names, every row a random value per property.
+/// Return the written file names.
+pub fn write_csv_fixtures(schema: &GraphSchema, dir: &str) -> Result<Vec<String>, Diagnostic> {
+    std::fs::create_dir_all(dir)
+        .map_err(|err| Diagnostic::error("Create csv fixture directory", err.to_string()))?;
+
+    let mut random = RandomGenerator::new();
+    let mut files = vec![];
+    for label in schema.vertex_labels().iter().chain(schema.edge_labels()) {
+        let file_name = fixture_name(label);
+        write_label_fixture(label, &Path::new(dir).join(&file_name), &mut random)?;
+        files.push(file_name);
+    }
+
+    Ok(files)
+}
+
+/// File name of the fixture of a label: its id, and its name reduced to the characters
+/// safe in a path and a url, e.g. `1_Person.csv`.
+fn fixture_name(label: &Label) -> String {
+    let name = label
+        .get_name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    format!("{}_{}.csv", label.label_id, name)
+}
+
+fn write_label_fixture(
+    label: &Label,
+    path: &Path,
+    random: &mut RandomGenerator,
+) -> Result<(), Diagnostic> {
+    let to_diagnostic =
+        |err: csv::Error| Diagnostic::error("Write csv fixture", format!("{:?}: {}", path, err));
+
+    let mut writer = csv::Writer::from_path(path).map_err(to_diagnostic)?;
+    writer
+        .write_record(label.properties.iter().map(|prop| prop.get_name()))
+        .map_err(to_diagnostic)?;
+
+    for _ in 0..constants::DEFAULT_CSV_ROWS {
+        let record = label.properties.iter().map(|prop| {
+            // empty field: null
+            match prop.random_stored_value(random) {
+                FieldValue::String(value) => value,
+                FieldValue::Null => String::new(),
+                value => value.to_string(),
+            }
+        });
+        writer.write_record(record).map_err(to_diagnostic)?;
+    }
+
+    writer
+        .flush()
+        .map_err(|err| Diagnostic::error("Write csv fixture", err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_csv_fixtures;
+    use crate::common::DataType;
+    use crate::meta::testing::pk_property;
+    use crate::meta::{GraphSchema, Label, LabelKind};
+
+    #[test]
+    fn test_write_csv_fixtures() {
+        let person = Label {
+            label_name: "Person".to_string(),
+            label_id: 0,
+            kind: LabelKind::Vertex,
+            properties: vec![pk_property("id", 0, DataType::Int32)],
+        };
+        // an introspected name is no path.
+        let odd = Label {
+            label_name: "../a`b".to_string(),
+            label_id: 1,
+            ..person.clone()
+        };
+        let labels = vec![person, odd];
+        let schema = GraphSchema::new("test".to_string(), labels);
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "cypher_smith_fixture_{}_{}",
+            std::process::id(),
+            nanos
+        ));
+        let files = write_csv_fixtures(&schema, dir.to_str().unwrap()).unwrap();
+        assert_eq!(files, ["0_Person.csv", "1____a_b.csv"]);
+        assert!(dir.join("1____a_b.csv").exists());
+
+        let content = std::fs::read_to_string(dir.join("0_Person.csv")).unwrap();
+        assert!(content.starts_with("id\n"));
+        assert_eq!(content.lines().count(), 21);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
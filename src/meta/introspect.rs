@@ -0,0 +1,276 @@
+use serde_json::{Map, Value};
+
+use super::{GraphSchema, Label, LabelKind};
+use crate::common::{DataType, LabelId, Property};
+
+#[derive(Debug, Default)]
+struct LabelSamples {
+    name: String,
+    samples: Vec<Map<String, Value>>,
+    // (src label name, dst label name), edges only.
+    relations: Vec<(String, String)>,
+}
+
+/// Infer a `GraphSchema` from the labels of a database and sampled properties.
+///
+/// A property is nullable if some sample lacks it, the primary key is a property
+/// present and distinct in every sample, preferring `id` like names.
+#[derive(Debug, Default)]
+pub struct SchemaInferrer {
+    vertex_labels: Vec<LabelSamples>,
+    edge_labels: Vec<LabelSamples>,
+}
+
+impl SchemaInferrer {
+    pub fn add_vertex_label(&mut self, name: &str) {
+        if find(&self.vertex_labels, name).is_none() {
+            self.vertex_labels.push(LabelSamples {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+    }
+
+    pub fn add_edge_label(&mut self, name: &str) {
+        if find(&self.edge_labels, name).is_none() {
+            self.edge_labels.push(LabelSamples {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+    }
+
+    pub fn add_vertex_sample(&mut self, label: &str, properties: &Map<String, Value>) {
+        self.add_vertex_label(label);
+        let idx = find(&self.vertex_labels, label).unwrap();
+        self.vertex_labels[idx].samples.push(properties.clone());
+    }
+
+    pub fn add_edge_sample(
+        &mut self,
+        label: &str,
+        src: &str,
+        dst: &str,
+        properties: &Map<String, Value>,
+    ) {
+        self.add_edge_label(label);
+        let idx = find(&self.edge_labels, label).unwrap();
+        let samples = &mut self.edge_labels[idx];
+        samples.samples.push(properties.clone());
+        let relation = (src.to_string(), dst.to_string());
+        if !samples.relations.contains(&relation) {
+            samples.relations.push(relation);
+        }
+    }
+
+    /// Vertex labels are numbered first, edge labels continue.
+    pub fn build(&self, name: &str) -> GraphSchema {
+        let mut labels = vec![];
+        for (idx, samples) in self.vertex_labels.iter().enumerate() {
+            labels.push(Label {
+                label_name: samples.name.clone(),
+                label_id: idx as LabelId,
+                kind: LabelKind::Vertex,
+                properties: infer_properties(&samples.samples),
+            });
+        }
+
+        let offset = self.vertex_labels.len();
+        for (idx, samples) in self.edge_labels.iter().enumerate() {
+            let relations = samples
+                .relations
+                .iter()
+                .filter_map(|(src, dst)| {
+                    Some((
+                        find(&self.vertex_labels, src)? as LabelId,
+                        find(&self.vertex_labels, dst)? as LabelId,
+                    ))
+                })
+                .collect();
+            labels.push(Label {
+                label_name: samples.name.clone(),
+                label_id: (offset + idx) as LabelId,
+                kind: LabelKind::Edge {
+                    relations,
+                    is_directed: true,
+                },
+                properties: infer_properties(&samples.samples),
+            });
+        }
+
+        GraphSchema::new(name.to_string(), labels)
+    }
+}
+
+fn find(labels: &[LabelSamples], name: &str) -> Option<usize> {
+    labels.iter().position(|label| label.name == name)
+}
+
+fn infer_properties(samples: &[Map<String, Value>]) -> Vec<Property> {
+    let mut names: Vec<&String> = vec![];
+    for sample in samples {
+        for name in sample.keys() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    let mut properties = names
+        .into_iter()
+        .filter_map(|name| {
+            let values = samples
+                .iter()
+                .filter_map(|sample| sample.get(name))
+                .filter(|value| !value.is_null())
+                .collect::<Vec<_>>();
+            Some(Property {
+                name: name.clone(),
+                prop_id: 0,
+                prop_type: infer_type(&values)?,
+                is_pk: false,
+                nullable: values.len() < samples.len(),
+                is_delete: false,
+                domain: None,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (idx, property) in properties.iter_mut().enumerate() {
+        property.prop_id = idx as _;
+    }
+
+    let candidates = properties
+        .iter()
+        .enumerate()
+        .filter(|(_, property)| is_pk_candidate(property, samples))
+        .map(|(idx, property)| (idx, property.name.to_lowercase()))
+        .collect::<Vec<_>>();
+    let pk = candidates
+        .iter()
+        .find(|(_, name)| name == "id")
+        .or_else(|| candidates.iter().find(|(_, name)| name.ends_with("id")))
+        .or_else(|| candidates.first());
+    if let Some((idx, _)) = pk {
+        properties[*idx].is_pk = true;
+    }
+
+    properties
+}
+
+/// `None` for lists, maps and properties without a value.
+fn infer_type(values: &[&Value]) -> Option<DataType> {
+    let mut d_type: Option<DataType> = None;
+    for value in values {
+        let value_type = match value {
+            Value::Bool(_) => DataType::Bool,
+            Value::Number(n) if n.is_i64() || n.is_u64() => match n.as_i64() {
+                Some(i) if i32::try_from(i).is_ok() => DataType::Int32,
+                _ => DataType::Int64,
+            },
+            Value::Number(_) => DataType::Double,
+            Value::String(_) => DataType::String,
+            _ => return None,
+        };
+        d_type = Some(match (d_type, value_type) {
+            (None, value_type) => value_type,
+            (Some(DataType::Int32), DataType::Int64) | (Some(DataType::Int64), DataType::Int32) => {
+                DataType::Int64
+            }
+            (Some(DataType::Int32 | DataType::Int64 | DataType::Double), DataType::Double)
+            | (Some(DataType::Double), DataType::Int32 | DataType::Int64) => DataType::Double,
+            (Some(d_type), value_type)
+                if std::mem::discriminant(&d_type) == std::mem::discriminant(&value_type) =>
+            {
+                d_type
+            }
+            // mixed values.
+            _ => DataType::String,
+        });
+    }
+    d_type
+}
+
+fn is_pk_candidate(property: &Property, samples: &[Map<String, Value>]) -> bool {
+    if samples.is_empty()
+        || property.nullable
+        || !matches!(
+            property.prop_type,
+            DataType::Int32 | DataType::Int64 | DataType::String
+        )
+    {
+        return false;
+    }
+    let mut values = samples
+        .iter()
+        .filter_map(|sample| sample.get(&property.name))
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>();
+    values.sort();
+    values.dedup();
+    values.len() == samples.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use super::SchemaInferrer;
+    use crate::common::DataType;
+    use crate::meta::LabelKind;
+
+    fn map(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_schema_inferrer() {
+        let mut inferrer = SchemaInferrer::default();
+        inferrer.add_vertex_label("Empty");
+        inferrer.add_vertex_sample(
+            "Person",
+            &map(json!({"name": "a", "person_id": 1, "age": 3})),
+        );
+        inferrer.add_vertex_sample(
+            "Person",
+            &map(json!({"name": "a", "person_id": 2, "age": 3.5})),
+        );
+        inferrer.add_vertex_sample("Person", &map(json!({"person_id": 3, "tags": [1]})));
+        inferrer.add_edge_sample("Knows", "Person", "Person", &map(json!({"since": 2000})));
+        inferrer.add_edge_sample("Knows", "Person", "Unknown", &map(json!({})));
+
+        let schema = inferrer.build("test");
+        assert_eq!(schema.vertex_labels().len(), 2);
+
+        let person = &schema.vertex_labels()[1];
+        assert_eq!(person.label_name, "Person");
+        let properties = person
+            .properties
+            .iter()
+            .map(|prop| {
+                (
+                    prop.name.as_str(),
+                    prop.prop_type,
+                    prop.is_pk,
+                    prop.nullable,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            properties[..],
+            [
+                ("age", DataType::Double, false, true),
+                ("name", DataType::String, false, true),
+                ("person_id", DataType::Int32, true, false),
+            ]
+        ));
+
+        let knows = &schema.edge_labels()[0];
+        assert_eq!(knows.label_id, 2);
+        match &knows.kind {
+            LabelKind::Edge { relations, .. } => assert_eq!(relations, &vec![(1, 1)]),
+            LabelKind::Vertex => panic!("edge label expected"),
+        }
+        assert!(knows.properties[0].nullable);
+    }
+}
@@ -102,6 +102,7 @@ mod tests {
                 is_pk: true,
                 nullable: false,
                 is_delete: false,
+                domain: None,
             },
             Property {
                 name: "name".to_string(),
@@ -110,6 +111,7 @@ mod tests {
                 is_pk: false,
                 nullable: true,
                 is_delete: false,
+                domain: None,
             },
         ];
 
@@ -133,6 +135,7 @@ mod tests {
             is_pk: true,
             nullable: false,
             is_delete: false,
+            domain: None,
         }];
         let edge_label = Label {
             label_name: "Knows".to_string(),
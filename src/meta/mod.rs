@@ -1,5 +1,12 @@
+mod fixture;
+mod introspect;
 mod label;
 mod schema;
+#[cfg(test)]
+pub mod testing;
+mod validate;
 
+pub use fixture::write_csv_fixtures;
+pub use introspect::SchemaInferrer;
 pub use label::{Label, LabelKind};
 pub use schema::GraphSchema;
@@ -43,6 +43,14 @@ impl GraphSchema {
 }
 
 impl GraphSchema {
+    pub fn vertex_labels(&self) -> &[Label] {
+        &self.vertex_labels
+    }
+
+    pub fn edge_labels(&self) -> &[Label] {
+        &self.edge_labels
+    }
+
     /// Get Vertex Label.
     pub fn rand_vertex_label(&self, random: &mut RandomGenerator) -> Label {
         let length = self.vertex_labels.len();
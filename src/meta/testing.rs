@@ -0,0 +1,60 @@
+use crate::common::{DataType, Property, PropertyId};
+use crate::meta::{GraphSchema, Label, LabelKind};
+
+/// A nullable property without a domain.
+pub fn property(name: &str, prop_id: PropertyId, prop_type: DataType) -> Property {
+    Property {
+        name: name.to_string(),
+        prop_id,
+        prop_type,
+        is_pk: false,
+        nullable: true,
+        is_delete: false,
+        domain: None,
+    }
+}
+
+/// A primary key property.
+pub fn pk_property(name: &str, prop_id: PropertyId, prop_type: DataType) -> Property {
+    Property {
+        is_pk: true,
+        nullable: false,
+        ..property(name, prop_id, prop_type)
+    }
+}
+
+/// Person {id: pk, name: nullable}.
+pub fn person() -> Label {
+    Label {
+        label_name: "Person".to_string(),
+        label_id: 1,
+        kind: LabelKind::Vertex,
+        properties: vec![
+            pk_property("id", 0, DataType::Int32),
+            property("name", 1, DataType::String),
+        ],
+    }
+}
+
+/// Knows(Person, Person) {edge_id: pk}.
+pub fn knows() -> Label {
+    Label {
+        label_name: "Knows".to_string(),
+        label_id: 3,
+        kind: LabelKind::Edge {
+            relations: vec![(1, 1)],
+            is_directed: true,
+        },
+        properties: vec![pk_property("edge_id", 0, DataType::Int32)],
+    }
+}
+
+/// Person, Company {id: pk, name: nullable} and Knows(Person, Person).
+pub fn test_schema() -> GraphSchema {
+    let company = Label {
+        label_name: "Company".to_string(),
+        label_id: 2,
+        ..person()
+    };
+    GraphSchema::new("test".to_string(), vec![person(), company, knows()])
+}
@@ -0,0 +1,306 @@
+use std::{collections::HashMap, path::Path};
+
+use super::{GraphSchema, Label, LabelKind};
+use crate::common::{DataType, Diagnostic, FieldValue, LabelId, Property, RESERVED_WORD};
+
+impl GraphSchema {
+    /// Read and validate a schema file, the diagnostics carry the file name.
+    pub fn from_file(path: &Path) -> Result<GraphSchema, Vec<Diagnostic>> {
+        let file = path.display();
+        let json = std::fs::read_to_string(path).map_err(|err| {
+            vec![Diagnostic::error(
+                "Read schema",
+                format!("{}: {}", file, err),
+            )]
+        })?;
+        let schema = serde_json::from_str::<GraphSchema>(&json).map_err(|err| {
+            vec![Diagnostic::error(
+                "Invalid schema JSON",
+                format!("{}: {}", file, err),
+            )]
+        })?;
+
+        let diagnostics = schema
+            .validate()
+            .into_iter()
+            .map(|diagnostic| Diagnostic {
+                detail: diagnostic
+                    .detail
+                    .map(|detail| format!("{}: {}", file, detail)),
+                ..diagnostic
+            })
+            .collect::<Vec<_>>();
+        if diagnostics.is_empty() {
+            Ok(schema)
+        } else {
+            Err(diagnostics)
+        }
+    }
+
+    /// Check the schema before generating queries from it.
+    /// The detail of each diagnostic starts with the field, e.g. `vertex_labels[0].label_id`.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        if self.vertex_labels().is_empty() {
+            diagnostics.push(Diagnostic::error(
+                "Empty label set",
+                "vertex_labels: at least one vertex label is required".to_string(),
+            ));
+        }
+        if self.edge_labels().is_empty() {
+            diagnostics.push(Diagnostic::error(
+                "Empty label set",
+                "edge_labels: at least one edge label is required".to_string(),
+            ));
+        }
+
+        let labels = self
+            .vertex_labels()
+            .iter()
+            .enumerate()
+            .map(|(idx, label)| (format!("vertex_labels[{}]", idx), label))
+            .chain(
+                self.edge_labels()
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, label)| (format!("edge_labels[{}]", idx), label)),
+            )
+            .collect::<Vec<_>>();
+
+        let mut ids: HashMap<LabelId, &str> = HashMap::new();
+        let mut names: HashMap<&str, &str> = HashMap::new();
+        for (field, label) in labels.iter() {
+            if let Some(first) = ids.insert(label.label_id, field) {
+                diagnostics.push(Diagnostic::error(
+                    "Duplicate label id",
+                    format!(
+                        "{}.label_id: {} is also used by {}",
+                        field, label.label_id, first
+                    ),
+                ));
+            }
+            if let Some(first) = names.insert(&label.label_name, field) {
+                diagnostics.push(Diagnostic::error(
+                    "Duplicate label name",
+                    format!(
+                        "{}.label_name: {} is also used by {}",
+                        field, label.label_name, first
+                    ),
+                ));
+            }
+            if RESERVED_WORD
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case(&label.label_name))
+            {
+                diagnostics.push(Diagnostic::error(
+                    "Reserved word label name",
+                    format!(
+                        "{}.label_name: {} is a reserved word",
+                        field, label.label_name
+                    ),
+                ));
+            }
+            diagnostics.extend(self.validate_relations(field, label));
+            diagnostics.extend(validate_properties(field, label));
+        }
+
+        diagnostics
+    }
+
+    fn validate_relations(&self, field: &str, label: &Label) -> Vec<Diagnostic> {
+        let relations = match &label.kind {
+            LabelKind::Edge { relations, .. } => relations,
+            LabelKind::Vertex => return vec![],
+        };
+
+        let mut diagnostics = vec![];
+        for (idx, (src, dst)) in relations.iter().enumerate() {
+            for label_id in [src, dst] {
+                if self
+                    .vertex_labels()
+                    .iter()
+                    .all(|vertex| vertex.label_id != *label_id)
+                {
+                    diagnostics.push(Diagnostic::error(
+                        "Unknown relation label",
+                        format!(
+                            "{}.kind.Edge.relations[{}]: no vertex label with id {}",
+                            field, idx, label_id
+                        ),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+fn validate_properties(field: &str, label: &Label) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut ids = HashMap::new();
+    for (idx, property) in label.properties.iter().enumerate() {
+        if let Some(first) = ids.insert(property.prop_id, idx) {
+            diagnostics.push(Diagnostic::error(
+                "Duplicate property id",
+                format!(
+                    "{}.properties[{}].prop_id: {} is also used by properties[{}]",
+                    field, idx, property.prop_id, first
+                ),
+            ));
+        }
+        if property.is_pk && property.nullable {
+            diagnostics.push(Diagnostic::error(
+                "Nullable primary key",
+                format!(
+                    "{}.properties[{}]: {} is both is_pk and nullable",
+                    field, idx, property.name
+                ),
+            ));
+        }
+        diagnostics.extend(validate_domain(
+            &format!("{}.properties[{}].domain", field, idx),
+            property,
+        ));
+        // a primary key needs a distinct value per vertex.
+        if property.is_pk && matches!(property.prop_type, DataType::Null | DataType::Bool) {
+            diagnostics.push(Diagnostic::error(
+                "Primary key type",
+                format!(
+                    "{}.properties[{}].prop_type: {} of type {:?} cannot be a unique primary key",
+                    field, idx, property.name, property.prop_type
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// The bounds of a domain are given together, its closed set and samples hold values of
+/// the property type.
+fn validate_domain(field: &str, property: &Property) -> Vec<Diagnostic> {
+    let domain = match &property.domain {
+        Some(domain) => domain,
+        None => return vec![],
+    };
+    let mut diagnostics = vec![];
+    if domain.min.is_some() != domain.max.is_some() {
+        diagnostics.push(Diagnostic::error(
+            "Half-open domain",
+            format!("{}: min and max must be given together", field),
+        ));
+    }
+    for (name, values) in [("values", &domain.values), ("samples", &domain.samples)] {
+        for (idx, value) in values.iter().enumerate() {
+            if !value.is_null()
+                && matches!(
+                    FieldValue::from_json(value, property.prop_type),
+                    FieldValue::Null
+                )
+            {
+                diagnostics.push(Diagnostic::error(
+                    "Invalid domain value",
+                    format!(
+                        "{}.{}[{}]: {} is not a {:?} value",
+                        field, name, idx, value, property.prop_type
+                    ),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{DataType, Property};
+    use crate::meta::testing::{pk_property, property};
+    use crate::meta::{GraphSchema, Label, LabelKind};
+
+    #[test]
+    fn test_validate() {
+        let labels = vec![
+            Label {
+                label_name: "Person".to_string(),
+                label_id: 0,
+                kind: LabelKind::Vertex,
+                properties: vec![
+                    pk_property("p0", 0, DataType::Int32),
+                    property("p1", 1, DataType::Int32),
+                ],
+            },
+            Label {
+                label_name: "Knows".to_string(),
+                label_id: 1,
+                kind: LabelKind::Edge {
+                    relations: vec![(0, 0)],
+                    is_directed: true,
+                },
+                properties: vec![],
+            },
+        ];
+        let schema = GraphSchema::new("test".to_string(), labels);
+        assert!(schema.validate().is_empty());
+
+        let labels = vec![
+            Label {
+                label_name: "Match".to_string(),
+                label_id: 0,
+                kind: LabelKind::Vertex,
+                properties: vec![
+                    Property {
+                        is_pk: true,
+                        ..property("p0", 0, DataType::Int32)
+                    },
+                    property("p0", 0, DataType::Int32),
+                    pk_property("p2", 2, DataType::Bool),
+                    Property {
+                        domain: Some(
+                            serde_json::from_str(r#"{"min": 1, "values": [1, "a", null]}"#)
+                                .unwrap(),
+                        ),
+                        ..property("p3", 3, DataType::Int32)
+                    },
+                ],
+            },
+            Label {
+                label_name: "Match".to_string(),
+                label_id: 0,
+                kind: LabelKind::Vertex,
+                properties: vec![],
+            },
+            Label {
+                label_name: "Knows".to_string(),
+                label_id: 1,
+                kind: LabelKind::Edge {
+                    relations: vec![(0, 7)],
+                    is_directed: true,
+                },
+                properties: vec![],
+            },
+        ];
+        let schema = GraphSchema::new("test".to_string(), labels);
+        let details = schema
+            .validate()
+            .into_iter()
+            .map(|diagnostic| diagnostic.detail.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            details,
+            vec![
+                "vertex_labels[0].label_name: Match is a reserved word",
+                "vertex_labels[0].properties[0]: p0 is both is_pk and nullable",
+                "vertex_labels[0].properties[1].prop_id: 0 is also used by properties[0]",
+                "vertex_labels[0].properties[2].prop_type: p2 of type Bool cannot be a unique primary key",
+                "vertex_labels[0].properties[3].domain: min and max must be given together",
+                "vertex_labels[0].properties[3].domain.values[1]: \"a\" is not a Int32 value",
+                "vertex_labels[1].label_id: 0 is also used by vertex_labels[0]",
+                "vertex_labels[1].label_name: Match is also used by vertex_labels[0]",
+                "vertex_labels[1].label_name: Match is a reserved word",
+                "edge_labels[0].kind.Edge.relations[0]: no vertex label with id 7",
+            ]
+        );
+
+        let schema = GraphSchema::new("test".to_string(), vec![]);
+        assert_eq!(schema.validate().len(), 2);
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::Outcome;
+use crate::ast::CypherNode;
+
+/// Failures sharing a signature, most likely the same bug.
+#[derive(Debug, Clone, Serialize)]
+pub struct Bucket {
+    pub signature: String,
+    pub outcome: Outcome,
+    pub count: u32,
+    // query numbers.
+    pub first_seen: u32,
+    pub last_seen: u32,
+    // the shortest query of the bucket, its seed and errors.
+    pub query: String,
+    pub seed: u64,
+    #[serde(serialize_with = "serialize_errors")]
+    pub errors: Vec<Value>,
+}
+
+// csv only supports flat records.
+fn serialize_errors<S: serde::Serializer>(errors: &[Value], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&serde_json::to_string(errors).unwrap_or_default())
+}
+
+/// Buckets of failures, in the order they were first seen.
+#[derive(Debug, Default)]
+pub struct Buckets {
+    buckets: Vec<Bucket>,
+    index: HashMap<String, usize>,
+}
+
+impl Buckets {
+    /// Add a failure of query number `query_number`, true if it opens a new bucket.
+    pub fn add(
+        &mut self,
+        query_number: u32,
+        ast: &CypherNode,
+        query: &str,
+        seed: u64,
+        outcome: Outcome,
+        errors: &[Value],
+    ) -> bool {
+        let signature = signature(ast, outcome, errors);
+        if let Some(idx) = self.index.get(&signature) {
+            let bucket = &mut self.buckets[*idx];
+            bucket.count += 1;
+            bucket.last_seen = query_number;
+            if query.len() < bucket.query.len() {
+                bucket.query = query.to_string();
+                bucket.seed = seed;
+                bucket.errors = errors.to_vec();
+            }
+            return false;
+        }
+
+        self.index.insert(signature.clone(), self.buckets.len());
+        self.buckets.push(Bucket {
+            signature,
+            outcome,
+            count: 1,
+            first_seen: query_number,
+            last_seen: query_number,
+            query: query.to_string(),
+            seed,
+            errors: errors.to_vec(),
+        });
+        true
+    }
+
+    /// Most frequent first.
+    pub fn buckets(&self) -> Vec<&Bucket> {
+        let mut buckets = self.buckets.iter().collect::<Vec<_>>();
+        buckets.sort_by_key(|bucket| std::cmp::Reverse(bucket.count));
+        buckets
+    }
+
+    pub fn report(&self) {
+        if self.buckets.is_empty() {
+            return;
+        }
+        println!(
+            "\nError buckets:\n{:>8} {:>8} {:>8} {:<8} signature / smallest query",
+            "COUNT", "FIRST", "LAST", "OUTCOME"
+        );
+        for bucket in self.buckets() {
+            println!(
+                "{:>8} {:>8} {:>8} {:<8} {}\n{:>35}{}",
+                bucket.count,
+                bucket.first_seen,
+                bucket.last_seen,
+                format!("{:?}", bucket.outcome),
+                bucket.signature,
+                "",
+                bucket.query
+            );
+        }
+    }
+}
+
+/// Normalized errors and the top level clauses of the query.
+pub fn signature(ast: &CypherNode, outcome: Outcome, errors: &[Value]) -> String {
+    let mut messages = errors
+        .iter()
+        .map(|error| {
+            let field = |name: &str| error.get(name).and_then(|value| value.as_str());
+            // the code of an engine is shared by the errors of a kind, the message tells
+            // them apart.
+            match (field("code"), field("message")) {
+                (Some(code), Some(message)) => format!("{} {}", code, normalize(message)),
+                (Some(code), None) => code.to_string(),
+                (None, Some(message)) => normalize(message),
+                (None, None) => normalize(&error.to_string()),
+            }
+        })
+        .collect::<Vec<_>>();
+    messages.dedup();
+
+    let mut shape = vec![];
+    clause_shape(ast, &mut shape);
+    format!(
+        "{:?}: {} [{}]",
+        outcome,
+        messages.join("; "),
+        shape.join(" ")
+    )
+}
+
+/// Replace quoted strings and numbers by `?`, collapse whitespaces.
+pub fn normalize(message: &str) -> String {
+    let mut normalized = String::new();
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                for quoted in chars.by_ref() {
+                    if quoted == c {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            '0'..='9' => {
+                while let Some('0'..='9' | '.') = chars.peek() {
+                    chars.next();
+                }
+                normalized.push('?');
+            }
+            c if c.is_whitespace() => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                normalized.push(' ');
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized.trim().to_string()
+}
+
+fn clause_shape(node: &CypherNode, shape: &mut Vec<&'static str>) {
+    let clauses = |nodes: &Vec<Box<CypherNode>>, shape: &mut Vec<&'static str>| {
+        for node in nodes {
+            clause_shape(node, shape);
+        }
+    };
+    match node {
+        CypherNode::Query { query } => clause_shape(query, shape),
+        CypherNode::RegularQuery {
+            single_query,
+            union_all,
+        } => {
+            clause_shape(single_query, shape);
+            clauses(union_all, shape);
+        }
+        CypherNode::Union {
+            union_all: Some((is_all, query)),
+        } => {
+            shape.push(if *is_all { "UNION ALL" } else { "UNION" });
+            clause_shape(query, shape);
+        }
+        CypherNode::SingleQuery { part_query } => clause_shape(part_query, shape),
+        CypherNode::SinglePartQuery {
+            reading_clauses,
+            updating_clauses,
+            return_clause,
+        } => {
+            clauses(reading_clauses, shape);
+            clauses(updating_clauses, shape);
+            if let Some(return_clause) = return_clause {
+                clause_shape(return_clause, shape);
+            }
+        }
+        CypherNode::MultiPartQuery {
+            multi_part,
+            single_part,
+        } => {
+            for (reading_clauses, updating_clauses, with) in multi_part {
+                clauses(reading_clauses, shape);
+                clauses(updating_clauses, shape);
+                clause_shape(with, shape);
+            }
+            clause_shape(single_part, shape);
+        }
+        CypherNode::ReadingClause { reading_clause } => clause_shape(reading_clause, shape),
+        CypherNode::UpdatingClause { updating_clause } => clause_shape(updating_clause, shape),
+        CypherNode::SchemaCommand { command } => clause_shape(command, shape),
+        CypherNode::Match { is_optional, .. } => shape.push(if *is_optional {
+            "OPTIONAL MATCH"
+        } else {
+            "MATCH"
+        }),
+        CypherNode::Delete { is_detach, .. } => shape.push(if *is_detach {
+            "DETACH DELETE"
+        } else {
+            "DELETE"
+        }),
+        CypherNode::With { .. } => shape.push("WITH"),
+        CypherNode::Return { .. } => shape.push("RETURN"),
+        CypherNode::Unwind { .. } => shape.push("UNWIND"),
+        CypherNode::InQueryCall { .. } | CypherNode::StandaloneCall { .. } => shape.push("CALL"),
+        CypherNode::CallSubquery { .. } => shape.push("CALL {}"),
+        CypherNode::LoadCsv { .. } => shape.push("LOAD CSV"),
+        CypherNode::Create { .. } => shape.push("CREATE"),
+        CypherNode::Merge { .. } => shape.push("MERGE"),
+        CypherNode::Foreach { .. } | CypherNode::ConditionalUpdate { .. } => shape.push("FOREACH"),
+        CypherNode::Set { .. } => shape.push("SET"),
+        CypherNode::Remove { .. } => shape.push("REMOVE"),
+        CypherNode::CreateIndex { .. } => shape.push("CREATE INDEX"),
+        CypherNode::DropIndex { .. } => shape.push("DROP INDEX"),
+        CypherNode::CreateConstraint { .. } => shape.push("CREATE CONSTRAINT"),
+        CypherNode::DropConstraint { .. } => shape.push("DROP CONSTRAINT"),
+        CypherNode::ShowSchema { .. } => shape.push("SHOW"),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{normalize, Buckets};
+    use crate::{ast::CypherNode, report::Outcome};
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("Variable `v12` not defined at 3:14,  near 'abc'"),
+            "Variable ? not defined at ?:?, near ?"
+        );
+        assert_eq!(normalize("Type mismatch: 1.5 "), "Type mismatch: ?");
+    }
+
+    #[test]
+    fn test_buckets() {
+        let ast = CypherNode::ShowSchema {
+            show_constraints: false,
+        };
+        let error = |message: &str| vec![json!({"level": "Error", "message": message})];
+
+        let mut buckets = Buckets::default();
+        assert!(buckets.add(
+            1,
+            &ast,
+            "SHOW INDEXES;",
+            1,
+            Outcome::Error,
+            &error("unknown `v1`")
+        ));
+        assert!(!buckets.add(4, &ast, "SHOW;", 2, Outcome::Error, &error("unknown `v2`")));
+        assert!(buckets.add(5, &ast, "SHOW;", 3, Outcome::Bug, &error("unknown `v2`")));
+
+        let bucket = buckets.buckets()[0];
+        assert_eq!(bucket.signature, "Error: unknown ? [SHOW]");
+        assert_eq!(
+            (bucket.count, bucket.first_seen, bucket.last_seen),
+            (2, 1, 4)
+        );
+        assert_eq!((bucket.query.as_str(), bucket.seed), ("SHOW;", 2));
+
+        // the errors of one code are told apart by their messages.
+        let coded = |message: &str| {
+            vec![json!({"code": "Neo.ClientError.Statement.SyntaxError", "message": message})]
+        };
+        assert!(buckets.add(
+            6,
+            &ast,
+            "SHOW;",
+            4,
+            Outcome::Error,
+            &coded("Invalid input 'x'")
+        ));
+        assert!(!buckets.add(
+            7,
+            &ast,
+            "SHOW;",
+            5,
+            Outcome::Error,
+            &coded("Invalid input 'y'")
+        ));
+        assert!(buckets.add(
+            8,
+            &ast,
+            "SHOW;",
+            6,
+            Outcome::Error,
+            &coded("Variable `v0` not defined")
+        ));
+    }
+}
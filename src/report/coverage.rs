@@ -0,0 +1,461 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::Outcome;
+use crate::{
+    ast::CypherNode,
+    common::{
+        BinOpKind, CmpKind, Diagnostic, Expr, ExprKind, Literal, PredicateFunctionKind, UnOpKind,
+    },
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureCount {
+    // queries using the feature.
+    pub generated: u32,
+    pub accepted: u32,
+    pub rejected: u32,
+}
+
+/// Grammar coverage: how often each `CypherNode` and `ExprKind` variant, operator,
+/// predicate function and function was generated, accepted and rejected by the target.
+///
+/// Features are named by their type, e.g. `CypherNode::Match`, `BinOpKind::Add`
+/// or `Function::toUpper`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coverage {
+    pub features: BTreeMap<String, FeatureCount>,
+}
+
+impl Default for Coverage {
+    /// All the known features, not generated yet. Functions are added once generated.
+    fn default() -> Self {
+        let mut features = BTreeMap::new();
+        for (kind, names) in [
+            ("CypherNode", CypherNode::VARIANTS),
+            ("ExprKind", ExprKind::VARIANTS),
+            ("BinOpKind", BinOpKind::VARIANTS),
+            ("UnOpKind", UnOpKind::VARIANTS),
+            ("CmpKind", CmpKind::VARIANTS),
+            ("PredicateFunctionKind", PredicateFunctionKind::VARIANTS),
+        ] {
+            for name in names {
+                features.insert(format!("{}::{}", kind, name), FeatureCount::default());
+            }
+        }
+        Coverage { features }
+    }
+}
+
+impl Coverage {
+    /// Features used by the query, each counted once.
+    pub fn features(ast: &CypherNode) -> BTreeSet<String> {
+        let mut features = BTreeSet::new();
+        visit_node(ast, &mut features);
+        features
+    }
+
+    /// Count the features of a query, `None` if it was not executed.
+    pub fn record(&mut self, features: &BTreeSet<String>, outcome: Option<Outcome>) {
+        for feature in features {
+            let count = self.features.entry(feature.clone()).or_default();
+            count.generated += 1;
+            match outcome {
+                Some(Outcome::Success | Outcome::Warning) => count.accepted += 1,
+                Some(Outcome::Error | Outcome::Bug) => count.rejected += 1,
+                Some(Outcome::Failure | Outcome::Timeout | Outcome::Crash | Outcome::LogicBug)
+                | None => {}
+            }
+        }
+    }
+
+    /// Features never generated, or generated but never accepted.
+    pub fn gaps(&self) -> (Vec<&str>, Vec<&str>) {
+        let never_generated = self
+            .features
+            .iter()
+            .filter(|(_, count)| count.generated == 0)
+            .map(|(feature, _)| feature.as_str())
+            .collect();
+        let never_accepted = self
+            .features
+            .iter()
+            .filter(|(_, count)| count.generated > 0 && count.accepted == 0 && count.rejected > 0)
+            .map(|(feature, _)| feature.as_str())
+            .collect();
+        (never_generated, never_accepted)
+    }
+
+    pub fn report(&self) {
+        let generated = self
+            .features
+            .values()
+            .filter(|count| count.generated > 0)
+            .count();
+        println!(
+            "\nGrammar coverage: {}/{} features generated",
+            generated,
+            self.features.len()
+        );
+        println!(
+            "{:<40} {:>10} {:>10} {:>10}",
+            "FEATURE", "GENERATED", "ACCEPTED", "REJECTED"
+        );
+        for (feature, count) in self.features.iter() {
+            println!(
+                "{:<40} {:>10} {:>10} {:>10}",
+                feature, count.generated, count.accepted, count.rejected
+            );
+        }
+
+        let (never_generated, never_accepted) = self.gaps();
+        if !never_generated.is_empty() {
+            println!("Never generated: {}", never_generated.join(", "));
+        }
+        if !never_accepted.is_empty() {
+            println!("Never accepted: {}", never_accepted.join(", "));
+        }
+    }
+
+    /// Write the coverage as JSON.
+    pub fn export(&self, path: &str) -> Result<(), Diagnostic> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| Diagnostic::bug("Serialize coverage", err.to_string()))?;
+        std::fs::write(path, json)
+            .map_err(|err| Diagnostic::error("Write coverage", format!("{}: {}", path, err)))
+    }
+}
+
+fn visit_nodes<'a>(
+    nodes: impl IntoIterator<Item = &'a Box<CypherNode>>,
+    features: &mut BTreeSet<String>,
+) {
+    for node in nodes {
+        visit_node(node, features);
+    }
+}
+
+fn visit_exprs<'a>(exprs: impl IntoIterator<Item = &'a Expr>, features: &mut BTreeSet<String>) {
+    for expr in exprs {
+        visit_expr(expr, features);
+    }
+}
+
+fn visit_node(node: &CypherNode, features: &mut BTreeSet<String>) {
+    features.insert(format!("CypherNode::{}", node.name()));
+    match node {
+        CypherNode::Query { query } => visit_node(query, features),
+        CypherNode::RegularQuery {
+            single_query,
+            union_all,
+        } => {
+            visit_node(single_query, features);
+            visit_nodes(union_all, features);
+        }
+        CypherNode::StandaloneCall {
+            procedure,
+            yield_items,
+        } => {
+            visit_node(procedure, features);
+            visit_nodes(&yield_items.1, features);
+        }
+        CypherNode::SingleQuery { part_query } => visit_node(part_query, features),
+        CypherNode::SinglePartQuery {
+            reading_clauses,
+            updating_clauses,
+            return_clause,
+        } => {
+            visit_nodes(reading_clauses, features);
+            visit_nodes(updating_clauses, features);
+            visit_nodes(return_clause, features);
+        }
+        CypherNode::MultiPartQuery {
+            multi_part,
+            single_part,
+        } => {
+            for (reading_clauses, updating_clauses, with) in multi_part {
+                visit_nodes(reading_clauses, features);
+                visit_nodes(updating_clauses, features);
+                visit_node(with, features);
+            }
+            visit_node(single_part, features);
+        }
+        CypherNode::With {
+            projection_body,
+            where_clause,
+        } => {
+            visit_node(projection_body, features);
+            visit_exprs(where_clause, features);
+        }
+        CypherNode::Union { union_all } => {
+            if let Some((_, query)) = union_all {
+                visit_node(query, features);
+            }
+        }
+        CypherNode::ReadingClause { reading_clause } => visit_node(reading_clause, features),
+        CypherNode::UpdatingClause { updating_clause } => visit_node(updating_clause, features),
+        CypherNode::Return { projection_body } => visit_node(projection_body, features),
+        CypherNode::ProjectionBody {
+            projection_items,
+            order,
+            skip,
+            limit,
+            ..
+        } => {
+            visit_node(projection_items, features);
+            visit_nodes(order, features);
+            visit_exprs(skip, features);
+            visit_exprs(limit, features);
+        }
+        CypherNode::ProjectionItems { expressions, .. } => {
+            visit_exprs(expressions.iter().map(|(expr, _)| expr), features)
+        }
+        CypherNode::Order { sort_items } => {
+            visit_exprs(sort_items.iter().map(|(expr, _)| expr), features)
+        }
+        CypherNode::Match {
+            pattern,
+            where_clause,
+            ..
+        } => {
+            visit_node(pattern, features);
+            visit_exprs(where_clause, features);
+        }
+        CypherNode::Unwind { expression, .. } => visit_expr(expression, features),
+        CypherNode::InQueryCall {
+            explicit_proceduce_invocation,
+            yield_items,
+        } => {
+            visit_node(explicit_proceduce_invocation, features);
+            visit_nodes(yield_items, features);
+        }
+        CypherNode::CallSubquery { query, .. } => visit_node(query, features),
+        CypherNode::Create { pattern } => visit_node(pattern, features),
+        CypherNode::Merge {
+            pattern_part,
+            merge_actions,
+        } => {
+            visit_node(pattern_part, features);
+            visit_nodes(merge_actions.iter().map(|(_, action)| action), features);
+        }
+        CypherNode::Foreach {
+            expression,
+            updating_clauses,
+            ..
+        } => {
+            visit_expr(expression, features);
+            visit_nodes(updating_clauses, features);
+        }
+        CypherNode::ConditionalUpdate {
+            condition,
+            updating_clauses,
+            ..
+        } => {
+            visit_expr(condition, features);
+            visit_nodes(updating_clauses, features);
+        }
+        CypherNode::Delete { expressions, .. } => visit_exprs(expressions, features),
+        CypherNode::Set {
+            property_set,
+            variable_set,
+            variable_add,
+            ..
+        } => {
+            for (property, value) in property_set {
+                visit_expr(property, features);
+                visit_expr(value, features);
+            }
+            visit_exprs(
+                variable_set
+                    .iter()
+                    .chain(variable_add.iter())
+                    .map(|(_, expr)| expr),
+                features,
+            );
+        }
+        CypherNode::ExplicitProcedureInvocation { expressions, .. } => {
+            visit_exprs(expressions, features)
+        }
+        CypherNode::YieldItems { where_clause, .. } => visit_exprs(where_clause, features),
+        CypherNode::Remove {
+            property_remove, ..
+        } => visit_exprs(property_remove, features),
+        CypherNode::SchemaCommand { command } => visit_node(command, features),
+        CypherNode::Pattern { pattern_parts } => visit_nodes(pattern_parts, features),
+        CypherNode::PatternPart {
+            selector,
+            path_mode,
+            pattern_element,
+            ..
+        } => {
+            if let Some(selector) = selector {
+                // without the number of paths or groups.
+                let selector = format!("{:?}", selector);
+                let name = selector.split('(').next().unwrap_or_default();
+                features.insert(format!("PathSelector::{}", name));
+            }
+            if let Some(path_mode) = path_mode {
+                features.insert(format!("PathMode::{:?}", path_mode));
+            }
+            visit_node(pattern_element, features)
+        }
+        CypherNode::PatternElement {
+            pattern_element: (node, chain),
+            ..
+        } => {
+            visit_node(node, features);
+            for (relationship, node) in chain {
+                visit_node(relationship, features);
+                visit_node(node, features);
+            }
+        }
+        CypherNode::LoadCsv { .. }
+        | CypherNode::ImplicitProcedureInvocation { .. }
+        | CypherNode::CreateIndex { .. }
+        | CypherNode::DropIndex { .. }
+        | CypherNode::CreateConstraint { .. }
+        | CypherNode::DropConstraint { .. }
+        | CypherNode::ShowSchema { .. }
+        | CypherNode::NodePattern { .. }
+        | CypherNode::RelationshipPattern { .. } => {}
+        CypherNode::QuantifiedPathPattern {
+            pattern_element,
+            where_clause,
+            ..
+        } => {
+            visit_node(pattern_element, features);
+            visit_exprs(where_clause, features);
+        }
+    }
+}
+
+fn visit_expr(expr: &Expr, features: &mut BTreeSet<String>) {
+    let kind = match &expr.kind {
+        ExprKind::BinOp(kind, lhs, rhs) => {
+            features.insert(format!("BinOpKind::{:?}", kind));
+            visit_expr(lhs, features);
+            visit_expr(rhs, features);
+            "BinOp"
+        }
+        ExprKind::UnOp(kind, expr) => {
+            features.insert(format!("UnOpKind::{:?}", kind));
+            visit_expr(expr, features);
+            "UnOp"
+        }
+        ExprKind::Cmp(expr, tails) => {
+            visit_expr(expr, features);
+            for (kind, expr) in tails {
+                features.insert(format!("CmpKind::{:?}", kind));
+                visit_expr(expr, features);
+            }
+            "Cmp"
+        }
+        ExprKind::Lit(literal) => {
+            match literal {
+                Literal::List(items) => visit_exprs(items, features),
+                Literal::Map(entries) => {
+                    visit_exprs(entries.iter().map(|(_, expr)| expr), features)
+                }
+                _ => {}
+            }
+            "Lit"
+        }
+        ExprKind::Variable(_) => "Variable",
+        ExprKind::PredicateVariable(_) => "PredicateVariable",
+        ExprKind::Case(expr, alternatives, else_expr) => {
+            visit_exprs(expr.iter().map(|expr| expr.as_ref()), features);
+            for alternative in alternatives {
+                visit_expr(&alternative.condition, features);
+                visit_expr(&alternative.value, features);
+            }
+            visit_exprs(else_expr.iter().map(|expr| expr.as_ref()), features);
+            "Case"
+        }
+        ExprKind::Property(expr, _) => {
+            visit_expr(expr, features);
+            "Property"
+        }
+        ExprKind::Label(expr, _) => {
+            visit_expr(expr, features);
+            "Label"
+        }
+        ExprKind::Invocation(name, _, args) => {
+            features.insert(format!("Function::{}", name));
+            visit_exprs(args, features);
+            "Invocation"
+        }
+        ExprKind::FilterExpression(_, in_expr, where_expr) => {
+            visit_expr(in_expr, features);
+            visit_exprs(where_expr.iter().map(|expr| expr.as_ref()), features);
+            "FilterExpression"
+        }
+        ExprKind::PredicateFunction(kind, expr) => {
+            features.insert(format!("PredicateFunctionKind::{:?}", kind));
+            visit_expr(expr, features);
+            "PredicateFunction"
+        }
+        ExprKind::SubQuery(kind, query, where_clause) => {
+            features.insert(format!("SubQueryKind::{:?}", kind));
+            visit_node(query, features);
+            visit_exprs(where_clause.iter().map(|expr| expr.as_ref()), features);
+            "SubQuery"
+        }
+        ExprKind::TypePredicate(expr, _, _) => {
+            visit_expr(expr, features);
+            "TypePredicate"
+        }
+    };
+    features.insert(format!("ExprKind::{}", kind));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Coverage;
+    use crate::{
+        ast::CypherNode,
+        common::{BinOpKind, Expr, ExprKind, Literal, Variable},
+        report::Outcome,
+    };
+
+    #[test]
+    fn test_coverage() {
+        let literal = |value| Box::new(Expr::from(ExprKind::Lit(Literal::Integer(value))));
+        let unwind = CypherNode::ReadingClause {
+            reading_clause: Box::new(CypherNode::Unwind {
+                expression: Expr::from(ExprKind::BinOp(BinOpKind::Add, literal(1), literal(2))),
+                variable: Variable::new("v0".to_string()),
+            }),
+        };
+
+        let features = Coverage::features(&unwind);
+        assert_eq!(
+            features.into_iter().collect::<Vec<_>>(),
+            vec![
+                "BinOpKind::Add",
+                "CypherNode::ReadingClause",
+                "CypherNode::Unwind",
+                "ExprKind::BinOp",
+                "ExprKind::Lit",
+            ]
+        );
+
+        let mut coverage = Coverage::default();
+        coverage.record(&Coverage::features(&unwind), Some(Outcome::Error));
+        coverage.record(&Coverage::features(&unwind), None);
+        let count = coverage.features["BinOpKind::Add"];
+        assert_eq!((count.generated, count.accepted, count.rejected), (2, 0, 1));
+
+        let (never_generated, never_accepted) = coverage.gaps();
+        assert!(never_generated.contains(&"BinOpKind::Sub"));
+        assert!(never_accepted.contains(&"CypherNode::Unwind"));
+
+        // the known features are the variants of the enums.
+        assert_eq!(BinOpKind::VARIANTS.len(), 16);
+        assert!(BinOpKind::VARIANTS.contains(&format!("{:?}", BinOpKind::Range).as_str()));
+        assert!(coverage.features.contains_key("ExprKind::TypePredicate"));
+        assert!(coverage
+            .features
+            .contains_key("PredicateFunctionKind::Single"));
+    }
+}
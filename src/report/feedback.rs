@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Outcome;
+use crate::{common::constants, config::Weights};
+
+/// Productions of `Weights` which generate a coverage feature.
+fn productions(feature: &str) -> &'static [&'static str] {
+    match feature {
+        "CypherNode::Match" => &["match_clause", "optional_match"],
+        "CypherNode::Unwind" => &["unwind"],
+        "CypherNode::CallSubquery" => &["call_subquery"],
+        "CypherNode::LoadCsv" => &["load_csv"],
+        "CypherNode::Create" => &["create"],
+        "CypherNode::Merge" => &["merge"],
+        "CypherNode::Delete" => &["delete"],
+        "CypherNode::Set" => &["set"],
+        "CypherNode::Remove" => &["remove"],
+        "CypherNode::Foreach" => &["foreach"],
+        "CypherNode::ConditionalUpdate" => &["conditional_update"],
+        "ExprKind::Lit" => &["literal"],
+        "ExprKind::Variable" => &["variable"],
+        "ExprKind::Case" => &["case"],
+        // also the filter of the predicate functions.
+        "ExprKind::FilterExpression" => &["list_comprehension"],
+        "ExprKind::PredicateFunction" => &["predicate_function"],
+        "ExprKind::Property" => &["property_lookup"],
+        "ExprKind::Label" => &["node_labels"],
+        "SubQueryKind::PredicatePattern" => &["pattern_comprehension"],
+        "SubQueryKind::RelationShipsPattern" => &["relationships_pattern"],
+        "SubQueryKind::Exists" => &["exists_subquery"],
+        "SubQueryKind::Count" => &["count_subquery"],
+        "ExprKind::TypePredicate" => &["type_predicate"],
+        "CypherNode::QuantifiedPathPattern" => &["quantified_path"],
+        feature if feature.starts_with("PathSelector::") => &["path_selector"],
+        "BinOpKind::Or" => &["or"],
+        "BinOpKind::Xor" => &["xor"],
+        "BinOpKind::And" => &["and"],
+        "BinOpKind::Add" | "BinOpKind::Sub" => &["add_subtract"],
+        "BinOpKind::Mul" | "BinOpKind::Div" | "BinOpKind::Mod" => &["multiply_divide_modulo"],
+        "BinOpKind::Pow" => &["power"],
+        "BinOpKind::Contains" | "BinOpKind::StartsWith" | "BinOpKind::EndsWith" => {
+            &["string_operator"]
+        }
+        "BinOpKind::In" | "BinOpKind::Index" => &["list_operator"],
+        "UnOpKind::Not" => &["not"],
+        "UnOpKind::Pos" | "UnOpKind::Neg" => &["unary"],
+        "UnOpKind::Null" | "UnOpKind::NotNull" => &["null_operator"],
+        "UnOpKind::Parentheses" => &["parenthesized"],
+        feature if feature.starts_with("CmpKind::") => &["comparison"],
+        _ => &[],
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ProductionCount {
+    used: u32,
+    accepted: u32,
+    novel: u32,
+}
+
+/// Adapt the weights of the productions to the results of the queries using them.
+///
+/// Productions of accepted queries or of novel queries, which open a new error bucket
+/// or show a plan operator not seen before, get up to 4 times their configured weight,
+/// productions whose queries are always rejected get a quarter of it. Disabled
+/// productions stay disabled, the others at least 1.
+#[derive(Debug)]
+pub struct Feedback {
+    base: Weights,
+    counts: BTreeMap<&'static str, ProductionCount>,
+    // queries recorded since the weights were last adapted.
+    pending: u32,
+}
+
+impl Feedback {
+    pub fn new(base: Weights) -> Self {
+        Feedback {
+            base,
+            counts: BTreeMap::new(),
+            pending: 0,
+        }
+    }
+
+    pub fn record(&mut self, features: &BTreeSet<String>, outcome: Outcome, novel: bool) {
+        self.pending += 1;
+        if matches!(
+            outcome,
+            Outcome::Failure | Outcome::Timeout | Outcome::Crash | Outcome::LogicBug
+        ) {
+            return;
+        }
+        let mut used = features
+            .iter()
+            .flat_map(|feature| productions(feature).iter())
+            .collect::<Vec<_>>();
+        used.sort_unstable();
+        used.dedup();
+
+        for production in used {
+            let count = self.counts.entry(production).or_default();
+            count.used += 1;
+            if matches!(outcome, Outcome::Success | Outcome::Warning) {
+                count.accepted += 1;
+            }
+            if novel {
+                count.novel += 1;
+            }
+        }
+    }
+
+    /// The adapted weights once every `DEFAULT_ADAPT_INTERVAL` recorded queries.
+    pub fn next_weights(&mut self) -> Option<Weights> {
+        if self.pending < constants::DEFAULT_ADAPT_INTERVAL {
+            return None;
+        }
+        self.pending = 0;
+        Some(self.adapt())
+    }
+
+    /// The configured weights scaled by the results so far.
+    pub fn adapt(&self) -> Weights {
+        let mut weights = self.base.clone();
+        for (production, base) in self.base.productions() {
+            let count = match self.counts.get(production) {
+                Some(count) if base > 0 && count.used >= constants::DEFAULT_ADAPT_SAMPLES => count,
+                _ => continue,
+            };
+            let accepted = count.accepted as f64 / count.used as f64;
+            let novel = count.novel as f64 / count.used as f64;
+            let factor = (0.25 + 1.5 * accepted + 4.0 * novel).clamp(0.25, 4.0);
+            let weight = (base as f64 * factor).round() as u32;
+            let known = weights.set(production, weight.max(1));
+            debug_assert!(known, "unknown production {}", production);
+        }
+        weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::Feedback;
+    use crate::{config::Weights, report::Outcome};
+
+    #[test]
+    fn test_adapt() {
+        let base = Weights {
+            merge: 0,
+            ..Default::default()
+        };
+        let mut feedback = Feedback::new(base.clone());
+        let features = |features: &[&str]| {
+            features
+                .iter()
+                .map(|feature| feature.to_string())
+                .collect::<BTreeSet<_>>()
+        };
+
+        for idx in 0..40 {
+            feedback.record(
+                &features(&["CypherNode::Unwind", "CmpKind::Eq", "CmpKind::Lt"]),
+                Outcome::Success,
+                false,
+            );
+            feedback.record(
+                &features(&["CypherNode::Create", "CypherNode::Merge"]),
+                Outcome::Error,
+                false,
+            );
+            feedback.record(&features(&["BinOpKind::Pow"]), Outcome::Bug, idx % 4 == 0);
+        }
+        feedback.record(&features(&["CypherNode::Remove"]), Outcome::Error, false);
+
+        let weights = feedback.next_weights().unwrap();
+        assert!(feedback.next_weights().is_none());
+        assert!(weights.unwind > base.unwind);
+        assert!(weights.comparison > base.comparison);
+        assert!(weights.create < base.create);
+        assert!(weights.power > base.power);
+        assert_eq!(weights.merge, 0);
+        // not enough samples.
+        assert_eq!(weights.remove, base.remove);
+        // within a quarter and 4 times the configured weight of each production.
+        for ((_, weight), (_, base)) in weights.productions().into_iter().zip(base.productions()) {
+            assert!(weight <= 4 * base && 4 * weight + 2 >= base);
+        }
+    }
+}
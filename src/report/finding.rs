@@ -0,0 +1,216 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ast::{Mutation, Rewrite},
+    common::Diagnostic,
+    config::{PlanMode, Weights},
+    db::{Plan, QueryResult},
+};
+
+/// Outcome category of an executed query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Success,
+    // only warnings were returned.
+    Warning,
+    Error,
+    // the server reported an internal error.
+    Bug,
+    // the statement did not return a result, e.g. the RPC failed.
+    Failure,
+    // no result within the timeout of the stress profile.
+    Timeout,
+    // the server stopped answering after the statement.
+    Crash,
+    // a semantics preserving rewrite of the query returned other rows.
+    LogicBug,
+}
+
+impl Outcome {
+    /// The most severe level of the errors, `{"level": "Error", "message": .., "detail": ..}`.
+    pub fn of(result: &QueryResult) -> Self {
+        result
+            .errors
+            .iter()
+            .map(
+                |error| match error.get("level").and_then(|level| level.as_str()) {
+                    Some("Info") => Outcome::Success,
+                    Some("Warn") | Some("Warning") => Outcome::Warning,
+                    Some("Bug") | Some("Internal Error") => Outcome::Bug,
+                    _ => Outcome::Error,
+                },
+            )
+            .max_by_key(|outcome| *outcome as u8)
+            .unwrap_or(Outcome::Success)
+    }
+}
+
+/// Status codes of the syntax errors of the targets: Neo4j, the GQLSTATUS of ISO GQL.
+const SYNTAX_ERROR_CODES: &[&str] = &["Neo.ClientError.Statement.SyntaxError", "42001"];
+
+/// Level of the syntax errors of AtlasGraph.
+const SYNTAX_ERROR_LEVEL: &str = "SyntaxError";
+
+/// The errors report a syntax error, the response expected for a mutated query: the
+/// `level` of AtlasGraph, or the `code` or `gql_status` of the other targets.
+pub fn is_syntax_error(errors: &[Value]) -> bool {
+    let field = |error: &Value, name: &str| {
+        error
+            .get(name)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+    };
+    errors.iter().any(|error| {
+        field(error, "level").as_deref() == Some(SYNTAX_ERROR_LEVEL)
+            || ["code", "gql_status"].iter().any(|name| {
+                field(error, name).is_some_and(|code| SYNTAX_ERROR_CODES.contains(&code.as_str()))
+            })
+    })
+}
+
+/// A query which did not succeed, one line of the JSON Lines report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub query: String,
+    // reproduces the query with the same schema and config.
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub errors: Vec<Value>,
+    pub latency_ms: f64,
+    pub backend: String,
+    pub server_version: Option<String>,
+    // the serialized `CypherNode`.
+    pub ast: Value,
+    // the query is the render of the AST mutated into an invalid one.
+    #[serde(default)]
+    pub mutation: Option<Mutation>,
+    // the rewrite of a `LogicBug` and the query it returned other rows for.
+    #[serde(default)]
+    pub rewrite: Option<(Rewrite, String)>,
+    // the plan of the query, when captured, see `PlanMode`.
+    #[serde(default)]
+    pub plan: Option<Plan>,
+    // how the plan was captured, the target executed `PROFILE` and the query with
+    // `Profile`.
+    #[serde(default)]
+    pub plan_mode: Option<PlanMode>,
+    // the weights the query was generated with, when adapted, see `adaptive`.
+    #[serde(default)]
+    pub weights: Option<Weights>,
+}
+
+pub struct FindingWriter {
+    file: BufWriter<File>,
+}
+
+impl FindingWriter {
+    pub fn new(path: &str) -> Result<Self, Diagnostic> {
+        let file = File::create(path)
+            .map_err(|err| Diagnostic::error("Create report", format!("{}: {}", path, err)))?;
+        Ok(FindingWriter {
+            file: BufWriter::new(file),
+        })
+    }
+
+    /// Append a record, flushed so that an aborted run keeps its findings.
+    pub fn write(&mut self, finding: &Finding) -> Result<(), Diagnostic> {
+        let line = serde_json::to_string(finding)
+            .map_err(|err| Diagnostic::bug("Serialize finding", err.to_string()))?;
+        writeln!(self.file, "{}", line)
+            .and_then(|_| self.file.flush())
+            .map_err(|err| Diagnostic::error("Write report", err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_syntax_error, Finding, Outcome};
+    use crate::{
+        ast::{Mutation, Rewrite},
+        config::{PlanMode, Weights},
+        db::{Plan, QueryResult},
+    };
+
+    #[test]
+    fn test_outcome() {
+        let outcome = |result: &str| Outcome::of(&QueryResult::parse(result).unwrap());
+        assert_eq!(
+            outcome(r#"{"results": [], "errors": []}"#),
+            Outcome::Success
+        );
+        assert_eq!(
+            outcome(r#"{"errors": [{"level": "Warn", "message": "a", "detail": ""}]}"#),
+            Outcome::Warning
+        );
+        assert_eq!(
+            outcome(
+                r#"{"errors": [{"level": "Bug", "message": "a"}, {"level": "Error", "message": "b"}]}"#
+            ),
+            Outcome::Bug
+        );
+        assert_eq!(outcome(r#"{"errors": [{"message": "a"}]}"#), Outcome::Error);
+
+        let errors = |result: &str| QueryResult::parse(result).unwrap().errors;
+        assert!(is_syntax_error(&errors(
+            r#"{"errors": [{"level": "SyntaxError", "message": "Invalid input ')'"}]}"#
+        )));
+        assert!(is_syntax_error(&errors(
+            r#"{"errors": [{"code": "Neo.ClientError.Statement.SyntaxError", "message": "a"}]}"#
+        )));
+        assert!(is_syntax_error(&errors(
+            r#"{"errors": [{"level": "Error", "gql_status": "42001", "message": "a"}]}"#
+        )));
+        // the message does not tell a syntax error.
+        assert!(!is_syntax_error(&errors(
+            r#"{"errors": [{"level": "Error", "message": "Invalid input ')': expected an expression"}]}"#
+        )));
+        assert!(!is_syntax_error(&errors(
+            r#"{"errors": [{"level": "Error", "message": "Variable `v0` not defined"}]}"#
+        )));
+        assert_eq!(
+            outcome(r#"{"errors": [{"level": "SyntaxError", "message": "a"}]}"#),
+            Outcome::Error
+        );
+    }
+
+    #[test]
+    fn test_finding_serialize() {
+        let finding = Finding {
+            query: "RETURN 1;".to_string(),
+            seed: 7,
+            outcome: Outcome::Error,
+            errors: vec![serde_json::json!({"level": "Error", "message": "bad"})],
+            latency_ms: 1.5,
+            backend: "atlas://127.0.0.1:8080".to_string(),
+            server_version: None,
+            ast: serde_json::json!({"ShowSchema": {"show_constraints": false}}),
+            mutation: Some(Mutation::Swap),
+            rewrite: Some((
+                Rewrite::WrapCall,
+                "CALL { RETURN 1 AS c0 } RETURN c0;".to_string(),
+            )),
+            plan: Some(Plan {
+                operator: "ProduceResults".to_string(),
+                children: vec![],
+            }),
+            plan_mode: Some(PlanMode::Profile),
+            weights: Some(Weights::default()),
+        };
+        let line = serde_json::to_string(&finding).unwrap();
+        assert!(!line.contains('\n'));
+        let finding = serde_json::from_str::<Finding>(&line).unwrap();
+        assert_eq!(finding.outcome, Outcome::Error);
+        assert_eq!(finding.errors.len(), 1);
+        assert_eq!(finding.mutation, Some(Mutation::Swap));
+        assert_eq!(finding.rewrite.unwrap().0, Rewrite::WrapCall);
+        assert_eq!(finding.plan.unwrap().operator, "ProduceResults");
+        assert_eq!(finding.plan_mode, Some(PlanMode::Profile));
+        assert_eq!(finding.weights, Some(Weights::default()));
+    }
+}
@@ -0,0 +1,11 @@
+mod bucket;
+mod coverage;
+mod feedback;
+mod finding;
+mod plan;
+
+pub use bucket::Buckets;
+pub use coverage::Coverage;
+pub use feedback::Feedback;
+pub use finding::{is_syntax_error, Finding, FindingWriter, Outcome};
+pub use plan::PlanCoverage;
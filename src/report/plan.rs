@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::db::Plan;
+
+/// Plan coverage: how often each physical operator appeared in the plans of the
+/// queries, and how many distinct operator trees they had.
+#[derive(Debug, Default, Clone)]
+pub struct PlanCoverage {
+    // plans of each operator.
+    pub operators: BTreeMap<String, u32>,
+    // the distinct shapes, e.g. `ProduceResults(Filter(AllNodesScan))`.
+    pub shapes: BTreeSet<String>,
+    pub plans: u32,
+}
+
+impl PlanCoverage {
+    /// Count the operators of a plan, true if one of them is new.
+    pub fn record(&mut self, plan: &Plan) -> bool {
+        self.plans += 1;
+        self.shapes.insert(plan.to_string());
+        let mut new_operator = false;
+        for operator in plan.operators() {
+            let count = self.operators.entry(operator.to_string()).or_insert(0);
+            new_operator |= *count == 0;
+            *count += 1;
+        }
+        new_operator
+    }
+
+    pub fn report(&self) {
+        println!(
+            "\nPlan coverage: {} operators, {} distinct plans of {} plans",
+            self.operators.len(),
+            self.shapes.len(),
+            self.plans
+        );
+        println!("{:<40} {:>10}", "OPERATOR", "PLANS");
+        for (operator, count) in self.operators.iter() {
+            println!("{:<40} {:>10}", operator, count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PlanCoverage;
+    use crate::db::Plan;
+
+    #[test]
+    fn test_plan_coverage() {
+        let plan = |operators: &[&str]| {
+            operators.iter().rev().fold(None, |child, operator| {
+                Some(Plan {
+                    operator: operator.to_string(),
+                    children: child.into_iter().collect(),
+                })
+            })
+        };
+        let scan = plan(&["ProduceResults", "AllNodesScan"]).unwrap();
+        let filter = plan(&["ProduceResults", "Filter", "AllNodesScan"]).unwrap();
+
+        let mut coverage = PlanCoverage::default();
+        assert!(coverage.record(&scan));
+        assert!(!coverage.record(&scan));
+        assert!(coverage.record(&filter));
+        assert_eq!(coverage.plans, 3);
+        assert_eq!(coverage.shapes.len(), 2);
+        assert_eq!(coverage.operators["AllNodesScan"], 3);
+        assert_eq!(coverage.operators["Filter"], 1);
+    }
+}